@@ -0,0 +1,10 @@
+//! Reference bots built on the game engine.
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+
+#[macro_use]
+extern crate log;
+
+mod mcts;
+
+pub use mcts::*;
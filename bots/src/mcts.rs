@@ -0,0 +1,500 @@
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use splendor_core::naive_actors::{GreedyActor, GreedyWeights};
+use splendor_core::{
+    ActorError, Color, ColorVec, DropTokensAction, GameSnapshot, PlayerAction, PlayerActor,
+    SelectNoblesAction, MAX_PLAYERS,
+};
+use splendor_engine::GameState;
+use std::fmt::{self, Debug};
+use std::time::{Duration, Instant};
+use tokio::task::spawn_blocking;
+
+/// The policy which plays the moves after the tree, and the token drops and noble visits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutPolicy {
+    /// Take a random legal action.
+    Random,
+    /// Take the best action of a [`GreedyActor`].
+    Greedy,
+}
+
+/// The config of a [`MctsActor`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MctsConfig {
+    /// The number of iterations of each tree.
+    pub iterations: Option<u32>,
+    /// The time limit of each search in milliseconds.
+    ///
+    /// The search stops at whichever budget runs out first, without any budget the default
+    /// number of iterations is used.
+    pub time_limit_ms: Option<u64>,
+    /// The exploration constant of UCT, the rewards are in `[0, 1]`.
+    pub exploration: f32,
+    /// The policy of the rollouts.
+    pub rollout: RolloutPolicy,
+    /// The weights of the greedy policy.
+    pub greedy_weights: GreedyWeights,
+    /// The number of trees searched in parallel, their root statistics are merged.
+    pub threads: usize,
+    /// Rollouts longer than this number of turns are scored by the points.
+    pub max_rollout_turns: usize,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            iterations: Some(1000),
+            time_limit_ms: None,
+            exploration: 0.7,
+            rollout: RolloutPolicy::Greedy,
+            greedy_weights: GreedyWeights::default(),
+            threads: 1,
+            max_rollout_turns: 200,
+        }
+    }
+}
+
+/// A player actor searching with information set Monte Carlo Tree Search.
+///
+/// Each iteration samples the cards the player cannot see with [`GameState::determinize`],
+/// and walks a single tree of the actions of all players (SO-ISMCTS). Token drops and noble
+/// visits are decided by the rollout policy.
+///
+/// The actor is deterministic under a seeded generator, unless a time limit is set. As a
+/// [`PlayerActor`] it searches on the blocking threads of the tokio runtime driving it.
+pub struct MctsActor<R> {
+    rng: R,
+    config: MctsConfig,
+    greedy: GreedyActor<SmallRng>,
+}
+
+impl<R: RngCore + Send + Sync> MctsActor<R> {
+    /// Create a new MCTS actor with the given config.
+    pub fn new(mut rng: R, config: MctsConfig) -> Self {
+        let greedy = GreedyActor::with_weights(
+            SmallRng::seed_from_u64(rng.next_u64()),
+            config.greedy_weights,
+        );
+        MctsActor {
+            rng,
+            config,
+            greedy,
+        }
+    }
+
+    /// Get the config of the actor.
+    pub fn config(&self) -> &MctsConfig {
+        &self.config
+    }
+
+    /// Search the best action of the current player.
+    pub fn search(&mut self, snapshot: &GameSnapshot) -> PlayerAction {
+        let actions = snapshot.legal_actions();
+        match self.seeds(&actions) {
+            Some(seeds) => search_trees(snapshot, &actions, &self.config, seeds),
+            None => actions[0],
+        }
+    }
+
+    /// Draw the seed of each tree, `None` if there is a single legal action to take.
+    fn seeds(&mut self, actions: &[PlayerAction]) -> Option<Vec<u64>> {
+        (actions.len() > 1).then(|| {
+            (0..self.config.threads.max(1))
+                .map(|_| self.rng.next_u64())
+                .collect()
+        })
+    }
+}
+
+/// Search a tree for each seed in parallel and take the most visited action of their roots.
+fn search_trees(
+    snapshot: &GameSnapshot,
+    actions: &[PlayerAction],
+    config: &MctsConfig,
+    seeds: Vec<u64>,
+) -> PlayerAction {
+    let deadline = config
+        .time_limit_ms
+        .map(|ms| Instant::now() + Duration::from_millis(ms));
+    let iterations = match (config.iterations, deadline) {
+        (None, None) => MctsConfig::default().iterations,
+        (iterations, _) => iterations,
+    };
+    let roots = std::thread::scope(|scope| {
+        let handles = seeds
+            .into_iter()
+            .map(|seed| {
+                scope.spawn(move || Search::new(seed, config).run(snapshot, iterations, deadline))
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let mut stats: Vec<(PlayerAction, u32, f32)> = vec![];
+    for (action, visits, reward) in roots.into_iter().flatten() {
+        match stats.iter_mut().find(|(a, _, _)| *a == action) {
+            Some(stat) => {
+                stat.1 += visits;
+                stat.2 += reward;
+            }
+            None => stats.push((action, visits, reward)),
+        }
+    }
+    let (action, visits, reward) = stats
+        .iter()
+        .copied()
+        .fold(
+            None,
+            |best: Option<(PlayerAction, u32, f32)>, stat| match best {
+                Some(best) if best.1 >= stat.1 => Some(best),
+                _ => Some(stat),
+            },
+        )
+        .unwrap_or((actions[0], 0, 0.0));
+    debug!(
+        "Player#{} searched {} iterations, best action {:?} with {} visits, value {:.3}",
+        snapshot.current_player,
+        stats.iter().map(|(_, visits, _)| visits).sum::<u32>(),
+        action,
+        visits,
+        reward / visits.max(1) as f32
+    );
+    action
+}
+
+impl<R> Debug for MctsActor<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MctsActor")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+// the searches run on the blocking threads of the runtime, not on its workers
+#[async_trait::async_trait]
+impl<R: RngCore + Send + Sync> PlayerActor for MctsActor<R> {
+    async fn get_action(&mut self, snapshot: GameSnapshot) -> Result<PlayerAction, ActorError> {
+        let actions = snapshot.legal_actions();
+        let Some(seeds) = self.seeds(&actions) else {
+            return Ok(actions[0]);
+        };
+        let config = self.config.clone();
+        Ok(spawn_blocking(move || search_trees(&snapshot, &actions, &config, seeds)).await?)
+    }
+
+    async fn drop_tokens(
+        &mut self,
+        snapshot: GameSnapshot,
+    ) -> Result<DropTokensAction, ActorError> {
+        let greedy = self.greedy.clone();
+        Ok(spawn_blocking(move || greedy.best_drop(&snapshot)).await?)
+    }
+
+    async fn select_noble(
+        &mut self,
+        snapshot: GameSnapshot,
+    ) -> Result<SelectNoblesAction, ActorError> {
+        let greedy = self.greedy.clone();
+        Ok(spawn_blocking(move || greedy.best_noble(&snapshot)).await?)
+    }
+}
+
+/// A node of the search tree.
+struct Node {
+    /// The action leading to this node, `None` for the root.
+    action: Option<PlayerAction>,
+    /// The player who took the action.
+    player: usize,
+    children: Vec<usize>,
+    visits: u32,
+    /// How many times the node could be selected, its action is not legal in every sample.
+    availability: u32,
+    /// The total reward of the player who took the action.
+    reward: f32,
+}
+
+impl Node {
+    fn new(action: Option<PlayerAction>, player: usize) -> Self {
+        Node {
+            action,
+            player,
+            children: vec![],
+            visits: 0,
+            availability: 1,
+            reward: 0.0,
+        }
+    }
+}
+
+/// A single tree search.
+struct Search<'a> {
+    config: &'a MctsConfig,
+    rng: SmallRng,
+    greedy: GreedyActor<SmallRng>,
+    nodes: Vec<Node>,
+}
+
+impl<'a> Search<'a> {
+    fn new(seed: u64, config: &'a MctsConfig) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let greedy =
+            GreedyActor::with_weights(SmallRng::seed_from_u64(rng.gen()), config.greedy_weights);
+        Search {
+            config,
+            rng,
+            greedy,
+            nodes: vec![],
+        }
+    }
+
+    /// Run the search, returns the action, visits and total reward of the root children.
+    fn run(
+        mut self,
+        snapshot: &GameSnapshot,
+        iterations: Option<u32>,
+        deadline: Option<Instant>,
+    ) -> Vec<(PlayerAction, u32, f32)> {
+        self.nodes.push(Node::new(None, snapshot.current_player));
+        let mut done = 0;
+        while iterations.map_or(true, |n| done < n) && deadline.map_or(true, |d| Instant::now() < d)
+        {
+            let mut state = GameState::determinize(snapshot, &mut self.rng);
+            self.iterate(&mut state);
+            done += 1;
+        }
+        self.nodes[0]
+            .children
+            .iter()
+            .map(|&child| {
+                let node = &self.nodes[child];
+                (node.action.unwrap(), node.visits, node.reward)
+            })
+            .collect()
+    }
+
+    fn iterate(&mut self, state: &mut GameState) {
+        let mut path = vec![0];
+        let mut node = 0;
+        let mut winners = None;
+
+        // selection and expansion
+        while winners.is_none() {
+            let player = state.current_player();
+            let actions = state.legal_actions();
+            let untried = actions
+                .iter()
+                .filter(|action| {
+                    !self.nodes[node]
+                        .children
+                        .iter()
+                        .any(|&child| self.nodes[child].action.as_ref() == Some(*action))
+                })
+                .copied()
+                .collect::<Vec<_>>();
+            if let Some(action) = untried.choose(&mut self.rng) {
+                let child = self.nodes.len();
+                self.nodes.push(Node::new(Some(*action), player));
+                self.nodes[node].children.push(child);
+                path.push(child);
+                winners = self.play(state, action);
+                break;
+            }
+
+            let children = self.nodes[node]
+                .children
+                .iter()
+                .copied()
+                .filter(|&child| actions.contains(&self.nodes[child].action.unwrap()))
+                .collect::<SmallVec<_, 64>>();
+            for &child in children.iter() {
+                self.nodes[child].availability += 1;
+            }
+            let exploration = self.config.exploration;
+            let (child, _) = children
+                .iter()
+                .map(|&child| {
+                    let n = &self.nodes[child];
+                    let visits = n.visits.max(1) as f32;
+                    let ucb = n.reward / visits
+                        + exploration * ((n.availability as f32).ln() / visits).sqrt();
+                    (child, ucb)
+                })
+                .fold(
+                    None,
+                    |best: Option<(usize, f32)>, (child, ucb)| match best {
+                        Some((_, best_ucb)) if best_ucb >= ucb => best,
+                        _ => Some((child, ucb)),
+                    },
+                )
+                .unwrap();
+            let action = self.nodes[child].action.unwrap();
+            path.push(child);
+            node = child;
+            winners = self.play(state, &action);
+        }
+
+        // rollout
+        let mut turns = 0;
+        while winners.is_none() && turns < self.config.max_rollout_turns {
+            let action = self.rollout_action(state);
+            winners = self.play(state, &action);
+            turns += 1;
+        }
+        let rewards = rewards(state, winners.as_deref());
+
+        // backpropagation
+        for node in path {
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+            if node.action.is_some() {
+                node.reward += rewards[node.player];
+            }
+        }
+    }
+
+    /// Play a turn of the current player, returns the winners if the game ended.
+    fn play(
+        &mut self,
+        state: &mut GameState,
+        action: &PlayerAction,
+    ) -> Option<SmallVec<usize, MAX_PLAYERS>> {
+        state.apply_action(action).unwrap();
+        if state.needs_drop() {
+            let drop_tokens = self.rollout_drop(state);
+            state.drop_tokens(&drop_tokens).unwrap();
+        }
+        let noble_visits = state.noble_visits();
+        if !noble_visits.is_empty() {
+            let select_noble = if noble_visits.len() > 1 {
+                self.rollout_noble(state, &noble_visits)
+            } else {
+                SelectNoblesAction(noble_visits[0])
+            };
+            state.visit_noble(&select_noble).unwrap();
+        }
+        state.end_turn()
+    }
+
+    fn rollout_action(&mut self, state: &GameState) -> PlayerAction {
+        match self.config.rollout {
+            RolloutPolicy::Random => *state.legal_actions().choose(&mut self.rng).unwrap(),
+            RolloutPolicy::Greedy => self.greedy.best_action(&state.snapshot()),
+        }
+    }
+
+    fn rollout_drop(&mut self, state: &GameState) -> DropTokensAction {
+        match self.config.rollout {
+            RolloutPolicy::Random => {
+                let snapshot = state.snapshot();
+                let mut tokens = snapshot.current_player().tokens;
+                let mut drops = ColorVec::empty();
+                while tokens.total() > 10 {
+                    let colors = (0usize..6)
+                        .map(|i| Color::try_from(i).unwrap())
+                        .filter(|c| tokens.get(*c) > 0)
+                        .collect::<SmallVec<_, 6>>();
+                    let color = *colors.choose(&mut self.rng).unwrap();
+                    drops.add(color, 1);
+                    tokens.sub(color, 1);
+                }
+                DropTokensAction(drops)
+            }
+            RolloutPolicy::Greedy => self.greedy.best_drop(&state.snapshot()),
+        }
+    }
+
+    fn rollout_noble(&mut self, state: &GameState, noble_visits: &[usize]) -> SelectNoblesAction {
+        match self.config.rollout {
+            RolloutPolicy::Random => {
+                SelectNoblesAction(*noble_visits.choose(&mut self.rng).unwrap())
+            }
+            RolloutPolicy::Greedy => self.greedy.best_noble(&state.snapshot()),
+        }
+    }
+}
+
+/// The reward of each player, the winners share a reward of `1`.
+///
+/// Unfinished games are won by the players with the most points, and stuck games are lost by
+/// everyone.
+fn rewards(state: &GameState, winners: Option<&[usize]>) -> SmallVec<f32, MAX_PLAYERS> {
+    let points = state.points();
+    let leaders = match winners {
+        Some(winners) => winners
+            .iter()
+            .copied()
+            .collect::<SmallVec<_, MAX_PLAYERS>>(),
+        None => {
+            let max = points.iter().copied().max().unwrap_or(0);
+            (0..points.len()).filter(|&i| points[i] == max).collect()
+        }
+    };
+    let mut rewards = SmallVec::from_elem(0.0, points.len());
+    for &leader in leaders.iter() {
+        rewards[leader] = 1.0 / leaders.len() as f32;
+    }
+    rewards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use splendor_core::naive_actors::RandomActor;
+    use splendor_engine::GameContext;
+
+    fn config(iterations: u32, threads: usize) -> MctsConfig {
+        MctsConfig {
+            iterations: Some(iterations),
+            threads,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let mut rng = SmallRng::seed_from_u64(42);
+        let snapshot = GameState::with_rng(&mut rng, 2).snapshot();
+        let search = |threads| {
+            MctsActor::new(SmallRng::seed_from_u64(7), config(20, threads)).search(&snapshot)
+        };
+        assert_eq!(search(1), search(1));
+        assert_eq!(search(2), search(2));
+    }
+
+    #[tokio::test]
+    async fn test_beats_random() {
+        for seed in 0..2 {
+            let mcts = seed as usize % 2;
+            let config = MctsConfig {
+                max_rollout_turns: 10,
+                ..config(10, 1)
+            };
+            let actors = (0..2)
+                .map(|i| {
+                    let rng = SmallRng::seed_from_u64(seed + i as u64);
+                    if i == mcts {
+                        Box::new(MctsActor::new(rng, config.clone())) as Box<dyn PlayerActor>
+                    } else {
+                        Box::new(RandomActor::new(rng)) as _
+                    }
+                })
+                .collect();
+            let mut game = GameContext::with_rng(&mut SmallRng::seed_from_u64(seed), actors);
+            let winners = loop {
+                if let Some(winners) = game.step().await.unwrap() {
+                    break winners;
+                }
+            };
+            assert_eq!(winners.as_slice(), &[mcts]);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! The environment contract between the supervisor and the game server.
+//!
+//! The supervisor builds the environment of a game server with [`ServerConfig::to_env`],
+//! and the game server parses it back with [`ServerConfig::from_env`].
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// The minimum number of players in a game.
+pub const MIN_PLAYERS: usize = 2;
+/// The maximum number of players in a game.
+pub const MAX_PLAYERS: usize = 4;
+
+/// The prefix of all variables of the contract.
+///
+/// Any variable with this prefix which is not part of the contract is rejected.
+pub const ENV_PREFIX: &str = "SPLENDOR_";
+
+/// The id of the game.
+pub const GAME_ID: &str = "SPLENDOR_GAME_ID";
+/// The number of players.
+pub const N_PLAYERS: &str = "SPLENDOR_N_PLAYERS";
+/// The seed of the game, optional.
+pub const SEED: &str = "SPLENDOR_SEED";
+/// The timeout of each step in seconds.
+pub const STEP_TIMEOUT: &str = "SPLENDOR_STEP_TIMEOUT";
+/// The directory where the player secrets are written.
+pub const SECRETS_PATH: &str = "SPLENDOR_SECRETS_PATH";
+/// The address the game server listens on.
+pub const SERVER_ADDR: &str = "SPLENDOR_SERVER_ADDR";
+/// The path of the supervisor UDS socket, optional.
+pub const SUPERVISOR_SOCKET: &str = "SPLENDOR_SUPERVISOR_SOCKET";
+
+/// Variables of older game servers, rejected with the variable replacing them,
+/// so that a setting is never silently ignored.
+const LEGACY_VARS: [(&str, &str); 2] = [("SEED", SEED), ("RANDOM_SEED", SEED)];
+
+const KNOWN_VARS: [&str; 7] = [
+    GAME_ID,
+    N_PLAYERS,
+    SEED,
+    STEP_TIMEOUT,
+    SECRETS_PATH,
+    SERVER_ADDR,
+    SUPERVISOR_SOCKET,
+];
+
+/// Errors of building or parsing a [`ServerConfig`].
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// A required variable is not set.
+    #[error("missing environment variable {0}")]
+    Missing(&'static str),
+    /// A variable cannot be parsed.
+    #[error("invalid value of {name}: {value:?}")]
+    Invalid {
+        /// The name of the variable.
+        name: &'static str,
+        /// The value of the variable.
+        value: String,
+    },
+    /// A variable with the [`ENV_PREFIX`] is not part of the contract.
+    #[error("unknown environment variable {0}")]
+    Unknown(String),
+    /// A variable of older game servers is set.
+    #[error("legacy environment variable {name} is not supported, use {replacement} instead")]
+    Legacy {
+        /// The name of the legacy variable.
+        name: &'static str,
+        /// The name of the variable replacing it.
+        replacement: &'static str,
+    },
+    /// The number of players is out of range.
+    #[error(
+        "invalid number of players {0}, must be between {} and {}",
+        MIN_PLAYERS,
+        MAX_PLAYERS
+    )]
+    Players(usize),
+    /// The config is parsed but not valid.
+    #[error("invalid config: {0}")]
+    Validation(&'static str),
+}
+
+/// The config of a game server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerConfig {
+    /// The id of the game, used when reporting to the supervisor.
+    pub game_id: Uuid,
+    /// The number of players, from [`MIN_PLAYERS`] to [`MAX_PLAYERS`].
+    pub n_players: usize,
+    /// The seed to deterministically reproduce the game, a random game is played if unset.
+    pub seed: Option<u64>,
+    /// The timeout of each step in seconds.
+    pub step_timeout: u64,
+    /// The secret of each player is written to `{secrets_path}/player{idx}/secret`.
+    pub secrets_path: PathBuf,
+    /// The address the game server listens on.
+    pub server_addr: SocketAddr,
+    /// The path of the supervisor UDS socket, required by supervised game servers.
+    pub supervisor_socket: Option<PathBuf>,
+}
+
+impl ServerConfig {
+    /// Parse the config from the environment of the current process.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        }))
+    }
+
+    /// Parse the config from `(name, value)` pairs, variables without the [`ENV_PREFIX`]
+    /// are ignored, except the legacy ones which are rejected.
+    pub fn from_vars<I>(vars: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut known = HashMap::new();
+        for (name, value) in vars {
+            if let Some((name, replacement)) =
+                LEGACY_VARS.into_iter().find(|(legacy, _)| *legacy == name)
+            {
+                return Err(ConfigError::Legacy { name, replacement });
+            }
+            if name.starts_with(ENV_PREFIX) {
+                known.insert(name, value);
+            }
+        }
+        let vars = known;
+        if let Some(name) = vars
+            .keys()
+            .find(|name| !KNOWN_VARS.contains(&name.as_str()))
+        {
+            return Err(ConfigError::Unknown(name.clone()));
+        }
+        let config = Self {
+            game_id: required(&vars, GAME_ID)?,
+            n_players: required(&vars, N_PLAYERS)?,
+            seed: optional(&vars, SEED)?,
+            step_timeout: required(&vars, STEP_TIMEOUT)?,
+            secrets_path: required(&vars, SECRETS_PATH)?,
+            server_addr: required(&vars, SERVER_ADDR)?,
+            supervisor_socket: optional(&vars, SUPERVISOR_SOCKET)?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the values of the config.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&self.n_players) {
+            return Err(ConfigError::Players(self.n_players));
+        }
+        if self.step_timeout == 0 {
+            return Err(ConfigError::Validation("step timeout must be positive"));
+        }
+        Ok(())
+    }
+
+    /// Build the environment variables of the config.
+    pub fn to_env(&self) -> Result<Vec<(&'static str, String)>, ConfigError> {
+        self.validate()?;
+        let mut env = vec![
+            (GAME_ID, self.game_id.to_string()),
+            (N_PLAYERS, self.n_players.to_string()),
+            (STEP_TIMEOUT, self.step_timeout.to_string()),
+            (SECRETS_PATH, self.secrets_path.display().to_string()),
+            (SERVER_ADDR, self.server_addr.to_string()),
+        ];
+        if let Some(seed) = self.seed {
+            env.push((SEED, seed.to_string()));
+        }
+        if let Some(socket) = &self.supervisor_socket {
+            env.push((SUPERVISOR_SOCKET, socket.display().to_string()));
+        }
+        Ok(env)
+    }
+}
+
+fn optional<T: FromStr>(
+    vars: &HashMap<String, String>,
+    name: &'static str,
+) -> Result<Option<T>, ConfigError> {
+    vars.get(name)
+        .map(|value| {
+            value.parse().map_err(|_| ConfigError::Invalid {
+                name,
+                value: value.clone(),
+            })
+        })
+        .transpose()
+}
+
+fn required<T: FromStr>(
+    vars: &HashMap<String, String>,
+    name: &'static str,
+) -> Result<T, ConfigError> {
+    optional(vars, name)?.ok_or(ConfigError::Missing(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ServerConfig {
+        ServerConfig {
+            game_id: Uuid::new_v4(),
+            n_players: 4,
+            seed: Some(42),
+            step_timeout: 300,
+            secrets_path: PathBuf::from("/app/secrets"),
+            server_addr: "0.0.0.0:8080".parse().unwrap(),
+            supervisor_socket: Some(PathBuf::from("/var/run/splendor/supervisor.sock")),
+        }
+    }
+
+    fn vars(config: &ServerConfig) -> Vec<(String, String)> {
+        config
+            .to_env()
+            .unwrap()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .chain([("PATH".to_string(), "/usr/bin".to_string())])
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let config = config();
+        assert_eq!(ServerConfig::from_vars(vars(&config)).unwrap(), config);
+
+        let config = ServerConfig {
+            n_players: 2,
+            seed: None,
+            supervisor_socket: None,
+            ..config
+        };
+        assert_eq!(ServerConfig::from_vars(vars(&config)).unwrap(), config);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut env = vars(&config());
+        env.retain(|(name, _)| name != STEP_TIMEOUT);
+        assert!(matches!(
+            ServerConfig::from_vars(env),
+            Err(ConfigError::Missing(STEP_TIMEOUT))
+        ));
+
+        let mut env = vars(&config());
+        env.push(("SPLENDOR_RANDOM_SEED".to_string(), "42".to_string()));
+        assert!(matches!(
+            ServerConfig::from_vars(env),
+            Err(ConfigError::Unknown(name)) if name == "SPLENDOR_RANDOM_SEED"
+        ));
+
+        let mut env = vars(&config());
+        env.retain(|(name, _)| name != SEED);
+        env.push((SEED.to_string(), "-1".to_string()));
+        assert!(matches!(
+            ServerConfig::from_vars(env),
+            Err(ConfigError::Invalid { name: SEED, .. })
+        ));
+
+        for n_players in [1, 5] {
+            let config = ServerConfig {
+                n_players,
+                ..config()
+            };
+            assert!(matches!(config.to_env(), Err(ConfigError::Players(n)) if n == n_players));
+        }
+
+        for legacy in ["SEED", "RANDOM_SEED"] {
+            let mut env = vars(&config());
+            env.push((legacy.to_string(), "42".to_string()));
+            let error = ServerConfig::from_vars(env).unwrap_err();
+            assert!(matches!(
+                error,
+                ConfigError::Legacy { name, replacement: SEED } if name == legacy
+            ));
+            assert!(error.to_string().contains(SEED), "{error}");
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use splendor_core::{
+    ActionRequest, ActionType, DropTokensAction, PlayerAction, SelectNoblesAction,
+};
+use splendor_engine::{GameState, InvalidActionError};
+
+/// A response which does not conform to the protocol.
+#[derive(Debug, thiserror::Error)]
+pub enum ConformanceError {
+    /// The response cannot be deserialized into the expected type.
+    #[error("malformed response: {0}")]
+    Malformed(#[from] serde_json::Error),
+    /// The response is deserialized, but serializes back into a different JSON.
+    #[error("response is not canonical, expected {expected}")]
+    NotCanonical {
+        /// The JSON serialized by the Rust types.
+        expected: String,
+    },
+    /// The response breaks the rules.
+    #[error("illegal response: {0}")]
+    Illegal(#[from] InvalidActionError),
+    /// The actor does not respond in time.
+    #[error("no response in time")]
+    Timeout,
+    /// The actor closes the connection.
+    #[error("connection closed")]
+    Closed,
+}
+
+/// Check the response of a request is well-formed, canonical and legal.
+///
+/// The response is replayed on a state determinized from the snapshot of the request,
+/// so the legality follows the rules of the game engine.
+pub fn check_response(request: &ActionRequest, response: &str) -> Result<(), ConformanceError> {
+    let mut state = GameState::determinize(&request.snapshot, &mut SmallRng::seed_from_u64(0));
+    match request.ty {
+        ActionType::GetAction => state.apply_action(&parse::<PlayerAction>(response)?)?,
+        ActionType::DropTokens => state.drop_tokens(&parse::<DropTokensAction>(response)?)?,
+        ActionType::SelectNoble => {
+            let action = parse::<SelectNoblesAction>(response)?;
+            // checked before visiting, an index out of range must not panic the harness
+            if !state.noble_visits().contains(&action.0) {
+                return Err(InvalidActionError {
+                    player: state.current_player(),
+                    reason: "noble cannot be visited",
+                }
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse<T: Serialize + DeserializeOwned>(response: &str) -> Result<T, ConformanceError> {
+    let value: serde_json::Value = serde_json::from_str(response)?;
+    let parsed: T = serde_json::from_value(value.clone())?;
+    let canonical = serde_json::to_value(&parsed).expect("action serialization should not fail");
+    if canonical != value {
+        return Err(ConformanceError::NotCanonical {
+            expected: canonical.to_string(),
+        });
+    }
+    Ok(parsed)
+}
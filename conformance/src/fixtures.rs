@@ -0,0 +1,305 @@
+use crate::HarnessError;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use splendor_core::{
+    ActionRequest, ActionType, BuyCardAction, BuyCardSource, CardView, Color, ColorVec,
+    DropTokensAction, GameSnapshot, PlayerAction, ReserveCardAction, SelectNoblesAction,
+    TakeTokenAction, Tier,
+};
+use splendor_engine::GameState;
+use std::path::Path;
+
+/// A golden request of the protocol, with a response conforming to it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Fixture {
+    /// The name of the fixture, also the name of its file.
+    pub name: String,
+    /// What the fixture covers.
+    pub description: String,
+    /// The request sent to the actor.
+    pub request: ActionRequest,
+    /// A response conforming to the request.
+    pub response: serde_json::Value,
+}
+
+impl Fixture {
+    fn new<T: Serialize>(
+        name: &str,
+        description: &str,
+        ty: ActionType,
+        snapshot: GameSnapshot,
+        response: T,
+    ) -> Self {
+        Fixture {
+            name: name.to_string(),
+            description: description.to_string(),
+            request: ActionRequest { ty, snapshot },
+            response: serde_json::to_value(response)
+                .expect("response serialization should not fail"),
+        }
+    }
+
+    /// Get the JSON of the fixture file.
+    pub fn to_json(&self) -> String {
+        let mut json =
+            serde_json::to_string_pretty(self).expect("fixture serialization should not fail");
+        json.push('\n');
+        json
+    }
+}
+
+/// The seed of the game all the fixtures start from.
+const SEED: u64 = 42;
+
+/// Get a snapshot of a new two players game, the fixtures are edited from it.
+fn new_game() -> GameSnapshot {
+    GameState::with_rng(&mut SmallRng::seed_from_u64(SEED), 2).snapshot()
+}
+
+/// Generate the fixtures from the Rust types.
+///
+/// Every variant of the responses is covered, also the edge cases of the snapshots,
+/// such as invisible reserved cards and empty tiers.
+pub fn generate_fixtures() -> Vec<Fixture> {
+    let mut fixtures = vec![];
+
+    fixtures.push(Fixture::new(
+        "take_three_different",
+        "Take three tokens of different colors at the start of a game.",
+        ActionType::GetAction,
+        new_game(),
+        PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(ColorVec::new(
+            1, 1, 1, 0, 0, 0,
+        ))),
+    ));
+
+    fixtures.push(Fixture::new(
+        "take_two_same",
+        "Take two tokens of the same color.",
+        ActionType::GetAction,
+        new_game(),
+        PlayerAction::TakeTokens(TakeTokenAction::TwoSame(ColorVec::new(0, 0, 0, 2, 0, 0))),
+    ));
+
+    let mut snapshot = new_game();
+    snapshot.tokens = ColorVec::new(0, 0, 3, 2, 0, 5);
+    fixtures.push(Fixture::new(
+        "take_two_different",
+        "Take two tokens of different colors, only two colors are left in the bank.",
+        ActionType::GetAction,
+        snapshot,
+        PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(ColorVec::new(
+            0, 0, 1, 1, 0, 0,
+        ))),
+    ));
+
+    fixtures.push(Fixture::new(
+        "reserve_revealed",
+        "Reserve a revealed card of tier 2.",
+        ActionType::GetAction,
+        new_game(),
+        PlayerAction::ReserveCard(ReserveCardAction {
+            tier: Tier::II,
+            idx: Some(1),
+        }),
+    ));
+
+    fixtures.push(Fixture::new(
+        "reserve_from_pool",
+        "Reserve a card from the pool of tier 3, the index is null.",
+        ActionType::GetAction,
+        new_game(),
+        PlayerAction::ReserveCard(ReserveCardAction {
+            tier: Tier::III,
+            idx: None,
+        }),
+    ));
+
+    let mut snapshot = new_game();
+    let card = snapshot.card_pool.revealed[Tier::I as usize][0];
+    let mut tokens = card.requires;
+    let color = (0usize..5)
+        .map(|i| Color::try_from(i).unwrap())
+        .find(|c| tokens.get(*c) > 0)
+        .unwrap();
+    tokens.sub(color, 1);
+    tokens.add(Color::Yellow, 1);
+    snapshot.tokens = snapshot.tokens.saturating_sub(&tokens);
+    snapshot.players[0].tokens = tokens;
+    let uses = snapshot.players[0].payment(&card).unwrap();
+    fixtures.push(Fixture::new(
+        "buy_revealed_with_gold",
+        "Buy a revealed card of tier 1, paying a missing token with a gold token.",
+        ActionType::GetAction,
+        snapshot,
+        PlayerAction::BuyCard(BuyCardAction {
+            source: BuyCardSource::Revealed {
+                tier: Tier::I,
+                idx: 0,
+            },
+            uses,
+        }),
+    ));
+
+    let mut snapshot = new_game();
+    let card = snapshot.card_pool.revealed[Tier::I as usize].remove(0);
+    let player = &mut snapshot.players[0];
+    player.reserved_cards.push(CardView::Visible(card));
+    player.tokens = card.requires;
+    snapshot.tokens = snapshot.tokens.saturating_sub(&card.requires);
+    let uses = snapshot.players[0].payment(&card).unwrap();
+    fixtures.push(Fixture::new(
+        "buy_reserved",
+        "Buy a reserved card, only three cards of tier 1 are revealed.",
+        ActionType::GetAction,
+        snapshot,
+        PlayerAction::BuyCard(BuyCardAction {
+            source: BuyCardSource::Reserved(0),
+            uses,
+        }),
+    ));
+
+    let mut snapshot = new_game();
+    let opponent = &mut snapshot.players[1];
+    opponent.reserved_cards.push(CardView::Invisible(Tier::II));
+    opponent.reserved_cards.push(CardView::Invisible(Tier::III));
+    opponent.tokens = ColorVec::new(0, 0, 0, 0, 0, 2);
+    snapshot.tokens.sub(Color::Yellow, 2);
+    snapshot.card_pool.remaining[Tier::II as usize] -= 1;
+    snapshot.card_pool.remaining[Tier::III as usize] -= 1;
+    fixtures.push(Fixture::new(
+        "invisible_reserved_cards",
+        "The opponent reserved two cards from the pools, which are invisible.",
+        ActionType::GetAction,
+        snapshot,
+        PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(ColorVec::new(
+            0, 0, 1, 1, 1, 0,
+        ))),
+    ));
+
+    let mut snapshot = new_game();
+    snapshot.card_pool.revealed[Tier::III as usize].clear();
+    snapshot.card_pool.remaining[Tier::III as usize] = 0;
+    snapshot.card_pool.remaining[Tier::II as usize] = 0;
+    fixtures.push(Fixture::new(
+        "empty_tiers",
+        "No card of tier 3 is left, and the pool of tier 2 is empty.",
+        ActionType::GetAction,
+        snapshot,
+        PlayerAction::ReserveCard(ReserveCardAction {
+            tier: Tier::II,
+            idx: Some(0),
+        }),
+    ));
+
+    let mut snapshot = new_game();
+    snapshot.tokens = ColorVec::empty();
+    let revealed = &mut snapshot.card_pool.revealed[Tier::III as usize];
+    let reserved = revealed.drain(..3).collect::<Vec<_>>();
+    snapshot.players[0]
+        .reserved_cards
+        .extend(reserved.into_iter().map(CardView::Visible));
+    fixtures.push(Fixture::new(
+        "nop",
+        "No token is left and the player reserved three cards, so nothing can be done.",
+        ActionType::GetAction,
+        snapshot,
+        PlayerAction::Nop,
+    ));
+
+    let mut snapshot = new_game();
+    let tokens = ColorVec::new(3, 3, 3, 2, 0, 1);
+    snapshot.tokens = snapshot.tokens.saturating_sub(&tokens);
+    snapshot.players[0].tokens = tokens;
+    fixtures.push(Fixture::new(
+        "drop_tokens",
+        "The player has 12 tokens and drops 2 of them.",
+        ActionType::DropTokens,
+        snapshot,
+        DropTokensAction(ColorVec::new(1, 0, 0, 0, 0, 1)),
+    ));
+
+    let mut snapshot = new_game();
+    let mut bonus = ColorVec::empty();
+    for noble in snapshot.nobles[..2].iter() {
+        for color in (0usize..5).map(|i| Color::try_from(i).unwrap()) {
+            bonus.set(color, bonus.get(color).max(noble.requires.get(color)));
+        }
+    }
+    snapshot.players[0].development_cards.bonus = bonus;
+    fixtures.push(Fixture::new(
+        "select_noble",
+        "The player can visit two nobles and selects the second one, only the bonus is set.",
+        ActionType::SelectNoble,
+        snapshot,
+        SelectNoblesAction(1),
+    ));
+
+    fixtures
+}
+
+/// Load the fixtures of a directory, sorted by the name.
+pub fn load_fixtures(dir: &Path) -> Result<Vec<Fixture>, HarnessError> {
+    let mut fixtures = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            fixtures.push(serde_json::from_str::<Fixture>(&std::fs::read_to_string(
+                path,
+            )?)?);
+        }
+    }
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fixtures)
+}
+
+/// Write the fixtures into a directory, one file for each.
+pub fn write_fixtures(dir: &Path, fixtures: &[Fixture]) -> Result<(), HarnessError> {
+    std::fs::create_dir_all(dir)?;
+    for fixture in fixtures {
+        std::fs::write(
+            dir.join(format!("{}.json", fixture.name)),
+            fixture.to_json(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check_response;
+
+    #[test]
+    fn test_fixtures_up_to_date() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let fixtures = generate_fixtures();
+        for fixture in fixtures.iter() {
+            let path = dir.join(format!("{}.json", fixture.name));
+            let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+            assert!(
+                checked_in.replace("\r\n", "\n") == fixture.to_json(),
+                "{} is out of date, run `cargo run -p splendor-conformance -- generate`",
+                path.display()
+            );
+        }
+        assert_eq!(load_fixtures(&dir).unwrap().len(), fixtures.len());
+    }
+
+    #[test]
+    fn test_golden_responses() {
+        for fixture in generate_fixtures() {
+            let response = fixture.response.to_string();
+            if let Err(e) = check_response(&fixture.request, &response) {
+                panic!("{}: {e}", fixture.name);
+            }
+            if fixture.name == "nop" {
+                assert_eq!(
+                    fixture.request.snapshot.legal_actions(),
+                    vec![PlayerAction::Nop]
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{check_response, ConformanceError, Fixture};
+use futures_util::{SinkExt, StreamExt};
+use std::borrow::Cow;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::WebSocketStream;
+
+/// An error which stops the harness.
+#[derive(Debug, thiserror::Error)]
+pub enum HarnessError {
+    /// An error occurred while reading or writing files.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// An error occurred while (de)serializing JSON.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// An error occurred while using the WebSocket.
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[from] tungstenite::Error),
+    /// The actor does not connect in time.
+    #[error("the actor did not connect in time")]
+    ConnectTimeout,
+    /// The actor authenticates with a wrong secret.
+    #[error("the actor sent a wrong secret")]
+    Secret,
+}
+
+/// The result of a fixture replayed against an actor.
+#[derive(Debug)]
+pub struct Outcome {
+    /// The name of the fixture.
+    pub name: String,
+    /// Whether the response conforms to the protocol.
+    pub result: Result<(), ConformanceError>,
+}
+
+/// Replay the fixtures against the first actor connecting to the listener with the secret.
+///
+/// The actor talks the same WebSocket protocol as with the game server: it sends the secret
+/// first, then answers every request. A non-conforming response does not stop the replay,
+/// but the remaining fixtures fail once the actor times out or closes the connection.
+pub async fn replay(
+    listener: &TcpListener,
+    secret: &str,
+    fixtures: &[Fixture],
+    timeout: Duration,
+) -> Result<Vec<Outcome>, HarnessError> {
+    let (stream, addr) = tokio::time::timeout(timeout, listener.accept())
+        .await
+        .map_err(|_| HarnessError::ConnectTimeout)??;
+    let mut ws_stream = tokio_tungstenite::accept_async(stream).await?;
+    info!("Actor connected from: {addr}");
+    match tokio::time::timeout(timeout, recv_text(&mut ws_stream)).await {
+        Ok(Some(text)) if text == secret => {}
+        Ok(Some(_)) => return Err(HarnessError::Secret),
+        Ok(None) | Err(_) => return Err(HarnessError::ConnectTimeout),
+    }
+
+    let mut outcomes = vec![];
+    let mut closed = false;
+    for fixture in fixtures {
+        let result = if closed {
+            Err(ConformanceError::Closed)
+        } else {
+            let request = serde_json::to_string(&fixture.request)?;
+            match ws_stream.send(Message::Text(request)).await {
+                Ok(()) => match tokio::time::timeout(timeout, recv_text(&mut ws_stream)).await {
+                    Ok(Some(response)) => check_response(&fixture.request, &response),
+                    Ok(None) => Err(ConformanceError::Closed),
+                    Err(_) => Err(ConformanceError::Timeout),
+                },
+                Err(_) => Err(ConformanceError::Closed),
+            }
+        };
+        // a late response would be taken as the response of the next fixture
+        closed |= matches!(
+            result,
+            Err(ConformanceError::Closed | ConformanceError::Timeout)
+        );
+        outcomes.push(Outcome {
+            name: fixture.name.clone(),
+            result,
+        });
+    }
+
+    if !closed {
+        let _ = ws_stream
+            .close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: Cow::Borrowed("conformance finished"),
+            }))
+            .await;
+    }
+    Ok(outcomes)
+}
+
+/// Receive the next text message, `None` if the connection is closed.
+async fn recv_text(ws_stream: &mut WebSocketStream<TcpStream>) -> Option<String> {
+    while let Some(msg) = ws_stream.next().await {
+        match msg {
+            Ok(Message::Text(text)) => return Some(text),
+            Ok(Message::Close(_)) | Err(_) => return None,
+            Ok(_) => continue,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_fixtures;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+    use splendor_core::naive_actors::RandomActor;
+    use splendor_sdk::WebSocketActorClient;
+
+    #[tokio::test]
+    async fn test_replay_rust_sdk() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let actor = tokio::spawn(async move {
+            let actor = RandomActor::new(SmallRng::seed_from_u64(42));
+            let mut client = WebSocketActorClient::new(url, "secret", actor)
+                .await
+                .unwrap();
+            client.run().await.unwrap();
+        });
+
+        let fixtures = generate_fixtures();
+        let outcomes = replay(&listener, "secret", &fixtures, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(outcomes.len(), fixtures.len());
+        for outcome in outcomes {
+            assert!(
+                outcome.result.is_ok(),
+                "{}: {:?}",
+                outcome.name,
+                outcome.result
+            );
+        }
+        actor.await.unwrap();
+    }
+}
@@ -0,0 +1,17 @@
+//! # Splendor Protocol Conformance
+//!
+//! Golden requests of the JSON protocol, and a harness replaying them against any actor
+//! through the WebSocket protocol of the game server.
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+
+#[macro_use]
+extern crate log;
+
+mod check;
+mod fixtures;
+mod harness;
+
+pub use check::*;
+pub use fixtures::*;
+pub use harness::*;
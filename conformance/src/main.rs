@@ -0,0 +1,104 @@
+use rand::distributions::{Alphanumeric, DistString};
+use splendor_conformance::{generate_fixtures, load_fixtures, replay, write_fixtures};
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::{ExitCode, Stdio};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::process::Command;
+
+const USAGE: &str = "\
+usage: splendor-conformance generate [DIR]
+       splendor-conformance run [--fixtures DIR] [--timeout SECS] -- COMMAND [ARGS...]
+
+generate  write the fixtures generated from the Rust types
+run       replay the fixtures against an actor, which is started by COMMAND
+          with the RPC_URL and CLIENT_SECRET variables";
+
+fn default_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+#[tokio::main]
+async fn main() -> Result<ExitCode, Box<dyn Error>> {
+    pretty_env_logger::init();
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("generate") => {
+            let dir = args.get(1).map(PathBuf::from).unwrap_or_else(default_dir);
+            write_fixtures(&dir, &generate_fixtures())?;
+            println!("fixtures written to {}", dir.display());
+            Ok(ExitCode::SUCCESS)
+        }
+        Some("run") => run(&args[1..]).await,
+        _ => {
+            eprintln!("{USAGE}");
+            Ok(ExitCode::from(2))
+        }
+    }
+}
+
+async fn run(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut dir = default_dir();
+    let mut timeout = Duration::from_secs(10);
+    let mut args = args.iter();
+    let command = loop {
+        let Some(arg) = args.next() else {
+            return Err(format!("missing the actor command\n{USAGE}").into());
+        };
+        if arg == "--" {
+            break args.cloned().collect::<Vec<_>>();
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value of {arg}\n{USAGE}"))?;
+        match arg.as_str() {
+            "--fixtures" => dir = PathBuf::from(value),
+            "--timeout" => timeout = Duration::from_secs(value.parse()?),
+            _ => return Err(format!("unknown argument {arg}\n{USAGE}").into()),
+        }
+    };
+    let (program, program_args) = command
+        .split_first()
+        .ok_or_else(|| format!("missing the actor command\n{USAGE}"))?;
+
+    let fixtures = load_fixtures(&dir)?;
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let secret = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let secret_dir =
+        std::env::temp_dir().join(format!("splendor-conformance-{}", std::process::id()));
+    tokio::fs::create_dir_all(&secret_dir).await?;
+    let secret_path = secret_dir.join("secret");
+    tokio::fs::write(&secret_path, &secret).await?;
+
+    let mut actor = Command::new(program)
+        .args(program_args)
+        .env("RPC_URL", format!("ws://{}", listener.local_addr()?))
+        .env("CLIENT_SECRET", &secret_path)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    let outcomes = replay(&listener, &secret, &fixtures, timeout).await;
+    if tokio::time::timeout(timeout, actor.wait()).await.is_err() {
+        actor.kill().await?;
+    }
+    tokio::fs::remove_dir_all(&secret_dir).await?;
+
+    let mut failed = 0;
+    for outcome in outcomes? {
+        match outcome.result {
+            Ok(()) => println!("ok      {}", outcome.name),
+            Err(e) => {
+                failed += 1;
+                println!("FAILED  {}: {e}", outcome.name);
+            }
+        }
+    }
+    println!("{} passed, {failed} failed", fixtures.len() - failed);
+    Ok(if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 /// An enum to represent the actions a player can take.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum ActionType {
     /// Get the action to take.
@@ -15,14 +16,17 @@ pub enum ActionType {
 
 /// A struct to represent the drop tokens action.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DropTokensAction(pub ColorVec);
 
 /// A struct to represent the select nobles action.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SelectNoblesAction(pub usize);
 
 /// An enum to represent the actions a player can take.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", content = "action")]
 #[serde(rename_all = "snake_case")]
 pub enum PlayerAction {
@@ -45,6 +49,7 @@ impl PlayerAction {
 
 /// An enum to represent the take tokens action.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", content = "tokens")]
 #[serde(rename_all = "snake_case")]
 pub enum TakeTokenAction {
@@ -56,6 +61,7 @@ pub enum TakeTokenAction {
 
 /// A struct to represent the buy card action.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BuyCardAction {
     /// Source of the card
     pub source: BuyCardSource,
@@ -65,6 +71,7 @@ pub struct BuyCardAction {
 
 /// An enum to represent the source of the card to buy.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", content = "location")]
 #[serde(rename_all = "snake_case")]
 pub enum BuyCardSource {
@@ -81,6 +88,7 @@ pub enum BuyCardSource {
 
 /// A struct to represent the reserve card action.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ReserveCardAction {
     /// The source tier of the reserved card.
     pub tier: Tier,
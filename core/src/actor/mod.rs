@@ -33,6 +33,7 @@ impl Display for ActorError {
 ///
 /// Might be used to request an action from a player.
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ActionRequest {
     /// The type of the action.
     #[serde(rename = "type")]
@@ -1,12 +1,19 @@
 use super::*;
 use crate::{
-    BuyCardAction, BuyCardSource, Color, ColorVec, ReserveCardAction, TakeTokenAction, Tier,
+    BuyCardAction, BuyCardSource, Card, Color, ColorVec, Noble, ReserveCardAction, TakeTokenAction,
+    Tier, MAX_PLAYERS,
 };
 use rand::seq::SliceRandom;
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use std::fmt;
 
+/// The points of a noble.
+const NOBLE_POINTS: u8 = 3;
+/// Scores within this margin of the best score are considered ties.
+const SCORE_EPSILON: f32 = 1e-4;
+
 /// A random player actor for testing.
 #[derive(Default, Copy, Clone)]
 pub struct RandomActor<R> {
@@ -41,7 +48,7 @@ impl<R: RngCore + Send + Sync> PlayerActor for RandomActor<R> {
                 .filter(|(_, n)| *n > 0)
                 .collect::<SmallVec<_, 5>>();
             tokens.shuffle(&mut self.rng);
-            if tokens.len() >= 1 {
+            if !tokens.is_empty() {
                 let mut colors = ColorVec::empty();
                 for (i, _) in tokens.iter().take(3) {
                     colors.add(Color::try_from(*i).unwrap(), 1);
@@ -114,18 +121,10 @@ impl<R: RngCore + Send + Sync> PlayerActor for RandomActor<R> {
                         .reserved_cards
                         .iter()
                         .enumerate()
-                        .filter_map(|(idx, c)| Some((c.unwrap(), BuyCardSource::Reserved(idx)))),
+                        .map(|(idx, c)| (c.unwrap(), BuyCardSource::Reserved(idx))),
                 )
                 .filter_map(|(card, source)| {
-                    let effective_cost = card
-                        .requires
-                        .saturating_sub(&current_player.development_cards.bonus);
-                    let diff = effective_cost.saturating_sub(&current_player.tokens);
-                    if diff.total() > current_player.tokens.get(Color::Yellow) {
-                        return None;
-                    }
-                    let mut uses = effective_cost - diff;
-                    uses.set(Color::Yellow, diff.total());
+                    let uses = current_player.payment(card)?;
                     Some(BuyCardAction { source, uses })
                 })
                 .collect::<SmallVec<_, { 4 * 3 }>>();
@@ -172,9 +171,298 @@ impl<R: RngCore + Send + Sync> PlayerActor for RandomActor<R> {
             .nobles
             .iter()
             .enumerate()
-            .filter(|(_, n)| current_player.development_cards.bonus.le(&n.requires))
+            .filter(|(_, n)| n.requires.le(&current_player.development_cards.bonus))
             .collect::<SmallVec<_, 5>>();
         let (idx, _) = possible_nobles.choose(&mut self.rng).unwrap();
         Ok(SelectNoblesAction(*idx))
     }
 }
+
+/// The weights of the [`GreedyActor`] heuristic.
+///
+/// Every term is measured in points, a weight of `0` disables the term.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GreedyWeights {
+    /// Points gained, including the points of visited nobles.
+    pub points: f32,
+    /// Bonus progress toward the nobles still available.
+    pub noble_progress: f32,
+    /// How close the tokens and bonus in hand are to buying the cards in reach.
+    pub token_efficiency: f32,
+    /// How close the leading opponent is to the revealed card taken away.
+    pub denial: f32,
+}
+
+impl Default for GreedyWeights {
+    fn default() -> Self {
+        GreedyWeights {
+            points: 1.0,
+            noble_progress: 0.5,
+            token_efficiency: 1.0,
+            denial: 0.25,
+        }
+    }
+}
+
+/// A player actor which takes the action with the best heuristic score.
+///
+/// Ties are broken with the random number generator, so the actor is deterministic under a
+/// seeded generator.
+#[derive(Default, Copy, Clone)]
+pub struct GreedyActor<R> {
+    rng: R,
+    weights: GreedyWeights,
+}
+
+impl<R: RngCore + Send + Sync> GreedyActor<R> {
+    /// Create a new greedy actor with the default weights.
+    pub fn new(rng: R) -> Self {
+        Self::with_weights(rng, GreedyWeights::default())
+    }
+
+    /// Create a new greedy actor with the given weights.
+    pub fn with_weights(rng: R, weights: GreedyWeights) -> Self {
+        GreedyActor { rng, weights }
+    }
+
+    /// Get the action with the best score.
+    pub fn best_action(&mut self, snapshot: &GameSnapshot) -> PlayerAction {
+        let scored = snapshot
+            .legal_actions()
+            .into_iter()
+            .map(|action| (action, self.score(snapshot, &action)))
+            .collect::<Vec<_>>();
+        let best = scored
+            .iter()
+            .map(|(_, score)| *score)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let candidates = scored
+            .into_iter()
+            .filter(|(_, score)| *score >= best - SCORE_EPSILON)
+            .map(|(action, _)| action)
+            .collect::<SmallVec<_, 8>>();
+        *candidates.choose(&mut self.rng).unwrap()
+    }
+}
+
+impl<R> GreedyActor<R> {
+    /// Get the weights of the heuristic.
+    pub fn weights(&self) -> &GreedyWeights {
+        &self.weights
+    }
+
+    /// Score an action of the current player, the higher the better.
+    pub fn score(&self, snapshot: &GameSnapshot, action: &PlayerAction) -> f32 {
+        let before = Position::new(snapshot);
+        let mut after = before.clone();
+        let mut denied = None;
+        match action {
+            PlayerAction::TakeTokens(action) => {
+                after.tokens += action.tokens();
+                self.drop_excess(snapshot, &mut after);
+            }
+            PlayerAction::ReserveCard(ReserveCardAction { tier, idx }) => {
+                if snapshot.tokens.get(Color::Yellow) > 0 {
+                    after.tokens.add(Color::Yellow, 1);
+                    self.drop_excess(snapshot, &mut after);
+                }
+                if let Some(idx) = *idx {
+                    let card = snapshot.card_pool.revealed[*tier as usize][idx];
+                    after.taken = Some((*tier, idx));
+                    after.reserved.push(card);
+                    denied = Some(card);
+                }
+            }
+            PlayerAction::BuyCard(BuyCardAction { source, uses }) => {
+                let card = match *source {
+                    BuyCardSource::Revealed { tier, idx } => {
+                        let card = snapshot.card_pool.revealed[tier as usize][idx];
+                        after.taken = Some((tier, idx));
+                        denied = Some(card);
+                        card
+                    }
+                    BuyCardSource::Reserved(idx) => after.reserved.remove(idx),
+                };
+                after.tokens -= uses;
+                after.points += card.points;
+                after.bonus.add(card.bonus, 1);
+                if let Some(idx) = after
+                    .nobles
+                    .iter()
+                    .position(|n| n.requires.le(&after.bonus))
+                {
+                    after.nobles.remove(idx);
+                    after.points += NOBLE_POINTS;
+                }
+            }
+            PlayerAction::Nop => {}
+        }
+        let denial = denied.map_or(0.0, |card| denial(snapshot, &card));
+        self.value(snapshot, &after) - self.value(snapshot, &before) + self.weights.denial * denial
+    }
+
+    /// Get the tokens to drop which keep the best value.
+    pub fn best_drop(&self, snapshot: &GameSnapshot) -> DropTokensAction {
+        let mut position = Position::new(snapshot);
+        self.drop_excess(snapshot, &mut position);
+        DropTokensAction(snapshot.current_player().tokens - position.tokens)
+    }
+
+    /// Get the noble an opponent is the closest to, among the nobles the current player can
+    /// visit.
+    pub fn best_noble(&self, snapshot: &GameSnapshot) -> SelectNoblesAction {
+        let current_player = snapshot.current_player();
+        let (idx, _) = snapshot
+            .nobles
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.requires.le(&current_player.development_cards.bonus))
+            .min_by_key(|(_, n)| {
+                snapshot
+                    .players
+                    .iter()
+                    .filter(|p| p.idx != current_player.idx)
+                    .map(|p| {
+                        n.requires
+                            .saturating_sub(&p.development_cards.bonus)
+                            .total()
+                    })
+                    .min()
+                    .unwrap_or(0)
+            })
+            .unwrap();
+        SelectNoblesAction(idx)
+    }
+
+    /// The heuristic value of a position of the current player.
+    fn value(&self, snapshot: &GameSnapshot, position: &Position) -> f32 {
+        let noble_progress = position
+            .nobles
+            .iter()
+            .map(|n| {
+                let missing = n.requires.saturating_sub(&position.bonus).total();
+                NOBLE_POINTS as f32 * (1.0 - missing as f32 / n.requires.total() as f32)
+            })
+            .sum::<f32>();
+        let targets = snapshot
+            .card_pool
+            .revealed
+            .iter()
+            .flat_map(|cards| cards.iter().enumerate())
+            .filter(|(idx, c)| position.taken != Some((c.tier, *idx)))
+            .map(|(_, c)| c)
+            .chain(position.reserved.iter());
+        let (n_targets, reach) = targets.fold((0, 0.0), |(n, reach), card| {
+            (
+                n + 1,
+                reach + reach_of(card, &position.bonus, &position.tokens),
+            )
+        });
+        let token_efficiency = if n_targets > 0 {
+            reach / n_targets as f32
+        } else {
+            0.0
+        };
+        self.weights.points * position.points as f32
+            + self.weights.noble_progress * noble_progress
+            + self.weights.token_efficiency * token_efficiency
+    }
+
+    /// Drop tokens above the limit one at a time, keeping the value as high as possible.
+    fn drop_excess(&self, snapshot: &GameSnapshot, position: &mut Position) {
+        while position.tokens.total() > 10 {
+            let color = (0usize..6)
+                .map(|i| Color::try_from(i).unwrap())
+                .filter(|c| position.tokens.get(*c) > 0)
+                .map(|c| {
+                    let mut next = position.clone();
+                    next.tokens.sub(c, 1);
+                    (c, self.value(snapshot, &next))
+                })
+                .fold(None, |best: Option<(Color, f32)>, (c, value)| match best {
+                    Some((_, best_value)) if best_value >= value => best,
+                    _ => Some((c, value)),
+                })
+                .map(|(c, _)| c)
+                .unwrap();
+            position.tokens.sub(color, 1);
+        }
+    }
+}
+
+impl<R> Debug for GreedyActor<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GreedyActor")
+            .field("weights", &self.weights)
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: RngCore + Send + Sync> PlayerActor for GreedyActor<R> {
+    async fn get_action(&mut self, snapshot: GameSnapshot) -> Result<PlayerAction, ActorError> {
+        Ok(self.best_action(&snapshot))
+    }
+
+    async fn drop_tokens(
+        &mut self,
+        snapshot: GameSnapshot,
+    ) -> Result<DropTokensAction, ActorError> {
+        Ok(self.best_drop(&snapshot))
+    }
+
+    async fn select_noble(
+        &mut self,
+        snapshot: GameSnapshot,
+    ) -> Result<SelectNoblesAction, ActorError> {
+        Ok(self.best_noble(&snapshot))
+    }
+}
+
+/// The part of the game a [`GreedyActor`] scores, seen by the current player.
+#[derive(Clone)]
+struct Position {
+    points: u8,
+    tokens: ColorVec,
+    bonus: ColorVec,
+    reserved: SmallVec<Card, 3>,
+    nobles: SmallVec<Noble, { MAX_PLAYERS + 1 }>,
+    /// The revealed card taken by the action.
+    taken: Option<(Tier, usize)>,
+}
+
+impl Position {
+    fn new(snapshot: &GameSnapshot) -> Self {
+        let player = snapshot.current_player();
+        Position {
+            points: player.points,
+            tokens: player.tokens,
+            bonus: player.development_cards.bonus,
+            reserved: player.reserved_cards.iter().map(|c| *c.unwrap()).collect(),
+            nobles: snapshot.nobles.clone(),
+            taken: None,
+        }
+    }
+}
+
+/// The worth of a card, discounted by the tokens still missing to buy it.
+fn reach_of(card: &Card, bonus: &ColorVec, tokens: &ColorVec) -> f32 {
+    let missing = card
+        .requires
+        .saturating_sub(bonus)
+        .saturating_sub(tokens)
+        .total()
+        .saturating_sub(tokens.get(Color::Yellow));
+    (card.points as f32 + 1.0) / (1.0 + missing as f32)
+}
+
+/// How close the leading opponent is to a card.
+fn denial(snapshot: &GameSnapshot, card: &Card) -> f32 {
+    snapshot
+        .players
+        .iter()
+        .filter(|p| p.idx != snapshot.current_player)
+        .map(|p| reach_of(card, &p.development_cards.bonus, &p.tokens))
+        .fold(0.0, f32::max)
+}
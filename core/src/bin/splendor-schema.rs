@@ -0,0 +1,18 @@
+//! Write the JSON Schemas of the protocol into a directory, `schema` by default.
+use splendor_core::protocol_schemas;
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let dir = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "schema".to_string()),
+    );
+    std::fs::create_dir_all(&dir)?;
+    for (name, json) in protocol_schemas() {
+        let path = dir.join(format!("{name}.json"));
+        std::fs::write(&path, json)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
@@ -26,6 +26,16 @@ impl Tier {
             Tier::III => "3️⃣",
         }
     }
+
+    /// Get the roman numeral of the tier.
+    #[inline(always)]
+    pub fn roman(&self) -> &'static str {
+        match self {
+            Tier::I => "I",
+            Tier::II => "II",
+            Tier::III => "III",
+        }
+    }
 }
 
 impl Serialize for Tier {
@@ -54,6 +64,7 @@ impl TryFrom<usize> for Tier {
 /// A struct to represent a card.
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Card {
     /// The tier of the card.
     pub tier: Tier,
@@ -79,13 +90,15 @@ impl Card {
 
 /// A struct to represent the development cards in player's hand.
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DevelopmentCards {
     /// The total points of the development cards.
     pub points: u8,
     /// The total bonus of the development cards.
     pub bonus: ColorVec,
     /// The cards in the development cards, grouped by bonus color.
+    #[cfg_attr(feature = "schemars", schemars(with = "[Vec<Card>; 5]"))]
     pub inner: [SmallVec<Card, 7>; 5],
 }
 
@@ -156,6 +169,7 @@ impl From<ReservedCard> for Card {
 
 /// A struct to represent the view of other players' reserved cards.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", content = "view")]
 #[serde(rename_all = "snake_case")]
 pub enum CardView {
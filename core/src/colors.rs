@@ -19,6 +19,7 @@ use strum::EnumIter;
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum Color {
     /// Black color, Coal.
@@ -48,6 +49,32 @@ impl Color {
             Color::Yellow => "🟡",
         }
     }
+
+    /// Get the ASCII letter of the color, `k` for black.
+    #[inline(always)]
+    pub fn letter(&self) -> char {
+        match self {
+            Color::Black => 'k',
+            Color::Blue => 'b',
+            Color::Green => 'g',
+            Color::Red => 'r',
+            Color::White => 'w',
+            Color::Yellow => 'y',
+        }
+    }
+
+    /// Get the color of an ASCII letter, case insensitive.
+    pub fn from_letter(letter: char) -> Option<Color> {
+        match letter.to_ascii_lowercase() {
+            'k' => Some(Color::Black),
+            'b' => Some(Color::Blue),
+            'g' => Some(Color::Green),
+            'r' => Some(Color::Red),
+            'w' => Some(Color::White),
+            'y' => Some(Color::Yellow),
+            _ => None,
+        }
+    }
 }
 
 /// A struct to represent the color combinations.
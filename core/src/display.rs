@@ -0,0 +1,551 @@
+use crate::{
+    ActionType, BuyCardAction, BuyCardSource, Card, CardView, Color, ColorVec, DropTokensAction,
+    GameSnapshot, Noble, PlayerAction, PlayerSnapshot, ReserveCardAction, SelectNoblesAction,
+    TakeTokenAction, Tier,
+};
+use std::fmt::{self, Display, Formatter};
+
+/// The symbols used to render colors and tiers.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Charset {
+    /// Emoji, such as `🔵` and `2️⃣`.
+    #[default]
+    Emoji,
+    /// Plain ASCII, such as `b` and `II`.
+    Ascii,
+}
+
+/// A struct to represent how the core types are rendered.
+///
+/// `Display` uses the default style, the alternate flag `{:#}` renders verbosely.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RenderStyle {
+    /// The symbols of colors and tiers.
+    pub charset: Charset,
+    /// Color the tokens and cards with ANSI escape codes.
+    pub ansi: bool,
+    /// Render all the details, players and games span multiple lines.
+    pub verbose: bool,
+}
+
+impl RenderStyle {
+    /// The plain ASCII style.
+    pub const fn ascii() -> Self {
+        RenderStyle {
+            charset: Charset::Ascii,
+            ansi: false,
+            verbose: false,
+        }
+    }
+
+    /// Set whether to use ANSI escape codes.
+    pub const fn with_ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    /// Set whether to render verbosely.
+    pub const fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+}
+
+/// A trait to render a type with a [`RenderStyle`].
+pub trait Render {
+    /// Render with the given style.
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result;
+
+    /// Wrap with a style, the wrapper implements `Display`.
+    fn styled(&self, style: RenderStyle) -> Styled<'_, Self> {
+        Styled { value: self, style }
+    }
+}
+
+/// A value wrapped with a [`RenderStyle`].
+#[derive(Debug)]
+pub struct Styled<'a, T: ?Sized> {
+    value: &'a T,
+    style: RenderStyle,
+}
+
+impl<T: Render + ?Sized> Display for Styled<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let style = self.style.with_verbose(self.style.verbose || f.alternate());
+        self.value.render(f, style)
+    }
+}
+
+/// A player action rendered with the cards it refers to, such as `buy 🔵3 (2⚫ 1🟡)`.
+#[derive(Debug)]
+pub struct ActionInSnapshot<'a> {
+    action: &'a PlayerAction,
+    snapshot: &'a GameSnapshot,
+}
+
+impl PlayerAction {
+    /// Render the action with the cards of the snapshot it is taken in.
+    pub fn in_snapshot<'a>(&'a self, snapshot: &'a GameSnapshot) -> ActionInSnapshot<'a> {
+        ActionInSnapshot {
+            action: self,
+            snapshot,
+        }
+    }
+}
+
+macro_rules! impl_display {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Display for $ty {
+                fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                    let style = RenderStyle::default().with_verbose(f.alternate());
+                    self.render(f, style)
+                }
+            }
+        )*
+    };
+}
+
+impl_display!(
+    Color,
+    Tier,
+    ColorVec,
+    Card,
+    Noble,
+    CardView,
+    PlayerSnapshot,
+    GameSnapshot,
+    ActionType,
+    DropTokensAction,
+    SelectNoblesAction,
+    TakeTokenAction,
+    BuyCardSource,
+    BuyCardAction,
+    ReserveCardAction,
+    PlayerAction,
+    ActionInSnapshot<'_>,
+);
+
+fn ansi_code(color: Color) -> &'static str {
+    match color {
+        Color::Black => "\x1b[90m",
+        Color::Blue => "\x1b[94m",
+        Color::Green => "\x1b[92m",
+        Color::Red => "\x1b[91m",
+        Color::White => "\x1b[97m",
+        Color::Yellow => "\x1b[93m",
+    }
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Render a colored item, the count goes before the color and the suffix after it.
+fn colored(
+    f: &mut Formatter<'_>,
+    style: RenderStyle,
+    color: Color,
+    count: Option<u8>,
+    suffix: Option<u8>,
+    upper: bool,
+) -> fmt::Result {
+    if style.ansi {
+        f.write_str(ansi_code(color))?;
+    }
+    if let Some(count) = count {
+        write!(f, "{count}")?;
+    }
+    match style.charset {
+        Charset::Emoji => f.write_str(color.emoji())?,
+        Charset::Ascii if upper => write!(f, "{}", color.letter().to_ascii_uppercase())?,
+        Charset::Ascii => write!(f, "{}", color.letter())?,
+    }
+    if let Some(suffix) = suffix {
+        write!(f, "{suffix}")?;
+    }
+    if style.ansi {
+        f.write_str(ANSI_RESET)?;
+    }
+    Ok(())
+}
+
+/// Render items separated by `sep`, or `empty` without any item.
+fn join<T>(
+    f: &mut Formatter<'_>,
+    items: impl IntoIterator<Item = T>,
+    sep: &str,
+    empty: &str,
+    mut render: impl FnMut(&mut Formatter<'_>, T) -> fmt::Result,
+) -> fmt::Result {
+    let mut items = items.into_iter().peekable();
+    if items.peek().is_none() {
+        return f.write_str(empty);
+    }
+    let mut first = true;
+    for item in items {
+        if !first {
+            f.write_str(sep)?;
+        }
+        first = false;
+        render(f, item)?;
+    }
+    Ok(())
+}
+
+impl Render for Color {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        colored(f, style, *self, None, None, false)
+    }
+}
+
+impl Render for Tier {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        match style.charset {
+            Charset::Emoji => f.write_str(self.emoji()),
+            Charset::Ascii => f.write_str(self.roman()),
+        }
+    }
+}
+
+impl Render for ColorVec {
+    /// Compactly renders the colors with tokens, such as `2⚫ 1🟡`, verbosely all the colors.
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        let colors = self
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| style.verbose || *n > 0)
+            .map(|(i, n)| (Color::try_from(i).unwrap(), n));
+        join(f, colors, " ", "-", |f, (color, n)| {
+            colored(f, style, color, Some(n), None, false)
+        })
+    }
+}
+
+impl Render for Card {
+    /// Compactly renders the bonus and points, such as `🔵3`, verbosely the tier and cost.
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        if style.verbose {
+            self.tier.render(f, style)?;
+            f.write_str(" ")?;
+        }
+        colored(f, style, self.bonus, None, Some(self.points), true)?;
+        if style.verbose {
+            f.write_str(" (")?;
+            self.requires.render(f, style.with_verbose(false))?;
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+impl Render for Noble {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        match style.charset {
+            Charset::Emoji => f.write_str("👑")?,
+            Charset::Ascii => f.write_str("N")?,
+        }
+        if style.verbose {
+            f.write_str("3 ")?;
+        }
+        f.write_str("(")?;
+        self.requires.render(f, style.with_verbose(false))?;
+        f.write_str(")")
+    }
+}
+
+impl Render for CardView {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        match self {
+            CardView::Visible(card) => card.render(f, style),
+            CardView::Invisible(tier) => {
+                tier.render(f, style)?;
+                f.write_str(if style.verbose { " hidden" } else { "?" })
+            }
+        }
+    }
+}
+
+impl Render for PlayerSnapshot {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        let compact = style.with_verbose(false);
+        write!(f, "#{} {}pts", self.idx, self.points)?;
+        if !style.verbose {
+            f.write_str(" tokens ")?;
+            self.tokens.render(f, compact)?;
+            f.write_str(" bonus ")?;
+            self.development_cards.bonus.render(f, compact)?;
+            return write!(
+                f,
+                " reserved {} nobles {}",
+                self.reserved_cards.len(),
+                self.nobles.len()
+            );
+        }
+        f.write_str("\n  tokens: ")?;
+        self.tokens.render(f, compact)?;
+        f.write_str("\n  bonus: ")?;
+        self.development_cards.bonus.render(f, compact)?;
+        f.write_str("\n  cards: ")?;
+        join(f, self.development_cards.iter(), " ", "-", |f, card| {
+            card.render(f, compact)
+        })?;
+        f.write_str("\n  reserved: ")?;
+        join(f, self.reserved_cards.iter(), ", ", "-", |f, card| {
+            card.render(f, style)
+        })?;
+        f.write_str("\n  nobles: ")?;
+        join(f, self.nobles.iter(), " ", "-", |f, noble| {
+            noble.render(f, compact)
+        })
+    }
+}
+
+impl Render for GameSnapshot {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        let compact = style.with_verbose(false);
+        write!(
+            f,
+            "round {}, player #{} to move",
+            self.current_round, self.current_player
+        )?;
+        if self.last_round {
+            f.write_str(", last round")?;
+        }
+        f.write_str("\nbank: ")?;
+        self.tokens.render(f, compact)?;
+        f.write_str("\nnobles: ")?;
+        join(f, self.nobles.iter(), " ", "-", |f, noble| {
+            noble.render(f, compact)
+        })?;
+        for tier in [Tier::III, Tier::II, Tier::I] {
+            f.write_str("\n")?;
+            tier.render(f, style)?;
+            write!(f, " [{}]: ", self.card_pool.remaining[tier as usize])?;
+            let sep = if style.verbose { ", " } else { " " };
+            join(
+                f,
+                self.card_pool.revealed[tier as usize].iter(),
+                sep,
+                "-",
+                |f, card| card.render(f, style),
+            )?;
+        }
+        for player in self.players.iter() {
+            f.write_str("\n")?;
+            player.render(f, style)?;
+        }
+        Ok(())
+    }
+}
+
+impl Render for ActionType {
+    fn render(&self, f: &mut Formatter<'_>, _style: RenderStyle) -> fmt::Result {
+        f.write_str(match self {
+            ActionType::GetAction => "get action",
+            ActionType::DropTokens => "drop tokens",
+            ActionType::SelectNoble => "select noble",
+        })
+    }
+}
+
+impl Render for DropTokensAction {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        f.write_str("drop ")?;
+        self.0.render(f, style.with_verbose(false))
+    }
+}
+
+impl Render for SelectNoblesAction {
+    fn render(&self, f: &mut Formatter<'_>, _style: RenderStyle) -> fmt::Result {
+        write!(f, "visit noble #{}", self.0)
+    }
+}
+
+impl Render for TakeTokenAction {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        f.write_str("take ")?;
+        if style.verbose {
+            f.write_str(match self {
+                TakeTokenAction::ThreeDifferent(_) => "different tokens ",
+                TakeTokenAction::TwoSame(_) => "same tokens ",
+            })?;
+        }
+        self.tokens().render(f, style.with_verbose(false))
+    }
+}
+
+impl Render for BuyCardSource {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        match self {
+            BuyCardSource::Revealed { tier, idx } => {
+                tier.render(f, style)?;
+                write!(f, "#{idx}")
+            }
+            BuyCardSource::Reserved(idx) => write!(f, "reserved#{idx}"),
+        }
+    }
+}
+
+impl Render for BuyCardAction {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        f.write_str("buy ")?;
+        self.source.render(f, style)?;
+        f.write_str(" (")?;
+        self.uses.render(f, style.with_verbose(false))?;
+        f.write_str(")")
+    }
+}
+
+impl Render for ReserveCardAction {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        f.write_str("reserve ")?;
+        self.tier.render(f, style)?;
+        match self.idx {
+            Some(idx) => write!(f, "#{idx}"),
+            None => f.write_str(" pool"),
+        }
+    }
+}
+
+impl Render for PlayerAction {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        match self {
+            PlayerAction::TakeTokens(action) => action.render(f, style),
+            PlayerAction::BuyCard(action) => action.render(f, style),
+            PlayerAction::ReserveCard(action) => action.render(f, style),
+            PlayerAction::Nop => f.write_str("pass"),
+        }
+    }
+}
+
+impl Render for ActionInSnapshot<'_> {
+    fn render(&self, f: &mut Formatter<'_>, style: RenderStyle) -> fmt::Result {
+        let revealed =
+            |tier: Tier, idx: usize| self.snapshot.card_pool.revealed[tier as usize].get(idx);
+        match self.action {
+            PlayerAction::BuyCard(action) => {
+                let card = match action.source {
+                    BuyCardSource::Revealed { tier, idx } => revealed(tier, idx).copied(),
+                    BuyCardSource::Reserved(idx) => self
+                        .snapshot
+                        .current_player()
+                        .reserved_cards
+                        .get(idx)
+                        .and_then(|card| match card {
+                            CardView::Visible(card) => Some(*card),
+                            CardView::Invisible(_) => None,
+                        }),
+                };
+                let Some(card) = card else {
+                    return action.render(f, style);
+                };
+                f.write_str("buy ")?;
+                card.render(f, style)?;
+                f.write_str(" (")?;
+                action.uses.render(f, style.with_verbose(false))?;
+                f.write_str(")")
+            }
+            PlayerAction::ReserveCard(ReserveCardAction {
+                tier,
+                idx: Some(idx),
+            }) => match revealed(*tier, *idx) {
+                Some(card) => {
+                    f.write_str("reserve ")?;
+                    card.render(f, style)
+                }
+                None => self.action.render(f, style),
+            },
+            action => action.render(f, style),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smallvec::smallvec;
+
+    fn card() -> Card {
+        Card::new(Tier::II, Color::Blue, 3, ColorVec::new(3, 0, 2, 0, 0, 0))
+    }
+
+    #[test]
+    fn test_display() {
+        let tokens = ColorVec::new(2, 0, 0, 0, 0, 1);
+        assert_eq!(tokens.to_string(), "2⚫ 1🟡");
+        assert_eq!(format!("{tokens:#}"), "2⚫ 0🔵 0🟢 0🔴 0⚪ 1🟡");
+        assert_eq!(ColorVec::empty().to_string(), "-");
+
+        assert_eq!(card().to_string(), "🔵3");
+        assert_eq!(format!("{:#}", card()), "2️⃣ 🔵3 (3⚫ 2🟢)");
+        assert_eq!(CardView::Invisible(Tier::I).to_string(), "1️⃣?");
+        assert_eq!(
+            Noble::new(ColorVec::new(4, 0, 0, 4, 0, 0)).to_string(),
+            "👑(4⚫ 4🔴)"
+        );
+
+        let action = PlayerAction::BuyCard(BuyCardAction {
+            source: BuyCardSource::Revealed {
+                tier: Tier::II,
+                idx: 3,
+            },
+            uses: tokens,
+        });
+        assert_eq!(action.to_string(), "buy 2️⃣#3 (2⚫ 1🟡)");
+        assert_eq!(
+            PlayerAction::TakeTokens(TakeTokenAction::TwoSame(ColorVec::new(0, 2, 0, 0, 0, 0)))
+                .to_string(),
+            "take 2🔵"
+        );
+        assert_eq!(PlayerAction::Nop.to_string(), "pass");
+        assert_eq!(SelectNoblesAction(1).to_string(), "visit noble #1");
+    }
+
+    #[test]
+    fn test_styles() {
+        let ascii = RenderStyle::ascii();
+        let tokens = ColorVec::new(2, 0, 0, 0, 0, 1);
+        assert_eq!(tokens.styled(ascii).to_string(), "2k 1y");
+        assert_eq!(format!("{:#}", card().styled(ascii)), "II B3 (3k 2g)");
+        assert_eq!(
+            DropTokensAction(tokens).styled(ascii).to_string(),
+            "drop 2k 1y"
+        );
+        assert_eq!(
+            tokens.styled(ascii.with_ansi(true)).to_string(),
+            "\x1b[90m2k\x1b[0m \x1b[93m1y\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_in_snapshot() {
+        let snapshot = GameSnapshot {
+            last_round: false,
+            current_round: 0,
+            current_player: 0,
+            tokens: ColorVec::new(7, 7, 7, 7, 7, 5),
+            card_pool: crate::CardPoolSnapshot {
+                remaining: [36, 25, 16],
+                revealed: [smallvec![], smallvec![card()], smallvec![]],
+            },
+            nobles: smallvec![],
+            players: smallvec![],
+        };
+        let action = PlayerAction::BuyCard(BuyCardAction {
+            source: BuyCardSource::Revealed {
+                tier: Tier::II,
+                idx: 0,
+            },
+            uses: ColorVec::new(2, 0, 0, 0, 0, 1),
+        });
+        assert_eq!(
+            action.in_snapshot(&snapshot).to_string(),
+            "buy 🔵3 (2⚫ 1🟡)"
+        );
+        let action = PlayerAction::ReserveCard(ReserveCardAction {
+            tier: Tier::II,
+            idx: Some(0),
+        });
+        assert_eq!(action.in_snapshot(&snapshot).to_string(), "reserve 🔵3");
+        assert!(snapshot.to_string().contains("2️⃣ [25]: 🔵3"));
+    }
+}
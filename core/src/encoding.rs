@@ -0,0 +1,325 @@
+use crate::{
+    BuyCardAction, BuyCardSource, Card, CardView, Color, ColorVec, GameSnapshot, Noble, Notation,
+    PlayerAction, PlayerSnapshot, ReserveCardAction, TakeTokenAction, Tier, MAX_PLAYERS,
+};
+use serde::Serialize;
+
+/// The features of a card: present, tier one-hot, bonus one-hot, points and the cost.
+pub const CARD_FEATURES: usize = 1 + 3 + 5 + 1 + 5;
+/// The features of a reserved card: the card and whether it is hidden.
+///
+/// A hidden card only has the present flag and the tier set.
+pub const RESERVED_FEATURES: usize = CARD_FEATURES + 1;
+/// The features of a noble: present and the requirements.
+pub const NOBLE_FEATURES: usize = 1 + 5;
+/// The features of a player: present, points, tokens, bonus, the number of cards and nobles,
+/// and the reserved cards.
+pub const PLAYER_FEATURES: usize = 1 + 1 + 6 + 5 + 1 + 1 + 3 * RESERVED_FEATURES;
+/// The size of an encoded snapshot.
+pub const OBSERVATION_SIZE: usize = 3
+    + 6
+    + 3
+    + 3 * 4 * CARD_FEATURES
+    + (MAX_PLAYERS + 1) * NOBLE_FEATURES
+    + MAX_PLAYERS * PLAYER_FEATURES;
+
+const TAKE_DIFFERENT: usize = 0;
+const TAKE_SAME: usize = TAKE_DIFFERENT + 25;
+const RESERVE_REVEALED: usize = TAKE_SAME + 5;
+const RESERVE_POOL: usize = RESERVE_REVEALED + 12;
+const BUY_REVEALED: usize = RESERVE_POOL + 3;
+const BUY_RESERVED: usize = BUY_REVEALED + 12;
+const NOP: usize = BUY_RESERVED + 3;
+/// The size of the action space.
+pub const ACTION_SPACE_SIZE: usize = NOP + 1;
+
+/// A struct to represent the layout of the encoding, to be matched by training code.
+#[derive(Debug, Clone, Serialize)]
+pub struct EncodingSpec {
+    /// The size of an encoded snapshot.
+    pub observation_size: usize,
+    /// The fields of an encoded snapshot, in order.
+    pub fields: Vec<FieldSpec>,
+    /// The size of the action space.
+    pub action_space_size: usize,
+    /// The notation of each action index, buying a card is noted without the payment.
+    pub actions: Vec<String>,
+}
+
+/// A struct to represent a field of the encoded snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldSpec {
+    /// The name of the field.
+    pub name: &'static str,
+    /// The offset of the field.
+    pub offset: usize,
+    /// The shape of the field, row-major.
+    pub shape: Vec<usize>,
+}
+
+/// Get the layout of the encoding.
+pub fn encoding_spec() -> EncodingSpec {
+    let shapes = [
+        ("header", vec![3]),
+        ("bank", vec![6]),
+        ("card_pool.remaining", vec![3]),
+        ("card_pool.revealed", vec![3, 4, CARD_FEATURES]),
+        ("nobles", vec![MAX_PLAYERS + 1, NOBLE_FEATURES]),
+        ("players", vec![MAX_PLAYERS, PLAYER_FEATURES]),
+    ];
+    let mut offset = 0;
+    let mut fields = vec![];
+    for (name, shape) in shapes {
+        let len = shape.iter().product::<usize>();
+        fields.push(FieldSpec {
+            name,
+            offset,
+            shape,
+        });
+        offset += len;
+    }
+    EncodingSpec {
+        observation_size: OBSERVATION_SIZE,
+        fields,
+        action_space_size: ACTION_SPACE_SIZE,
+        actions: (0..ACTION_SPACE_SIZE).map(action_label).collect(),
+    }
+}
+
+/// The color masks of taking different tokens: 3 colors, then 2 colors, then 1 color.
+fn take_masks() -> impl Iterator<Item = u8> {
+    [3, 2, 1]
+        .into_iter()
+        .flat_map(|n| (0u8..1 << 5).filter(move |mask| mask.count_ones() == n))
+}
+
+fn tokens_of_mask(mask: u8, count: u8) -> ColorVec {
+    let mut tokens = ColorVec::empty();
+    for i in 0usize..5 {
+        if mask & (1 << i) != 0 {
+            tokens.set(Color::try_from(i).unwrap(), count);
+        }
+    }
+    tokens
+}
+
+fn mask_of_tokens(tokens: &ColorVec) -> u8 {
+    tokens
+        .iter()
+        .take(5)
+        .enumerate()
+        .filter(|(_, n)| *n > 0)
+        .fold(0, |mask, (i, _)| mask | 1 << i)
+}
+
+fn action_label(index: usize) -> String {
+    let action = if index < TAKE_SAME {
+        let mask = take_masks().nth(index - TAKE_DIFFERENT).unwrap();
+        PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(tokens_of_mask(mask, 1)))
+    } else if index < RESERVE_REVEALED {
+        let mask = 1 << (index - TAKE_SAME);
+        PlayerAction::TakeTokens(TakeTokenAction::TwoSame(tokens_of_mask(mask, 2)))
+    } else if index < RESERVE_POOL {
+        let slot = index - RESERVE_REVEALED;
+        PlayerAction::ReserveCard(ReserveCardAction {
+            tier: Tier::try_from(slot / 4).unwrap(),
+            idx: Some(slot % 4),
+        })
+    } else if index < BUY_REVEALED {
+        PlayerAction::ReserveCard(ReserveCardAction {
+            tier: Tier::try_from(index - RESERVE_POOL).unwrap(),
+            idx: None,
+        })
+    } else if index < BUY_RESERVED {
+        let slot = index - BUY_REVEALED;
+        return format!("B:{}.{}", slot / 4 + 1, slot % 4);
+    } else if index < NOP {
+        return format!("B:R.{}", index - BUY_RESERVED);
+    } else {
+        PlayerAction::Nop
+    };
+    action.notation()
+}
+
+impl PlayerAction {
+    /// Get the index of the action in the action space.
+    ///
+    /// Buying a card is indexed by the card only, `None` if the action is malformed,
+    /// such as taking more than 3 colors.
+    pub fn action_index(&self) -> Option<usize> {
+        let slot = |tier: Tier, idx: usize| (idx < 4).then_some(tier as usize * 4 + idx);
+        match self {
+            PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(tokens)) => {
+                let mask = mask_of_tokens(tokens);
+                take_masks().position(|m| m == mask)
+            }
+            PlayerAction::TakeTokens(TakeTokenAction::TwoSame(tokens)) => {
+                let mask = mask_of_tokens(tokens);
+                (mask.count_ones() == 1).then(|| TAKE_SAME + mask.trailing_zeros() as usize)
+            }
+            PlayerAction::ReserveCard(ReserveCardAction {
+                tier,
+                idx: Some(idx),
+            }) => slot(*tier, *idx).map(|i| RESERVE_REVEALED + i),
+            PlayerAction::ReserveCard(ReserveCardAction { tier, idx: None }) => {
+                Some(RESERVE_POOL + *tier as usize)
+            }
+            PlayerAction::BuyCard(BuyCardAction { source, .. }) => match source {
+                BuyCardSource::Revealed { tier, idx } => {
+                    slot(*tier, *idx).map(|i| BUY_REVEALED + i)
+                }
+                BuyCardSource::Reserved(idx) => (*idx < 3).then_some(BUY_RESERVED + idx),
+            },
+            PlayerAction::Nop => Some(NOP),
+        }
+    }
+}
+
+impl GameSnapshot {
+    /// Encode the snapshot from the perspective of the current player.
+    ///
+    /// The players are rotated so the current player comes first, absent players, nobles and
+    /// cards are all zeros. The header holds the last round flag, the current round and the
+    /// number of players. Counts are not normalized. See [`encoding_spec`] for the layout.
+    pub fn encode(&self) -> Vec<f32> {
+        let mut out = Vec::with_capacity(OBSERVATION_SIZE);
+        out.push(self.last_round as u8 as f32);
+        out.push(self.current_round as f32);
+        out.push(self.players.len() as f32);
+        push_tokens(&mut out, &self.tokens, 6);
+        out.extend(self.card_pool.remaining.iter().map(|n| *n as f32));
+        for cards in self.card_pool.revealed.iter() {
+            for idx in 0..4 {
+                push_card(&mut out, cards.get(idx));
+            }
+        }
+        for idx in 0..MAX_PLAYERS + 1 {
+            push_noble(&mut out, self.nobles.get(idx));
+        }
+        let n = self.players.len();
+        for i in 0..MAX_PLAYERS {
+            let player = (i < n).then(|| &self.players[(self.current_player + i) % n]);
+            push_player(&mut out, player);
+        }
+        debug_assert_eq!(out.len(), OBSERVATION_SIZE);
+        out
+    }
+
+    /// Get the mask of the legal actions in the action space.
+    pub fn legal_action_mask(&self) -> Vec<bool> {
+        let mut mask = vec![false; ACTION_SPACE_SIZE];
+        for action in self.legal_actions() {
+            if let Some(index) = action.action_index() {
+                mask[index] = true;
+            }
+        }
+        mask
+    }
+
+    /// Get the action of an index in the action space, `None` if it is not legal.
+    ///
+    /// Cards are paid with [`PlayerSnapshot::payment`].
+    pub fn decode_action(&self, index: usize) -> Option<PlayerAction> {
+        self.legal_actions()
+            .into_iter()
+            .find(|action| action.action_index() == Some(index))
+    }
+}
+
+fn push_tokens(out: &mut Vec<f32>, tokens: &ColorVec, n: usize) {
+    out.extend(tokens.iter().take(n).map(|n| n as f32));
+}
+
+fn push_one_hot(out: &mut Vec<f32>, n: usize, hot: Option<usize>) {
+    out.extend((0..n).map(|i| (Some(i) == hot) as u8 as f32));
+}
+
+fn push_card(out: &mut Vec<f32>, card: Option<&Card>) {
+    match card {
+        Some(card) => {
+            out.push(1.0);
+            push_one_hot(out, 3, Some(card.tier as usize));
+            push_one_hot(out, 5, Some(card.bonus as usize));
+            out.push(card.points as f32);
+            push_tokens(out, &card.requires, 5);
+        }
+        None => out.extend([0.0; CARD_FEATURES]),
+    }
+}
+
+fn push_noble(out: &mut Vec<f32>, noble: Option<&Noble>) {
+    match noble {
+        Some(noble) => {
+            out.push(1.0);
+            push_tokens(out, &noble.requires, 5);
+        }
+        None => out.extend([0.0; NOBLE_FEATURES]),
+    }
+}
+
+fn push_player(out: &mut Vec<f32>, player: Option<&PlayerSnapshot>) {
+    let Some(player) = player else {
+        out.extend([0.0; PLAYER_FEATURES]);
+        return;
+    };
+    out.push(1.0);
+    out.push(player.points as f32);
+    push_tokens(out, &player.tokens, 6);
+    push_tokens(out, &player.development_cards.bonus, 5);
+    out.push(player.development_cards.iter().count() as f32);
+    out.push(player.nobles.len() as f32);
+    for idx in 0..3 {
+        match player.reserved_cards.get(idx) {
+            Some(CardView::Visible(card)) => {
+                push_card(out, Some(card));
+                out.push(0.0);
+            }
+            Some(CardView::Invisible(tier)) => {
+                out.push(1.0);
+                push_one_hot(out, 3, Some(*tier as usize));
+                out.extend([0.0; CARD_FEATURES - 4]);
+                out.push(1.0);
+            }
+            None => out.extend([0.0; RESERVED_FEATURES]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_encoding_spec() {
+        let spec = encoding_spec();
+        let last = spec.fields.last().unwrap();
+        assert_eq!(
+            last.offset + last.shape.iter().product::<usize>(),
+            OBSERVATION_SIZE
+        );
+        assert_eq!(spec.actions.len(), ACTION_SPACE_SIZE);
+        assert_eq!(
+            spec.actions.iter().collect::<HashSet<_>>().len(),
+            ACTION_SPACE_SIZE
+        );
+        assert_eq!(spec.actions[0], "T:KBG");
+        assert_eq!(spec.actions[TAKE_SAME], "T2:K");
+        assert_eq!(spec.actions[RESERVE_POOL + 2], "R:3.*");
+        assert_eq!(spec.actions[BUY_RESERVED], "B:R.0");
+        assert_eq!(spec.actions[NOP], "P");
+    }
+
+    #[test]
+    fn test_action_index() {
+        for (index, label) in encoding_spec().actions.iter().enumerate() {
+            if let Ok(action) = label.parse::<PlayerAction>() {
+                assert_eq!(action.action_index(), Some(index), "{label}");
+            }
+        }
+        let action = PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(ColorVec::new(
+            1, 1, 1, 1, 0, 0,
+        )));
+        assert_eq!(action.action_index(), None);
+    }
+}
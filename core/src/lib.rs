@@ -2,23 +2,30 @@
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 
-#[macro_use]
-extern crate log;
-
 mod action;
 mod actor;
 mod cards;
 mod colors;
+mod display;
+mod encoding;
 mod nobles;
+mod notation;
 mod record;
+#[cfg(feature = "schemars")]
+mod schema;
 mod snapshot;
 
 pub use action::*;
 pub use actor::*;
 pub use cards::*;
 pub use colors::*;
+pub use display::*;
+pub use encoding::*;
 pub use nobles::*;
+pub use notation::*;
 pub use record::*;
+#[cfg(feature = "schemars")]
+pub use schema::*;
 pub use snapshot::*;
 
 /// The maximum number of players in a game.
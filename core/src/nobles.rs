@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 /// A struct to represent a noble.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Noble {
     /// The color requirements of the noble.
     pub requires: ColorVec,
@@ -0,0 +1,352 @@
+use crate::{
+    BuyCardAction, BuyCardSource, Color, ColorVec, DropTokensAction, PlayerAction,
+    ReserveCardAction, SelectNoblesAction, TakeTokenAction, Tier,
+};
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+/// A notation which cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid notation `{notation}`: {reason}")]
+pub struct NotationError {
+    /// The notation.
+    pub notation: String,
+    /// Why the notation is invalid.
+    pub reason: &'static str,
+}
+
+/// A trait for the compact algebraic notation of actions.
+///
+/// | Action                      | Notation     |
+/// |-----------------------------|--------------|
+/// | take different tokens       | `T:KBG`      |
+/// | take two same tokens        | `T2:R`       |
+/// | buy a revealed card         | `B:2.3/K2Y1` |
+/// | buy a reserved card         | `B:R.0/B3`   |
+/// | reserve a revealed card     | `R:3.1`      |
+/// | reserve from the card pool  | `R:3.*`      |
+/// | do nothing                  | `P`          |
+/// | drop tokens                 | `D:KY`       |
+/// | select a noble              | `N:1`        |
+///
+/// Colors are `K`, `B`, `G`, `R`, `W` and `Y` for black, blue, green, red, white and yellow,
+/// lowercase letters are accepted as well. Tiers count from 1 while indexes count from 0,
+/// the same as the JSON. A token list is either colors repeated by the count (`KKY`),
+/// colors followed by the count (`K2Y1`), or `-` if empty.
+pub trait Notation: Sized {
+    /// Write the notation.
+    fn write_notation<W: Write>(&self, w: &mut W) -> fmt::Result;
+
+    /// Parse the notation.
+    fn parse_notation(s: &str) -> Result<Self, NotationError>;
+
+    /// Get the notation.
+    fn notation(&self) -> String {
+        let mut s = String::new();
+        self.write_notation(&mut s)
+            .expect("writing to a String should not fail");
+        s
+    }
+}
+
+impl Notation for PlayerAction {
+    fn write_notation<W: Write>(&self, w: &mut W) -> fmt::Result {
+        match self {
+            PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(tokens)) => {
+                w.write_str("T:")?;
+                write_letters(w, tokens)
+            }
+            PlayerAction::TakeTokens(TakeTokenAction::TwoSame(tokens)) => {
+                w.write_str("T2:")?;
+                let mut colors = ColorVec::empty();
+                for color in Color::iter().filter(|c| tokens.get(*c) > 0) {
+                    colors.set(color, 1);
+                }
+                write_letters(w, &colors)
+            }
+            PlayerAction::BuyCard(BuyCardAction { source, uses }) => {
+                match source {
+                    BuyCardSource::Revealed { tier, idx } => {
+                        write!(w, "B:{}.{}/", *tier as usize + 1, idx)?
+                    }
+                    BuyCardSource::Reserved(idx) => write!(w, "B:R.{idx}/")?,
+                }
+                write_counts(w, uses)
+            }
+            PlayerAction::ReserveCard(ReserveCardAction { tier, idx }) => {
+                write!(w, "R:{}.", *tier as usize + 1)?;
+                match idx {
+                    Some(idx) => write!(w, "{idx}"),
+                    None => w.write_str("*"),
+                }
+            }
+            PlayerAction::Nop => w.write_str("P"),
+        }
+    }
+
+    fn parse_notation(s: &str) -> Result<Self, NotationError> {
+        if s == "P" {
+            return Ok(PlayerAction::Nop);
+        }
+        let (kind, body) = split(s, s, ':', "missing `:`")?;
+        match kind {
+            "T" => Ok(PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(
+                parse_tokens(s, body)?,
+            ))),
+            "T2" => {
+                let mut tokens = parse_tokens(s, body)?;
+                for color in Color::iter() {
+                    if tokens.get(color) > 0 {
+                        tokens.set(color, 2);
+                    }
+                }
+                Ok(PlayerAction::TakeTokens(TakeTokenAction::TwoSame(tokens)))
+            }
+            "B" => {
+                let (source, uses) = split(s, body, '/', "missing `/` before the tokens")?;
+                let (tier, idx) = split(s, source, '.', "missing `.` before the index")?;
+                let idx = parse_index(s, idx)?;
+                let source = match tier {
+                    "R" => BuyCardSource::Reserved(idx),
+                    tier => BuyCardSource::Revealed {
+                        tier: parse_tier(s, tier)?,
+                        idx,
+                    },
+                };
+                Ok(PlayerAction::BuyCard(BuyCardAction {
+                    source,
+                    uses: parse_tokens(s, uses)?,
+                }))
+            }
+            "R" => {
+                let (tier, idx) = split(s, body, '.', "missing `.` before the index")?;
+                let idx = match idx {
+                    "*" => None,
+                    idx => Some(parse_index(s, idx)?),
+                };
+                Ok(PlayerAction::ReserveCard(ReserveCardAction {
+                    tier: parse_tier(s, tier)?,
+                    idx,
+                }))
+            }
+            _ => Err(error(s, "unknown action")),
+        }
+    }
+}
+
+impl Notation for DropTokensAction {
+    fn write_notation<W: Write>(&self, w: &mut W) -> fmt::Result {
+        w.write_str("D:")?;
+        write_letters(w, &self.0)
+    }
+
+    fn parse_notation(s: &str) -> Result<Self, NotationError> {
+        match split(s, s, ':', "missing `:`")? {
+            ("D", tokens) => Ok(DropTokensAction(parse_tokens(s, tokens)?)),
+            _ => Err(error(s, "not a drop tokens action")),
+        }
+    }
+}
+
+impl Notation for SelectNoblesAction {
+    fn write_notation<W: Write>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "N:{}", self.0)
+    }
+
+    fn parse_notation(s: &str) -> Result<Self, NotationError> {
+        match split(s, s, ':', "missing `:`")? {
+            ("N", idx) => Ok(SelectNoblesAction(parse_index(s, idx)?)),
+            _ => Err(error(s, "not a select noble action")),
+        }
+    }
+}
+
+macro_rules! impl_from_str {
+    ($($ty:ty),*) => {
+        $(
+            impl FromStr for $ty {
+                type Err = NotationError;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    Self::parse_notation(s)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_str!(PlayerAction, DropTokensAction, SelectNoblesAction);
+
+fn error(notation: &str, reason: &'static str) -> NotationError {
+    NotationError {
+        notation: notation.to_string(),
+        reason,
+    }
+}
+
+fn split<'a>(
+    notation: &str,
+    s: &'a str,
+    delimiter: char,
+    reason: &'static str,
+) -> Result<(&'a str, &'a str), NotationError> {
+    s.split_once(delimiter)
+        .ok_or_else(|| error(notation, reason))
+}
+
+/// Write the colors repeated by the count, such as `KKY`.
+fn write_letters<W: Write>(w: &mut W, tokens: &ColorVec) -> fmt::Result {
+    if tokens.total() == 0 {
+        return w.write_str("-");
+    }
+    for color in Color::iter() {
+        for _ in 0..tokens.get(color) {
+            w.write_char(color.letter().to_ascii_uppercase())?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the colors followed by the count, such as `K2Y1`.
+fn write_counts<W: Write>(w: &mut W, tokens: &ColorVec) -> fmt::Result {
+    if tokens.total() == 0 {
+        return w.write_str("-");
+    }
+    for color in Color::iter().filter(|c| tokens.get(*c) > 0) {
+        write!(
+            w,
+            "{}{}",
+            color.letter().to_ascii_uppercase(),
+            tokens.get(color)
+        )?;
+    }
+    Ok(())
+}
+
+fn parse_tokens(notation: &str, s: &str) -> Result<ColorVec, NotationError> {
+    let mut tokens = ColorVec::empty();
+    if s == "-" {
+        return Ok(tokens);
+    }
+    if s.is_empty() {
+        return Err(error(notation, "missing tokens"));
+    }
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let color = Color::from_letter(c).ok_or_else(|| error(notation, "unknown color"))?;
+        let mut count = None;
+        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+            chars.next();
+            let n = count
+                .unwrap_or(0u8)
+                .checked_mul(10)
+                .and_then(|n| n.checked_add(digit as u8))
+                .ok_or_else(|| error(notation, "too many tokens"))?;
+            count = Some(n);
+        }
+        let total = tokens
+            .get(color)
+            .checked_add(count.unwrap_or(1))
+            .ok_or_else(|| error(notation, "too many tokens"))?;
+        tokens.set(color, total);
+    }
+    Ok(tokens)
+}
+
+fn parse_tier(notation: &str, s: &str) -> Result<Tier, NotationError> {
+    s.parse::<u8>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|n| Tier::try_from(n).ok())
+        .ok_or_else(|| error(notation, "tier must be 1, 2 or 3"))
+}
+
+fn parse_index(notation: &str, s: &str) -> Result<usize, NotationError> {
+    s.parse().map_err(|_| error(notation, "invalid index"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notation() {
+        let cases = [
+            (
+                "T:KBG",
+                PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(ColorVec::new(
+                    1, 1, 1, 0, 0, 0,
+                ))),
+            ),
+            (
+                "T2:R",
+                PlayerAction::TakeTokens(TakeTokenAction::TwoSame(ColorVec::new(0, 0, 0, 2, 0, 0))),
+            ),
+            (
+                "B:2.3/K2Y1",
+                PlayerAction::BuyCard(BuyCardAction {
+                    source: BuyCardSource::Revealed {
+                        tier: Tier::II,
+                        idx: 3,
+                    },
+                    uses: ColorVec::new(2, 0, 0, 0, 0, 1),
+                }),
+            ),
+            (
+                "B:R.0/B3",
+                PlayerAction::BuyCard(BuyCardAction {
+                    source: BuyCardSource::Reserved(0),
+                    uses: ColorVec::new(0, 3, 0, 0, 0, 0),
+                }),
+            ),
+            (
+                "R:3.*",
+                PlayerAction::ReserveCard(ReserveCardAction {
+                    tier: Tier::III,
+                    idx: None,
+                }),
+            ),
+            (
+                "R:1.2",
+                PlayerAction::ReserveCard(ReserveCardAction {
+                    tier: Tier::I,
+                    idx: Some(2),
+                }),
+            ),
+            ("P", PlayerAction::Nop),
+        ];
+        for (notation, action) in cases {
+            assert_eq!(action.notation(), notation);
+            assert_eq!(notation.parse::<PlayerAction>().unwrap(), action);
+        }
+        assert_eq!("T:kbg".parse::<PlayerAction>().unwrap().notation(), "T:KBG");
+
+        let drop = DropTokensAction(ColorVec::new(2, 0, 0, 0, 0, 1));
+        assert_eq!(drop.notation(), "D:KKY");
+        assert_eq!("D:K2Y".parse::<DropTokensAction>().unwrap(), drop);
+        assert_eq!(SelectNoblesAction(1).notation(), "N:1");
+        assert_eq!(
+            "N:1".parse::<SelectNoblesAction>().unwrap(),
+            SelectNoblesAction(1)
+        );
+    }
+
+    #[test]
+    fn test_invalid_notation() {
+        for (notation, reason) in [
+            ("T", "missing `:`"),
+            ("X:K", "unknown action"),
+            ("T:", "missing tokens"),
+            ("T:KX", "unknown color"),
+            ("T:K300", "too many tokens"),
+            ("B:4.0/K1", "tier must be 1, 2 or 3"),
+            ("B:2.0", "missing `/` before the tokens"),
+            ("R:2.x", "invalid index"),
+        ] {
+            let error = notation.parse::<PlayerAction>().unwrap_err();
+            assert_eq!(error.reason, reason, "{notation}");
+        }
+        assert!("N:1".parse::<DropTokensAction>().is_err());
+    }
+}
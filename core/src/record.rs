@@ -1,9 +1,10 @@
 //! History Record
 
 use crate::{DropTokensAction, Noble, PlayerAction};
+use serde::{Deserialize, Serialize};
 
 /// Wraps an action with the player who took it.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActionRecord<T> {
     /// The player who took the action.
     pub player: usize,
@@ -19,7 +20,9 @@ impl<T> ActionRecord<T> {
 }
 
 /// A record of a game event.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "record")]
+#[serde(rename_all = "snake_case")]
 pub enum Record {
     /// A player has taken an action.
     PlayerAction(ActionRecord<PlayerAction>),
@@ -0,0 +1,114 @@
+use crate::{
+    ActionRequest, ActionType, BuyCardAction, BuyCardSource, Card, CardView, Color, ColorVec,
+    DropTokensAction, GameSnapshot, Noble, PlayerAction, PlayerSnapshot, ReserveCardAction,
+    SelectNoblesAction, TakeTokenAction, Tier,
+};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{ArrayValidation, InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
+
+impl JsonSchema for Tier {
+    fn schema_name() -> String {
+        "Tier".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::Integer.into()),
+            enum_values: Some(vec![0.into(), 1.into(), 2.into()]),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl JsonSchema for ColorVec {
+    fn schema_name() -> String {
+        "ColorVec".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::Array.into()),
+            array: Some(Box::new(ArrayValidation {
+                items: Some(gen.subschema_for::<u8>().into()),
+                min_items: Some(6),
+                max_items: Some(6),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+macro_rules! schemas {
+    ($($ty:ty),* $(,)?) => {
+        /// Get the JSON Schemas (draft 07) of the protocol types, named by the type.
+        ///
+        /// The checked-in `schema/` directory is generated by the `splendor-schema` binary.
+        pub fn protocol_schemas() -> Vec<(&'static str, String)> {
+            vec![$((stringify!($ty), schema_json::<$ty>())),*]
+        }
+    };
+}
+
+schemas!(
+    ActionRequest,
+    ActionType,
+    BuyCardAction,
+    BuyCardSource,
+    Card,
+    CardView,
+    Color,
+    ColorVec,
+    DropTokensAction,
+    GameSnapshot,
+    Noble,
+    PlayerAction,
+    PlayerSnapshot,
+    ReserveCardAction,
+    SelectNoblesAction,
+    TakeTokenAction,
+    Tier,
+);
+
+fn schema_json<T: JsonSchema>() -> String {
+    let schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    let mut json =
+        serde_json::to_string_pretty(&schema).expect("schema serialization should not fail");
+    json.push('\n');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_schema_up_to_date() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../schema");
+        let schemas = protocol_schemas();
+        for (name, json) in schemas.iter() {
+            let path = dir.join(format!("{name}.json"));
+            let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+            assert!(
+                checked_in.replace("\r\n", "\n") == *json,
+                "{} is out of date, run `cargo run -p splendor-core --features schemars --bin splendor-schema`",
+                path.display()
+            );
+        }
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_stem().unwrap().to_string_lossy();
+            assert!(
+                schemas.iter().any(|(n, _)| *n == name),
+                "{} is not generated from any type",
+                path.display()
+            );
+        }
+    }
+}
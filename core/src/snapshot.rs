@@ -1,9 +1,13 @@
-use crate::{Card, CardView, ColorVec, DevelopmentCards, Noble, MAX_PLAYERS};
+use crate::{
+    BuyCardAction, BuyCardSource, Card, CardView, Color, ColorVec, DevelopmentCards, Noble,
+    PlayerAction, ReserveCardAction, TakeTokenAction, Tier, MAX_PLAYERS,
+};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
 /// A struct to represent the game snapshot.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GameSnapshot {
     /// Is the game in the last round.
     pub last_round: bool,
@@ -17,23 +21,28 @@ pub struct GameSnapshot {
     /// The card pool snapshot.
     pub card_pool: CardPoolSnapshot,
     /// The nobles available in the game.
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<Noble>"))]
     pub nobles: SmallVec<Noble, { MAX_PLAYERS + 1 }>,
 
     /// The players' snapshot.
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<PlayerSnapshot>"))]
     pub players: SmallVec<PlayerSnapshot, MAX_PLAYERS>,
 }
 
 /// A struct to represent the card pool snapshot.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CardPoolSnapshot {
     /// The remaining cards in the pool.
     pub remaining: [usize; 3],
     /// The revealed cards in the pool.
+    #[cfg_attr(feature = "schemars", schemars(with = "[Vec<Card>; 3]"))]
     pub revealed: [SmallVec<Card, 4>; 3],
 }
 
 /// A struct to represent the player snapshot.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PlayerSnapshot {
     /// The index of the player.
     pub idx: usize,
@@ -44,7 +53,114 @@ pub struct PlayerSnapshot {
     /// The development cards the player has.
     pub development_cards: DevelopmentCards,
     /// The reserved cards the player has.
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<CardView>"))]
     pub reserved_cards: SmallVec<CardView, 3>,
     /// The nobles the player has visited.
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<Noble>"))]
     pub nobles: SmallVec<Noble, { MAX_PLAYERS + 1 }>,
 }
+
+impl GameSnapshot {
+    /// Get the snapshot of the current player.
+    pub fn current_player(&self) -> &PlayerSnapshot {
+        &self.players[self.current_player]
+    }
+
+    /// Enumerate the actions the current player can take.
+    ///
+    /// Cards are paid with [`PlayerSnapshot::payment`], and [`PlayerAction::Nop`] is only
+    /// returned when there is nothing else to do.
+    pub fn legal_actions(&self) -> Vec<PlayerAction> {
+        let player = self.current_player();
+        let mut actions = vec![];
+
+        // take up to 3 different tokens, as many as available
+        let colors = (0usize..5)
+            .map(|i| Color::try_from(i).unwrap())
+            .filter(|c| self.tokens.get(*c) > 0)
+            .collect::<SmallVec<_, 5>>();
+        let n = colors.len().min(3);
+        for mask in 0u8..1 << colors.len() {
+            if mask.count_ones() as usize != n || n == 0 {
+                continue;
+            }
+            let mut tokens = ColorVec::empty();
+            for (i, color) in colors.iter().enumerate() {
+                if mask & (1 << i) != 0 {
+                    tokens.add(*color, 1);
+                }
+            }
+            actions.push(PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(
+                tokens,
+            )));
+        }
+
+        // take 2 tokens of the same color
+        for color in colors.iter().filter(|c| self.tokens.get(**c) >= 4) {
+            let mut tokens = ColorVec::empty();
+            tokens.set(*color, 2);
+            actions.push(PlayerAction::TakeTokens(TakeTokenAction::TwoSame(tokens)));
+        }
+
+        // reserve a revealed card or a card from the pool
+        if player.reserved_cards.len() < 3 {
+            for (tier, cards) in self.card_pool.revealed.iter().enumerate() {
+                let tier = Tier::try_from(tier).unwrap();
+                for idx in 0..cards.len() {
+                    actions.push(PlayerAction::ReserveCard(ReserveCardAction {
+                        tier,
+                        idx: Some(idx),
+                    }));
+                }
+                if self.card_pool.remaining[tier as usize] > 0 {
+                    actions.push(PlayerAction::ReserveCard(ReserveCardAction {
+                        tier,
+                        idx: None,
+                    }));
+                }
+            }
+        }
+
+        // buy a revealed or reserved card
+        let revealed = self.card_pool.revealed.iter().flat_map(|cards| {
+            cards
+                .iter()
+                .enumerate()
+                .map(|(idx, c)| (c, BuyCardSource::Revealed { tier: c.tier, idx }))
+        });
+        let reserved = player
+            .reserved_cards
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, c)| match c {
+                CardView::Visible(card) => Some((card, BuyCardSource::Reserved(idx))),
+                CardView::Invisible(_) => None,
+            });
+        for (card, source) in revealed.chain(reserved) {
+            if let Some(uses) = player.payment(card) {
+                actions.push(PlayerAction::BuyCard(BuyCardAction { source, uses }));
+            }
+        }
+
+        if actions.is_empty() {
+            actions.push(PlayerAction::Nop);
+        }
+        actions
+    }
+}
+
+impl PlayerSnapshot {
+    /// Get the tokens to pay for a card, or `None` if the player cannot afford it.
+    ///
+    /// Color tokens are used first, and yellow tokens cover the rest.
+    pub fn payment(&self, card: &Card) -> Option<ColorVec> {
+        let effective_cost = card.requires.saturating_sub(&self.development_cards.bonus);
+        let diff = effective_cost.saturating_sub(&self.tokens);
+        if diff.total() > self.tokens.get(Color::Yellow) {
+            return None;
+        }
+        let mut uses = effective_cost - diff;
+        uses.set(Color::Yellow, diff.total());
+        Some(uses)
+    }
+}
@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand::prelude::*;
+use splendor_core::{PlayerAction, MAX_PLAYERS};
+use splendor_engine::testing::play_greedy;
+use splendor_engine::{CompactState, GameState};
+
+/// A state in the middle of a game, with the legal actions of the current player.
+fn midgame() -> (GameState, Vec<PlayerAction>) {
+    let mut state = GameState::with_rng(&mut SmallRng::seed_from_u64(42), MAX_PLAYERS);
+    play_greedy(&mut state, 10 * MAX_PLAYERS);
+    let actions = state.legal_actions();
+    (state, actions)
+}
+
+fn clone_apply(c: &mut Criterion) {
+    let (state, actions) = midgame();
+    let compact = CompactState::from(&state);
+
+    let mut group = c.benchmark_group("clone_apply");
+    group.bench_function("game_state", |b| {
+        b.iter(|| {
+            for action in actions.iter() {
+                let mut state = black_box(&state).clone();
+                state.apply_action(action).unwrap();
+                state.end_turn();
+                black_box(&state);
+            }
+        })
+    });
+    group.bench_function("compact_state", |b| {
+        b.iter(|| {
+            for action in actions.iter() {
+                let mut state = *black_box(&compact);
+                state.apply_action(action).unwrap();
+                state.end_turn();
+                black_box(&state);
+            }
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("convert");
+    group.bench_function("to_compact", |b| {
+        b.iter(|| CompactState::from(black_box(&state)))
+    });
+    group.bench_function("from_compact", |b| {
+        b.iter_batched(
+            || compact,
+            |compact| GameState::from(&compact),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, clone_apply);
+criterion_main!(benches);
@@ -0,0 +1,186 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand::prelude::*;
+use smallvec::SmallVec;
+use splendor_core::naive_actors::{GreedyActor, RandomActor};
+use splendor_core::{
+    BuyCardSource, GameSnapshot, PlayerAction, PlayerActor, TakeTokenAction, MAX_PLAYERS,
+};
+use splendor_engine::bench::{self, Action};
+use splendor_engine::testing::play_greedy;
+use splendor_engine::{GameContext, GameState};
+
+const ACTION_KINDS: [&str; 9] = [
+    "take_three",
+    "take_two",
+    "buy_revealed",
+    "buy_reserved",
+    "reserve_revealed",
+    "reserve_pool",
+    "nop",
+    "drop_tokens",
+    "select_noble",
+];
+
+fn kind(action: &PlayerAction) -> &'static str {
+    match action {
+        PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(_)) => "take_three",
+        PlayerAction::TakeTokens(TakeTokenAction::TwoSame(_)) => "take_two",
+        PlayerAction::BuyCard(action) => match action.source {
+            BuyCardSource::Revealed { .. } => "buy_revealed",
+            BuyCardSource::Reserved(_) => "buy_reserved",
+        },
+        PlayerAction::ReserveCard(action) if action.idx.is_some() => "reserve_revealed",
+        PlayerAction::ReserveCard(_) => "reserve_pool",
+        PlayerAction::Nop => "nop",
+    }
+}
+
+/// Find a state and a valid action of every kind of action, in games mixing greedy and
+/// random moves.
+fn action_cases() -> Vec<(&'static str, GameState, Action)> {
+    let mut cases: Vec<(&'static str, GameState, Action)> = vec![];
+    let mut rng = SmallRng::seed_from_u64(42);
+    let mut greedy = GreedyActor::new(SmallRng::seed_from_u64(42));
+    let add = |cases: &mut Vec<_>, name, state: &GameState, action| {
+        if !cases.iter().any(|(n, _, _)| *n == name) {
+            cases.push((name, state.clone(), action));
+        }
+    };
+    for seed in 0..100 {
+        let mut state = GameState::with_rng(&mut SmallRng::seed_from_u64(seed), MAX_PLAYERS);
+        while !state.game_end() && state.current_round() < 100 {
+            let actions = state.legal_actions();
+            for action in actions.iter() {
+                add(&mut cases, kind(action), &state, Action::Player(*action));
+            }
+            add(&mut cases, "nop", &state, Action::Player(PlayerAction::Nop));
+            let action = if rng.gen_bool(0.5) {
+                greedy.best_action(&state.snapshot())
+            } else {
+                *actions.choose(&mut rng).unwrap()
+            };
+            state.apply_action(&action).unwrap();
+            if state.needs_drop() {
+                let action = greedy.best_drop(&state.snapshot());
+                add(
+                    &mut cases,
+                    "drop_tokens",
+                    &state,
+                    Action::DropTokens(action),
+                );
+                state.drop_tokens(&action).unwrap();
+            }
+            if !state.noble_visits().is_empty() {
+                let action = greedy.best_noble(&state.snapshot());
+                add(
+                    &mut cases,
+                    "select_noble",
+                    &state,
+                    Action::SelectNoble(action),
+                );
+                state.visit_noble(&action).unwrap();
+            }
+            state.end_turn();
+        }
+        if cases.len() == ACTION_KINDS.len() {
+            break;
+        }
+    }
+    assert_eq!(
+        cases.len(),
+        ACTION_KINDS.len(),
+        "some kinds of action not found"
+    );
+    cases.sort_by_key(|(name, _, _)| ACTION_KINDS.iter().position(|n| n == name));
+    cases
+}
+
+/// A state in the middle of a game.
+fn midgame() -> GameState {
+    let mut state = GameState::with_rng(&mut SmallRng::seed_from_u64(42), MAX_PLAYERS);
+    play_greedy(&mut state, 10 * MAX_PLAYERS);
+    state
+}
+
+fn game(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("game");
+    for n_players in [2, MAX_PLAYERS] {
+        group.bench_function(format!("random_{n_players}p"), |b| {
+            b.iter(|| {
+                let actors = (0..n_players)
+                    .map(|i| Box::new(RandomActor::new(SmallRng::seed_from_u64(i as u64))) as _)
+                    .collect::<SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>>();
+                let mut game = GameContext::with_rng(&mut SmallRng::seed_from_u64(42), actors);
+                runtime.block_on(async {
+                    // random games can go on forever, cut them like the self-play runs
+                    while game.current_round() < 100 {
+                        if game.step().await.unwrap().is_some() {
+                            break;
+                        }
+                    }
+                });
+                game
+            })
+        });
+    }
+    group.finish();
+}
+
+fn snapshot(c: &mut Criterion) {
+    let initial = GameState::with_rng(&mut SmallRng::seed_from_u64(42), MAX_PLAYERS);
+    let midgame = midgame();
+    let mut group = c.benchmark_group("snapshot");
+    group.bench_function("initial", |b| b.iter(|| black_box(&initial).snapshot()));
+    group.bench_function("midgame", |b| b.iter(|| black_box(&midgame).snapshot()));
+    group.finish();
+
+    let snapshot = midgame.snapshot();
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let mut group = c.benchmark_group("json");
+    group.bench_function("serialize", |b| {
+        b.iter(|| serde_json::to_string(black_box(&snapshot)).unwrap())
+    });
+    group.bench_function("deserialize", |b| {
+        b.iter(|| serde_json::from_str::<GameSnapshot>(black_box(&json)).unwrap())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("card_pool");
+    group.bench_function("remaining", |b| {
+        b.iter(|| bench::remaining(black_box(&midgame)))
+    });
+    group.bench_function("revealed", |b| {
+        b.iter(|| bench::revealed(black_box(&midgame)))
+    });
+    group.finish();
+}
+
+fn actions(c: &mut Criterion) {
+    let cases = action_cases();
+    let mut group = c.benchmark_group("is_valid");
+    for (name, state, action) in cases.iter() {
+        assert!(action.is_valid(state), "{name} is not valid");
+        group.bench_function(*name, |b| {
+            b.iter(|| black_box(action).is_valid(black_box(state)))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("apply");
+    for (name, state, action) in cases.iter() {
+        group.bench_function(*name, |b| {
+            b.iter_batched_ref(
+                || state.clone(),
+                |state| black_box(action).apply(state),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, game, snapshot, actions);
+criterion_main!(benches);
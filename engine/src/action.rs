@@ -1,5 +1,6 @@
 use crate::error::InvalidActionError;
-use crate::game::GameContext;
+use crate::game::GameState;
+use crate::zobrist;
 use splendor_core::{
     BuyCardAction, BuyCardSource, Color, DropTokensAction, PlayerAction, ReserveCardAction,
     ReservedCard, SelectNoblesAction, TakeTokenAction,
@@ -7,12 +8,12 @@ use splendor_core::{
 use std::fmt::Debug;
 
 pub trait ActionExt: Debug {
-    fn is_valid(&self, ctx: &GameContext) -> Result<(), InvalidActionError>;
-    fn apply(&self, ctx: &mut GameContext);
+    fn is_valid(&self, ctx: &GameState) -> Result<(), InvalidActionError>;
+    fn apply(&self, ctx: &mut GameState);
 
     fn require(
         &self,
-        ctx: &GameContext,
+        ctx: &GameState,
         cond: bool,
         reason: &'static str,
     ) -> Result<(), InvalidActionError> {
@@ -31,7 +32,7 @@ pub trait ActionExt: Debug {
 }
 
 impl ActionExt for DropTokensAction {
-    fn is_valid(&self, ctx: &GameContext) -> Result<(), InvalidActionError> {
+    fn is_valid(&self, ctx: &GameState) -> Result<(), InvalidActionError> {
         // Check if the player has enough tokens to drop.
         self.require(
             ctx,
@@ -45,16 +46,18 @@ impl ActionExt for DropTokensAction {
         )
     }
 
-    fn apply(&self, ctx: &mut GameContext) {
+    fn apply(&self, ctx: &mut GameState) {
+        ctx.toggle_tokens();
         ctx.players[ctx.current_player].tokens -= self.0;
         ctx.tokens += self.0;
+        ctx.toggle_tokens();
     }
 }
 
 impl ActionExt for SelectNoblesAction {
-    fn is_valid(&self, ctx: &GameContext) -> Result<(), InvalidActionError> {
+    fn is_valid(&self, ctx: &GameState) -> Result<(), InvalidActionError> {
         // Check if nobles are available.
-        self.require(ctx, self.0 <= ctx.nobles.len(), "noble index out of range")?;
+        self.require(ctx, self.0 < ctx.nobles.len(), "noble index out of range")?;
         // Check if the player has met the noble requirements.
         let noble = &ctx.nobles.get(self.0);
         let player = &ctx.players[ctx.current_player];
@@ -65,15 +68,16 @@ impl ActionExt for SelectNoblesAction {
         )
     }
 
-    fn apply(&self, ctx: &mut GameContext) {
+    fn apply(&self, ctx: &mut GameState) {
         let noble = ctx.nobles.remove(self.0);
         let player = &mut ctx.players[ctx.current_player];
         player.nobles.push(noble);
+        ctx.hash ^= zobrist::noble(zobrist::BANK, &noble) ^ zobrist::noble(player.idx + 1, &noble);
     }
 }
 
 impl ActionExt for PlayerAction {
-    fn is_valid(&self, ctx: &GameContext) -> Result<(), InvalidActionError> {
+    fn is_valid(&self, ctx: &GameState) -> Result<(), InvalidActionError> {
         match self {
             PlayerAction::TakeTokens(action) => action.is_valid(ctx),
             PlayerAction::BuyCard(action) => action.is_valid(ctx),
@@ -82,7 +86,7 @@ impl ActionExt for PlayerAction {
         }
     }
 
-    fn apply(&self, ctx: &mut GameContext) {
+    fn apply(&self, ctx: &mut GameState) {
         match self {
             PlayerAction::TakeTokens(action) => action.apply(ctx),
             PlayerAction::BuyCard(action) => action.apply(ctx),
@@ -93,7 +97,7 @@ impl ActionExt for PlayerAction {
 }
 
 impl ActionExt for TakeTokenAction {
-    fn is_valid(&self, ctx: &GameContext) -> Result<(), InvalidActionError> {
+    fn is_valid(&self, ctx: &GameState) -> Result<(), InvalidActionError> {
         self.require(
             ctx,
             self.tokens().get(Color::Yellow) == 0,
@@ -132,10 +136,12 @@ impl ActionExt for TakeTokenAction {
         }
     }
 
-    fn apply(&self, ctx: &mut GameContext) {
+    fn apply(&self, ctx: &mut GameState) {
         let tokens = self.tokens();
+        ctx.toggle_tokens();
         ctx.tokens -= tokens;
         ctx.players[ctx.current_player].tokens += tokens;
+        ctx.toggle_tokens();
         trace!(
             "Player#{} now has tokens: {:?}, remaining tokens: {:?}",
             ctx.current_player,
@@ -146,7 +152,7 @@ impl ActionExt for TakeTokenAction {
 }
 
 impl ActionExt for BuyCardAction {
-    fn is_valid(&self, ctx: &GameContext) -> Result<(), InvalidActionError> {
+    fn is_valid(&self, ctx: &GameState) -> Result<(), InvalidActionError> {
         let player = &ctx.players[ctx.current_player];
         // Check if the card is available.
         let card = match self.source {
@@ -177,20 +183,27 @@ impl ActionExt for BuyCardAction {
         )
     }
 
-    fn apply(&self, ctx: &mut GameContext) {
+    fn apply(&self, ctx: &mut GameState) {
+        ctx.toggle_tokens();
         let player = &mut ctx.players[ctx.current_player];
         let card = match self.source {
             BuyCardSource::Revealed { tier, idx } => ctx.card_pool.take(tier, idx),
-            BuyCardSource::Reserved(idx) => player.reserved_cards.remove(idx).card,
+            BuyCardSource::Reserved(idx) => {
+                let card = player.reserved_cards.remove(idx);
+                ctx.hash ^= zobrist::reserved(player.idx, &card);
+                card.card
+            }
         };
+        ctx.hash ^= zobrist::development(player.idx, &card);
         player.development_cards.add(card);
         player.tokens -= self.uses;
         ctx.tokens += self.uses;
+        ctx.toggle_tokens();
     }
 }
 
 impl ActionExt for ReserveCardAction {
-    fn is_valid(&self, ctx: &GameContext) -> Result<(), InvalidActionError> {
+    fn is_valid(&self, ctx: &GameState) -> Result<(), InvalidActionError> {
         // Check if the card is available.
         match self.idx {
             None => self.require(
@@ -213,7 +226,7 @@ impl ActionExt for ReserveCardAction {
         )
     }
 
-    fn apply(&self, ctx: &mut GameContext) {
+    fn apply(&self, ctx: &mut GameState) {
         let card = match self.idx {
             None => {
                 let card = ctx.card_pool.take_from_pool(self.tier);
@@ -221,11 +234,14 @@ impl ActionExt for ReserveCardAction {
             }
             Some(idx) => ctx.card_pool.take(self.tier, idx).into(),
         };
+        ctx.hash ^= zobrist::reserved(ctx.current_player, &card);
+        ctx.toggle_tokens();
         let player = &mut ctx.players[ctx.current_player];
         player.reserved_cards.push(card);
         if ctx.tokens.get(Color::Yellow) > 0 {
             ctx.tokens.sub(Color::Yellow, 1);
             player.tokens.add(Color::Yellow, 1);
         }
+        ctx.toggle_tokens();
     }
 }
@@ -0,0 +1,47 @@
+//! The internals of the engine measured by the benches, not a stable API.
+
+use crate::action::ActionExt;
+use crate::GameState;
+use splendor_core::{DropTokensAction, PlayerAction, SelectNoblesAction};
+
+/// An action of any step of a turn.
+#[derive(Debug, Copy, Clone)]
+pub enum Action {
+    /// The action of the turn.
+    Player(PlayerAction),
+    /// Dropping tokens after the action.
+    DropTokens(DropTokensAction),
+    /// Visiting a noble at the end of the turn.
+    SelectNoble(SelectNoblesAction),
+}
+
+impl Action {
+    /// Check the action with [`ActionExt::is_valid`].
+    pub fn is_valid(&self, state: &GameState) -> bool {
+        match self {
+            Action::Player(action) => action.is_valid(state),
+            Action::DropTokens(action) => action.is_valid(state),
+            Action::SelectNoble(action) => action.is_valid(state),
+        }
+        .is_ok()
+    }
+
+    /// Apply the action with [`ActionExt::apply`], without checking it.
+    pub fn apply(&self, state: &mut GameState) {
+        match self {
+            Action::Player(action) => action.apply(state),
+            Action::DropTokens(action) => action.apply(state),
+            Action::SelectNoble(action) => action.apply(state),
+        }
+    }
+}
+
+/// Get the remaining cards of the tiers with `CardPool::remaining`.
+pub fn remaining(state: &GameState) -> [usize; 3] {
+    state.card_pool.remaining()
+}
+
+/// Get the number of the revealed cards of the tiers with `CardPool::revealed`.
+pub fn revealed(state: &GameState) -> [usize; 3] {
+    state.card_pool.revealed()
+}
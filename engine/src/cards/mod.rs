@@ -1,3 +1,4 @@
+use crate::zobrist;
 use rand::prelude::SliceRandom;
 use rand::RngCore;
 use smallvec::SmallVec;
@@ -10,15 +11,17 @@ use defs::*;
 /// A struct to represent the card pool.
 #[derive(Debug, Clone, Default)]
 pub(crate) struct CardPool {
-    pool: [SmallVec<Card, 40>; 3],
+    pub pool: [SmallVec<Card, 40>; 3],
     pub revealed: [SmallVec<Card, 4>; 3],
+    /// The Zobrist hash of the pool, updated with every move.
+    hash: u64,
 }
 
 impl CardPool {
     pub fn with_rng<R: RngCore>(rng: &mut R) -> Self {
-        let mut tier1 = SmallVec::from(TIRE1_CARDS.as_slice());
-        let mut tier2 = SmallVec::from(TIRE2_CARDS.as_slice());
-        let mut tier3 = SmallVec::from(TIRE3_CARDS.as_slice());
+        let mut tier1 = Self::cards(Tier::I);
+        let mut tier2 = Self::cards(Tier::II);
+        let mut tier3 = Self::cards(Tier::III);
 
         tier1.shuffle(rng);
         tier2.shuffle(rng);
@@ -34,10 +37,75 @@ impl CardPool {
                 this.reveal(tier);
             }
         }
+        this.hash = this.compute_hash();
 
         this
     }
 
+    /// Create a card pool with the given cards, the top of each pool is the last card.
+    pub fn from_parts(pool: [SmallVec<Card, 40>; 3], revealed: [SmallVec<Card, 4>; 3]) -> Self {
+        let mut this = CardPool {
+            pool,
+            revealed,
+            hash: 0,
+        };
+        this.hash = this.compute_hash();
+        this
+    }
+
+    /// Compute the Zobrist hash of the pool from scratch.
+    pub fn compute_hash(&self) -> u64 {
+        let deck = self.pool.iter().flat_map(|cards| {
+            cards
+                .iter()
+                .enumerate()
+                .map(|(depth, card)| zobrist::deck(depth, card))
+        });
+        let revealed = self.revealed.iter().flatten().map(zobrist::revealed);
+        deck.chain(revealed).fold(0, |acc, key| acc ^ key)
+    }
+
+    /// Get the Zobrist hash of the pool.
+    #[inline(always)]
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Get all the cards of the given tier.
+    pub fn cards(tier: Tier) -> SmallVec<Card, 40> {
+        SmallVec::from(match tier {
+            Tier::I => TIRE1_CARDS.as_slice(),
+            Tier::II => TIRE2_CARDS.as_slice(),
+            Tier::III => TIRE3_CARDS.as_slice(),
+        })
+    }
+
+    /// Get the ID of a card, the index of the card in all cards of the tiers in order.
+    pub fn card_id(card: &Card) -> u8 {
+        let (offset, cards) = match card.tier {
+            Tier::I => (0, TIRE1_CARDS.as_slice()),
+            Tier::II => (TIRE1_CARDS.len(), TIRE2_CARDS.as_slice()),
+            Tier::III => (
+                TIRE1_CARDS.len() + TIRE2_CARDS.len(),
+                TIRE3_CARDS.as_slice(),
+            ),
+        };
+        let idx = cards.iter().position(|c| c == card).expect("unknown card");
+        (offset + idx) as u8
+    }
+
+    /// Get the card of an ID.
+    pub fn card_by_id(id: u8) -> Card {
+        let id = id as usize;
+        if id < TIRE1_CARDS.len() {
+            TIRE1_CARDS[id]
+        } else if id < TIRE1_CARDS.len() + TIRE2_CARDS.len() {
+            TIRE2_CARDS[id - TIRE1_CARDS.len()]
+        } else {
+            TIRE3_CARDS[id - TIRE1_CARDS.len() - TIRE2_CARDS.len()]
+        }
+    }
+
     /// Reveal a card from the given tier.
     ///
     /// Returns true if a new card is revealed, false otherwise.
@@ -47,6 +115,7 @@ impl CardPool {
         let cards = &mut self.pool[tier as usize];
         if revealed.len() < 4 {
             if let Some(card) = cards.pop() {
+                self.hash ^= zobrist::deck(cards.len(), &card) ^ zobrist::revealed(&card);
                 revealed.push(card);
                 return true;
             }
@@ -86,6 +155,7 @@ impl CardPool {
     pub fn take(&mut self, tier: Tier, index: usize) -> Card {
         assert!(index < self.revealed[tier as usize].len());
         let card = self.revealed[tier as usize].remove(index);
+        self.hash ^= zobrist::revealed(&card);
         self.reveal(tier);
         card
     }
@@ -93,6 +163,9 @@ impl CardPool {
     /// Take a card from top of the pool.
     #[inline(always)]
     pub fn take_from_pool(&mut self, tier: Tier) -> Card {
-        self.pool[tier as usize].pop().unwrap()
+        let cards = &mut self.pool[tier as usize];
+        let card = cards.pop().unwrap();
+        self.hash ^= zobrist::deck(cards.len(), &card);
+        card
     }
 }
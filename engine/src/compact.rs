@@ -0,0 +1,551 @@
+use crate::cards::CardPool;
+use crate::error::InvalidActionError;
+use crate::game::GameState;
+use crate::nobles::{Nobles, NOBLES};
+use crate::player::PlayerContext;
+use smallvec::{smallvec, SmallVec};
+use splendor_core::{
+    BuyCardSource, Color, ColorVec, DevelopmentCards, DropTokensAction, PlayerAction, ReservedCard,
+    SelectNoblesAction, TakeTokenAction, Tier, MAX_PLAYERS,
+};
+use std::ops::{AddAssign, SubAssign};
+
+/// An empty slot of cards or nobles.
+const EMPTY: u8 = u8::MAX;
+/// The offset of the deck of each tier in the decks of [`CompactState`].
+const DECK_OFFSETS: [usize; 3] = [0, 40, 70];
+const LAST_ROUND: u8 = 1;
+const GAME_END: u8 = 2;
+
+/// The token counts of the colors, packed in the nibbles of a `u32`.
+///
+/// No color ever has more than 7 tokens, so the nibbles never carry. The counts sent by the
+/// players can be anything, they are checked against the unpacked counts before being packed.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+struct PackedTokens(u32);
+
+impl PackedTokens {
+    #[inline(always)]
+    fn get(self, color: Color) -> u8 {
+        (self.0 >> (color as u32 * 4)) as u8 & 0xf
+    }
+
+    #[inline(always)]
+    fn lanes(self) -> impl Iterator<Item = u8> {
+        (0..6).map(move |lane| (self.0 >> (lane * 4)) as u8 & 0xf)
+    }
+
+    #[inline(always)]
+    fn total(self) -> u8 {
+        self.lanes().sum()
+    }
+}
+
+impl From<ColorVec> for PackedTokens {
+    #[inline(always)]
+    fn from(tokens: ColorVec) -> Self {
+        debug_assert!(tokens.iter().all(|cnt| cnt <= 0xf), "{tokens:?} overflows");
+        PackedTokens(
+            tokens
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (lane, cnt)| acc | (cnt as u32) << (lane * 4)),
+        )
+    }
+}
+
+impl From<PackedTokens> for ColorVec {
+    #[inline(always)]
+    fn from(tokens: PackedTokens) -> Self {
+        let mut lanes = tokens.lanes();
+        let mut next = || lanes.next().unwrap();
+        ColorVec::new(next(), next(), next(), next(), next(), next())
+    }
+}
+
+impl AddAssign for PackedTokens {
+    #[inline(always)]
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl SubAssign for PackedTokens {
+    #[inline(always)]
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+/// The number of the filled slots, the filled slots come first.
+#[inline(always)]
+fn slots_len(slots: &[u8]) -> usize {
+    slots.iter().take_while(|id| **id != EMPTY).count()
+}
+
+/// Remove a slot and shift the slots after it.
+#[inline(always)]
+fn slots_remove(slots: &mut [u8], idx: usize) -> u8 {
+    let id = slots[idx];
+    slots.copy_within(idx + 1.., idx);
+    slots[slots.len() - 1] = EMPTY;
+    id
+}
+
+#[inline(always)]
+fn slots_push(slots: &mut [u8], id: u8) {
+    let len = slots_len(slots);
+    slots[len] = id;
+}
+
+fn noble_id(noble: &splendor_core::Noble) -> u8 {
+    NOBLES
+        .iter()
+        .position(|n| n == noble)
+        .expect("unknown noble") as u8
+}
+
+/// A player of a [`CompactState`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct CompactPlayer {
+    tokens: PackedTokens,
+    bonus: ColorVec,
+    /// The points of the development cards.
+    points: u8,
+    /// The bitset of the IDs of the development cards.
+    owned: u128,
+    /// The IDs of the reserved cards, the order is kept as it's part of the actions.
+    reserved: [u8; 3],
+    /// The bitset of the slots of the invisible reserved cards.
+    invisible: u8,
+    /// The bitset of the visited nobles.
+    nobles: u16,
+}
+
+impl CompactPlayer {
+    const fn new() -> Self {
+        CompactPlayer {
+            tokens: PackedTokens(0),
+            bonus: ColorVec::empty(),
+            points: 0,
+            owned: 0,
+            reserved: [EMPTY; 3],
+            invisible: 0,
+            nobles: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn total_points(&self) -> u8 {
+        self.points + self.nobles.count_ones() as u8 * 3
+    }
+
+    #[inline(always)]
+    fn remove_reserved(&mut self, idx: usize) -> u8 {
+        let low = self.invisible & ((1 << idx) - 1);
+        self.invisible = low | ((self.invisible >> 1) & !((1 << idx) - 1));
+        slots_remove(&mut self.reserved, idx)
+    }
+}
+
+/// A compact representation of [`GameState`], for search bots bottlenecked on copying states.
+///
+/// Cards are `u8` IDs, the development cards and the visited nobles are bitsets and the
+/// tokens are packed in nibbles. The state is `Copy` and a few hundred bytes, and plays by
+/// the same rules as [`GameState`].
+///
+/// The development cards and the visited nobles of a player come back in the order of their
+/// IDs when the state is converted back, everything else keeps its order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CompactState {
+    n_players: u8,
+    current_player: u8,
+    nop_count: u8,
+    flags: u8,
+    current_round: u16,
+    tokens: PackedTokens,
+    /// The decks of the tiers, the top of each deck is the last card.
+    decks: [u8; 90],
+    deck_len: [u8; 3],
+    revealed: [[u8; 4]; 3],
+    nobles: [u8; MAX_PLAYERS + 1],
+    players: [CompactPlayer; MAX_PLAYERS],
+}
+
+impl From<&GameState> for CompactState {
+    fn from(state: &GameState) -> Self {
+        let mut decks = [EMPTY; 90];
+        let mut deck_len = [0; 3];
+        let mut revealed = [[EMPTY; 4]; 3];
+        for tier in 0..3 {
+            let pool = &state.card_pool.pool[tier];
+            for (depth, card) in pool.iter().enumerate() {
+                decks[DECK_OFFSETS[tier] + depth] = CardPool::card_id(card);
+            }
+            deck_len[tier] = pool.len() as u8;
+            for (slot, card) in state.card_pool.revealed[tier].iter().enumerate() {
+                revealed[tier][slot] = CardPool::card_id(card);
+            }
+        }
+        let mut nobles = [EMPTY; MAX_PLAYERS + 1];
+        for (slot, noble) in state.nobles.iter().enumerate() {
+            nobles[slot] = noble_id(noble);
+        }
+        let mut players = [CompactPlayer::new(); MAX_PLAYERS];
+        for (player, ctx) in players.iter_mut().zip(state.players.iter()) {
+            player.tokens = ctx.tokens.into();
+            player.bonus = ctx.development_cards.bonus;
+            player.points = ctx.development_cards.points;
+            for card in ctx.development_cards.iter() {
+                player.owned |= 1 << CardPool::card_id(card);
+            }
+            for (slot, card) in ctx.reserved_cards.iter().enumerate() {
+                player.reserved[slot] = CardPool::card_id(&card.card);
+                player.invisible |= (card.invisible as u8) << slot;
+            }
+            for noble in ctx.nobles.iter() {
+                player.nobles |= 1 << noble_id(noble);
+            }
+        }
+        CompactState {
+            n_players: state.n_players as u8,
+            current_player: state.current_player as u8,
+            nop_count: state.nop_count as u8,
+            flags: (state.last_round as u8 * LAST_ROUND) | (state.game_end as u8 * GAME_END),
+            current_round: state.current_round as u16,
+            tokens: state.tokens.into(),
+            decks,
+            deck_len,
+            revealed,
+            nobles,
+            players,
+        }
+    }
+}
+
+impl From<&CompactState> for GameState {
+    fn from(state: &CompactState) -> Self {
+        let pool = [0, 1, 2].map(|tier| {
+            let offset = DECK_OFFSETS[tier];
+            state.decks[offset..offset + state.deck_len[tier] as usize]
+                .iter()
+                .map(|id| CardPool::card_by_id(*id))
+                .collect()
+        });
+        let revealed = state.revealed.map(|slots| {
+            slots[..slots_len(&slots)]
+                .iter()
+                .map(|id| CardPool::card_by_id(*id))
+                .collect()
+        });
+        let nobles = state.nobles[..slots_len(&state.nobles)]
+            .iter()
+            .map(|id| NOBLES[*id as usize])
+            .collect();
+        let players = state.players[..state.n_players as usize]
+            .iter()
+            .enumerate()
+            .map(|(idx, player)| {
+                let mut development_cards = DevelopmentCards::new();
+                for id in 0..90 {
+                    if player.owned & (1 << id) != 0 {
+                        development_cards.add(CardPool::card_by_id(id));
+                    }
+                }
+                PlayerContext {
+                    idx,
+                    tokens: player.tokens.into(),
+                    development_cards,
+                    reserved_cards: player.reserved[..slots_len(&player.reserved)]
+                        .iter()
+                        .enumerate()
+                        .map(|(slot, id)| {
+                            ReservedCard::new(
+                                CardPool::card_by_id(*id),
+                                player.invisible & (1 << slot) != 0,
+                            )
+                        })
+                        .collect(),
+                    nobles: (0..NOBLES.len())
+                        .filter(|id| player.nobles & (1 << id) != 0)
+                        .map(|id| NOBLES[id])
+                        .collect(),
+                }
+            })
+            .collect();
+        let mut game = GameState {
+            n_players: state.n_players as usize,
+            last_round: state.flags & LAST_ROUND != 0,
+            game_end: state.flags & GAME_END != 0,
+            nop_count: state.nop_count as usize,
+            current_round: state.current_round as usize,
+            current_player: state.current_player as usize,
+            tokens: state.tokens.into(),
+            card_pool: CardPool::from_parts(pool, revealed),
+            nobles: Nobles(nobles),
+            players,
+            hash: 0,
+        };
+        game.hash = game.compute_hash();
+        game
+    }
+}
+
+impl CompactState {
+    #[inline(always)]
+    fn require(&self, cond: bool, reason: &'static str) -> Result<(), InvalidActionError> {
+        if !cond {
+            return Err(InvalidActionError {
+                player: self.current_player as usize,
+                reason,
+            });
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn player(&self) -> &CompactPlayer {
+        &self.players[self.current_player as usize]
+    }
+
+    #[inline(always)]
+    fn player_mut(&mut self) -> &mut CompactPlayer {
+        &mut self.players[self.current_player as usize]
+    }
+
+    /// Take a revealed card and reveal a new card.
+    #[inline(always)]
+    fn take_revealed(&mut self, tier: Tier, idx: usize) -> u8 {
+        let tier = tier as usize;
+        let id = slots_remove(&mut self.revealed[tier], idx);
+        if let Some(top) = self.pop_deck(tier) {
+            slots_push(&mut self.revealed[tier], top);
+        }
+        id
+    }
+
+    /// Take the top card of a deck.
+    #[inline(always)]
+    fn pop_deck(&mut self, tier: usize) -> Option<u8> {
+        if self.deck_len[tier] == 0 {
+            return None;
+        }
+        self.deck_len[tier] -= 1;
+        let slot = &mut self.decks[DECK_OFFSETS[tier] + self.deck_len[tier] as usize];
+        Some(std::mem::replace(slot, EMPTY))
+    }
+
+    /// Take an action of the current player.
+    pub fn apply_action(&mut self, action: &PlayerAction) -> Result<(), InvalidActionError> {
+        match action {
+            PlayerAction::TakeTokens(action) => {
+                let tokens = action.tokens();
+                self.require(tokens.get(Color::Yellow) == 0, "cannot take yellow tokens")?;
+                self.require(
+                    tokens.le(&self.tokens.into()),
+                    "not enough tokens available",
+                )?;
+                let packed = PackedTokens::from(*tokens);
+                let colors = tokens.iter().filter(|cnt| *cnt > 0).count();
+                match action {
+                    TakeTokenAction::ThreeDifferent(_) => {
+                        self.require(
+                            tokens.iter().all(|cnt| cnt <= 1),
+                            "cannot take more than one token",
+                        )?;
+                        self.require(colors <= 3, "cannot take more than 3 tokens")?;
+                    }
+                    TakeTokenAction::TwoSame(_) => {
+                        self.require(
+                            tokens.iter().all(|cnt| cnt <= 2),
+                            "cannot take more than two tokens",
+                        )?;
+                        self.require(colors == 1, "must take tokens of the same color")?;
+                    }
+                }
+                self.tokens -= packed;
+                self.player_mut().tokens += packed;
+            }
+            PlayerAction::BuyCard(action) => {
+                let player = self.player();
+                let id = match action.source {
+                    BuyCardSource::Revealed { tier, idx } => {
+                        self.revealed[tier as usize].get(idx).copied()
+                    }
+                    BuyCardSource::Reserved(idx) => player.reserved.get(idx).copied(),
+                }
+                .filter(|id| *id != EMPTY);
+                self.require(id.is_some(), "card index out of range")?;
+                let card = CardPool::card_by_id(id.unwrap());
+                self.require(
+                    action.uses.le(&player.tokens.into()),
+                    "not enough tokens to use",
+                )?;
+                let uses = PackedTokens::from(action.uses);
+                let available = player.bonus + action.uses;
+                let diff = available
+                    .iter()
+                    .zip(card.requires.iter())
+                    .map(|(a, b)| b.saturating_sub(a))
+                    .sum::<u8>();
+                self.require(diff == action.uses.get(Color::Yellow), "invalid token use")?;
+
+                let id = match action.source {
+                    BuyCardSource::Revealed { tier, idx } => self.take_revealed(tier, idx),
+                    BuyCardSource::Reserved(idx) => self.player_mut().remove_reserved(idx),
+                };
+                let player = self.player_mut();
+                player.owned |= 1 << id;
+                player.bonus.add(card.bonus, 1);
+                player.points += card.points;
+                player.tokens -= uses;
+                self.tokens += uses;
+            }
+            PlayerAction::ReserveCard(action) => {
+                let tier = action.tier as usize;
+                match action.idx {
+                    None => self.require(self.deck_len[tier] > 0, "no cards available in pool"),
+                    Some(idx) => self.require(
+                        slots_len(&self.revealed[tier]) > idx,
+                        "card index out of range",
+                    ),
+                }?;
+                self.require(
+                    slots_len(&self.player().reserved) < 3,
+                    "cannot reserve more than 3 cards",
+                )?;
+
+                let (id, invisible) = match action.idx {
+                    None => (self.pop_deck(tier).unwrap(), true),
+                    Some(idx) => (self.take_revealed(action.tier, idx), false),
+                };
+                let yellow = self.tokens.get(Color::Yellow) > 0;
+                let player = self.player_mut();
+                let slot = slots_len(&player.reserved);
+                player.reserved[slot] = id;
+                player.invisible |= (invisible as u8) << slot;
+                if yellow {
+                    let yellow = PackedTokens::from(ColorVec::new(0, 0, 0, 0, 0, 1));
+                    player.tokens += yellow;
+                    self.tokens -= yellow;
+                }
+            }
+            PlayerAction::Nop => self.nop_count += 1,
+        }
+        Ok(())
+    }
+
+    /// Does the current player hold more than 10 tokens.
+    pub fn needs_drop(&self) -> bool {
+        self.player().tokens.total() > 10
+    }
+
+    /// Drop tokens of the current player.
+    pub fn drop_tokens(&mut self, action: &DropTokensAction) -> Result<(), InvalidActionError> {
+        self.require(self.needs_drop(), "no need to drop")?;
+        self.require(
+            action.0.le(&self.player().tokens.into()),
+            "not enough tokens to drop",
+        )?;
+        let tokens = PackedTokens::from(action.0);
+        self.player_mut().tokens -= tokens;
+        self.tokens += tokens;
+        Ok(())
+    }
+
+    /// Get the index of the nobles the current player can visit.
+    pub fn noble_visits(&self) -> SmallVec<usize, { MAX_PLAYERS + 1 }> {
+        let bonus = self.player().bonus;
+        self.nobles[..slots_len(&self.nobles)]
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| NOBLES[**id as usize].requires.le(&bonus))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Visit a noble with the current player.
+    pub fn visit_noble(&mut self, action: &SelectNoblesAction) -> Result<(), InvalidActionError> {
+        self.require(
+            action.0 < slots_len(&self.nobles),
+            "noble index out of range",
+        )?;
+        let id = self.nobles[action.0];
+        self.require(
+            NOBLES[id as usize].requires.le(&self.player().bonus),
+            "noble requirements not met",
+        )?;
+        slots_remove(&mut self.nobles, action.0);
+        self.player_mut().nobles |= 1 << id;
+        Ok(())
+    }
+
+    /// End the turn of the current player.
+    ///
+    /// Returns the winners if the game ended, no winner means the game got stuck.
+    pub fn end_turn(&mut self) -> Option<SmallVec<usize, MAX_PLAYERS>> {
+        if self.player().total_points() >= 15 {
+            self.flags |= LAST_ROUND;
+        }
+
+        if self.flags & LAST_ROUND != 0 && self.current_player == self.n_players - 1 {
+            self.flags |= GAME_END;
+            return Some(self.get_winner());
+        }
+
+        self.current_player = (self.current_player + 1) % self.n_players;
+        if self.current_player == 0 {
+            if self.nop_count == self.n_players {
+                self.flags |= GAME_END;
+                return Some(smallvec![]);
+            }
+            self.nop_count = 0;
+            self.current_round += 1;
+        }
+        None
+    }
+
+    fn get_winner(&self) -> SmallVec<usize, MAX_PLAYERS> {
+        // player with the most points wins, then the one with the fewest development cards
+        let players = &self.players[..self.n_players as usize];
+        let best = players
+            .iter()
+            .map(|p| (p.total_points(), std::cmp::Reverse(p.owned.count_ones())))
+            .max()
+            .unwrap();
+        players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| (p.total_points(), std::cmp::Reverse(p.owned.count_ones())) == best)
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Get the number of players in the game.
+    pub fn n_players(&self) -> usize {
+        self.n_players as usize
+    }
+
+    /// Get is the game ended.
+    pub fn game_end(&self) -> bool {
+        self.flags & GAME_END != 0
+    }
+
+    /// Get the current round.
+    pub fn current_round(&self) -> usize {
+        self.current_round as usize
+    }
+
+    /// Get the current player.
+    pub fn current_player(&self) -> usize {
+        self.current_player as usize
+    }
+
+    /// Get the points of each player.
+    pub fn points(&self) -> SmallVec<u8, MAX_PLAYERS> {
+        self.players[..self.n_players as usize]
+            .iter()
+            .map(|p| p.total_points())
+            .collect()
+    }
+}
@@ -1,17 +1,23 @@
 use serde::Serialize;
 use std::fmt::Display;
 
+/// An error of a game step.
 #[derive(Debug, Serialize, thiserror::Error)]
 pub enum StepError {
+    /// The actor took an action which breaks the rules.
     #[error("invalid action: {0}")]
     InvalidAction(#[from] InvalidActionError),
+    /// The actor failed to take an action.
     #[error("actor error: {0}")]
     ActorError(ActorError),
 }
 
+/// An action which breaks the rules.
 #[derive(Debug, Serialize, thiserror::Error)]
 pub struct InvalidActionError {
+    /// The player who took the action.
     pub player: usize,
+    /// Why the action is invalid.
     pub reason: &'static str,
 }
 
@@ -21,8 +27,10 @@ impl Display for InvalidActionError {
     }
 }
 
+/// An actor failure, which keeps the message only.
 #[derive(Debug, Serialize, thiserror::Error)]
 pub struct ActorError {
+    /// The message of the error.
     pub msg: String,
 }
 
@@ -1,19 +1,22 @@
 use crate::action::ActionExt;
 use crate::cards::CardPool;
-use crate::error::StepError;
+use crate::error::{InvalidActionError, StepError};
 use crate::nobles::Nobles;
 use crate::player::PlayerContext;
+use crate::zobrist;
+use rand::seq::SliceRandom;
 use rand::RngCore;
 use smallvec::{smallvec, SmallVec};
 use splendor_core::{
-    ActionRecord, CardPoolSnapshot, Color, ColorVec, GameSnapshot, Noble, PlayerActor, Record,
-    SelectNoblesAction, Tier, MAX_PLAYERS,
+    ActionRecord, CardPoolSnapshot, CardView, ColorVec, DropTokensAction, GameSnapshot, Noble,
+    PlayerAction, PlayerActor, Record, ReservedCard, SelectNoblesAction, Tier, MAX_PLAYERS,
 };
-use std::array;
 
-/// A struct to represent the game context.
-#[derive(Debug)]
-pub struct GameContext {
+/// A struct to represent the state of a game, without the player actors.
+///
+/// The state can be cloned and stepped synchronously, which is what search based bots need.
+#[derive(Debug, Clone)]
+pub struct GameState {
     pub(crate) n_players: usize,
     pub(crate) last_round: bool,
     pub(crate) game_end: bool,
@@ -26,25 +29,23 @@ pub struct GameContext {
     pub(crate) nobles: Nobles,
 
     pub(crate) players: SmallVec<PlayerContext, MAX_PLAYERS>,
+
+    /// The Zobrist hash of the state without the card pool, updated with every move.
+    pub(crate) hash: u64,
+}
+
+/// A struct to represent the game context.
+#[derive(Debug)]
+pub struct GameContext {
+    pub(crate) state: GameState,
     pub(crate) player_actors: SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>,
 
     pub(crate) records: Vec<Record>,
 }
 
-impl GameContext {
-    /// Create a new game context with thread_rng.
-    pub fn random(player_actors: SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>) -> Self {
-        GameContext::with_rng(&mut rand::thread_rng(), player_actors)
-    }
-
-    /// Create a new game context with a given random number generator.
-    ///
-    /// This can be used to create a game context with a specific seed for reproducibility.
-    pub fn with_rng<R: RngCore>(
-        rng: &mut R,
-        player_actors: SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>,
-    ) -> Self {
-        let n_players = player_actors.len();
+impl GameState {
+    /// Create a new game state with a given random number generator.
+    pub fn with_rng<R: RngCore>(rng: &mut R, n_players: usize) -> Self {
         let tokens = match n_players {
             2 => ColorVec::new(4, 4, 4, 4, 4, 5),
             3 => ColorVec::new(5, 5, 5, 5, 5, 5),
@@ -53,8 +54,8 @@ impl GameContext {
         };
         let card_pool = CardPool::with_rng(rng);
         let nobles = Nobles::with_rng(rng, n_players + 1);
-        let players = SmallVec::from_buf(array::from_fn(PlayerContext::new));
-        GameContext {
+        let players = (0..n_players).map(PlayerContext::new).collect();
+        let mut state = GameState {
             n_players,
             current_round: 0,
             last_round: false,
@@ -65,113 +66,166 @@ impl GameContext {
             card_pool,
             nobles,
             players,
-            player_actors,
-            records: Vec::new(),
+            hash: 0,
+        };
+        state.hash = state.compute_hash();
+        state
+    }
+
+    /// Sample a state which the current player of the snapshot cannot tell from the real one.
+    ///
+    /// The cards left in the pool and the invisible reserved cards of other players are drawn
+    /// from the cards nobody has seen.
+    pub fn determinize<R: RngCore>(snapshot: &GameSnapshot, rng: &mut R) -> Self {
+        let mut unseen = [Tier::I, Tier::II, Tier::III].map(CardPool::cards);
+        let seen = snapshot
+            .card_pool
+            .revealed
+            .iter()
+            .flatten()
+            .chain(
+                snapshot
+                    .players
+                    .iter()
+                    .flat_map(|p| p.development_cards.iter()),
+            )
+            .chain(snapshot.players.iter().flat_map(|p| {
+                p.reserved_cards.iter().filter_map(|c| match c {
+                    CardView::Visible(card) => Some(card),
+                    CardView::Invisible(_) => None,
+                })
+            }));
+        for card in seen {
+            let cards = &mut unseen[card.tier as usize];
+            if let Some(idx) = cards.iter().position(|c| c == card) {
+                cards.swap_remove(idx);
+            }
+        }
+        for cards in unseen.iter_mut() {
+            cards.shuffle(rng);
         }
+
+        let players = snapshot
+            .players
+            .iter()
+            .map(|p| PlayerContext {
+                idx: p.idx,
+                tokens: p.tokens,
+                development_cards: p.development_cards.clone(),
+                reserved_cards: p
+                    .reserved_cards
+                    .iter()
+                    .map(|c| match c {
+                        CardView::Visible(card) => ReservedCard::new(*card, false),
+                        CardView::Invisible(tier) => {
+                            let card = unseen[*tier as usize]
+                                .pop()
+                                .expect("no unseen card left for an invisible reserved card");
+                            ReservedCard::new(card, true)
+                        }
+                    })
+                    .collect(),
+                nobles: p.nobles.clone(),
+            })
+            .collect();
+        for (cards, remaining) in unseen.iter_mut().zip(snapshot.card_pool.remaining) {
+            cards.truncate(remaining);
+        }
+
+        let mut state = GameState {
+            n_players: snapshot.players.len(),
+            last_round: snapshot.last_round,
+            game_end: false,
+            nop_count: 0,
+            current_round: snapshot.current_round,
+            current_player: snapshot.current_player,
+            tokens: snapshot.tokens,
+            card_pool: CardPool::from_parts(unseen, snapshot.card_pool.revealed.clone()),
+            nobles: Nobles(snapshot.nobles.clone()),
+            players,
+            hash: 0,
+        };
+        state.hash = state.compute_hash();
+        state
     }
 
-    /// Step the game by one turn.
-    pub async fn step(&mut self) -> Result<Option<SmallVec<usize, MAX_PLAYERS>>, StepError> {
-        let snapshot = self.snapshot();
-        let action = self.player_actors[self.current_player]
-            .get_action(snapshot)
-            .await?;
+    /// Enumerate the actions the current player can take.
+    pub fn legal_actions(&self) -> Vec<PlayerAction> {
+        self.snapshot().legal_actions()
+    }
+
+    /// Take an action of the current player.
+    pub fn apply_action(&mut self, action: &PlayerAction) -> Result<(), InvalidActionError> {
         action.is_valid(self)?;
-        info!("Player#{} action: {:?}", self.current_player, action);
         action.apply(self);
-        self.records.push(Record::PlayerAction(ActionRecord::new(
-            self.current_player,
-            action,
-        )));
         if action.is_nop() {
             self.nop_count += 1;
         }
+        Ok(())
+    }
 
-        if self.players[self.current_player].tokens.total() > 10 {
-            info!("Player#{} needs to drop tokens", self.current_player);
-            let snapshot = self.snapshot();
-            let drop_tokens = self.player_actors[self.current_player]
-                .drop_tokens(snapshot)
-                .await?;
-            drop_tokens.is_valid(self)?;
-            info!(
-                "Player#{} dropped tokens: {:?}",
-                self.current_player, drop_tokens
-            );
-            drop_tokens.apply(self);
-            self.records.push(Record::DropTokens(ActionRecord::new(
-                self.current_player,
-                drop_tokens,
-            )));
-        }
+    /// Does the current player hold more than 10 tokens.
+    pub fn needs_drop(&self) -> bool {
+        self.players[self.current_player].tokens.total() > 10
+    }
+
+    /// Drop tokens of the current player.
+    pub fn drop_tokens(&mut self, action: &DropTokensAction) -> Result<(), InvalidActionError> {
+        action.is_valid(self)?;
+        action.apply(self);
+        Ok(())
+    }
 
-        let noble_visits: SmallVec<(usize, Noble), { MAX_PLAYERS + 1 }> = self
-            .nobles
+    /// Get the index of the nobles the current player can visit.
+    pub fn noble_visits(&self) -> SmallVec<usize, { MAX_PLAYERS + 1 }> {
+        self.nobles
             .iter()
-            .copied()
             .enumerate()
             .filter(|(_, n)| {
                 n.requires
                     .le(&self.players[self.current_player].development_cards.bonus)
             })
-            .collect();
-        if !noble_visits.is_empty() {
-            info!(
-                "Player#{}({:?}) can visit nobles: {:?}",
-                self.current_player,
-                self.players[self.current_player].development_cards.bonus,
-                noble_visits
-            );
-            let (action, noble) = if noble_visits.len() > 1 {
-                let snapshot = self.snapshot();
-                let select_noble = self.player_actors[self.current_player]
-                    .select_noble(snapshot)
-                    .await?;
-                select_noble.is_valid(self)?;
-                (select_noble, self.nobles.get(select_noble.0))
-            } else {
-                (SelectNoblesAction(noble_visits[0].0), noble_visits[0].1)
-            };
-            debug_assert!(action.is_valid(self).is_ok());
-            info!("Player#{} visited noble: {:?}", self.current_player, noble);
-            action.apply(self);
-            self.records.push(Record::VisitNoble(ActionRecord::new(
-                self.current_player,
-                noble,
-            )));
-        }
+            .map(|(idx, _)| idx)
+            .collect()
+    }
 
-        info!(
-            "Player#{} ended turn, current points: {}",
-            self.current_player,
-            self.players[self.current_player].points()
-        );
-        if self.players[self.current_player].points() >= 15 {
-            info!(
-                "Player#{} reached 15 points, this is the last turn",
-                self.current_player
-            );
+    /// Visit a noble with the current player.
+    pub fn visit_noble(
+        &mut self,
+        action: &SelectNoblesAction,
+    ) -> Result<Noble, InvalidActionError> {
+        action.is_valid(self)?;
+        let noble = self.nobles.get(action.0);
+        action.apply(self);
+        Ok(noble)
+    }
+
+    /// End the turn of the current player.
+    ///
+    /// Returns the winners if the game ended, no winner means the game got stuck.
+    pub fn end_turn(&mut self) -> Option<SmallVec<usize, MAX_PLAYERS>> {
+        if self.players[self.current_player].points() >= 15 && !self.last_round {
             self.last_round = true;
+            self.hash ^= zobrist::last_round();
         }
 
         if self.last_round && self.current_player == self.n_players - 1 {
-            info!("Game ended");
             self.game_end = true;
-            return Ok(Some(self.get_winner()));
+            return Some(self.get_winner());
         }
 
+        self.hash ^= zobrist::current_player(self.current_player);
         self.current_player = (self.current_player + 1) % self.n_players;
+        self.hash ^= zobrist::current_player(self.current_player);
         if self.current_player == 0 {
-            trace!("Round {} ended", self.current_round);
             if self.nop_count == self.n_players {
-                self.pretty_print();
-                error!("All players did nothing, game stuck, {:#?}", self);
-                return Ok(Some(smallvec![]));
+                self.game_end = true;
+                return Some(smallvec![]);
             }
             self.nop_count = 0;
             self.current_round += 1;
         }
-        Ok(None)
+        None
     }
 
     fn get_winner(&self) -> SmallVec<usize, MAX_PLAYERS> {
@@ -210,68 +264,15 @@ impl GameContext {
             winner_candidates
         };
 
-        info!("Winner(s): {:?}", winner);
-
         winner
     }
 
     fn pretty_print(&self) {
-        println!("current round: {}", self.current_round);
-        println!("current player: {}", self.current_player);
-        print!("tokens remaining:");
-        for (color, cnt) in self.tokens.iter().enumerate() {
-            print!("    {} {}", Color::try_from(color).unwrap().emoji(), cnt);
-        }
-        println!();
-        println!("card pool remaining: {:?}", self.card_pool.remaining());
-
-        for (tier, cards) in self.card_pool.revealed.iter().enumerate() {
-            print!("{}", Tier::try_from(tier).unwrap().emoji());
-            for card in cards {
-                print!("    ");
-                print!("{}", card.bonus.emoji());
-                print!(" {:?}", card.points);
-                for cnt in card.requires.iter().take(5) {
-                    print!(" {:?}", cnt);
-                }
-            }
-            println!();
-        }
-
-        for player in self.players.iter() {
-            println!("player#{}", player.idx);
-            println!("points: {}", player.points());
-            println!("tokens:");
-            for (color, cnt) in player.tokens.iter().enumerate() {
-                print!("    {} {}", Color::try_from(color).unwrap().emoji(), cnt);
-            }
-            println!();
-            println!("development cards:");
-            for (color, cards) in player.development_cards.inner.iter().enumerate() {
-                print!("{}", Color::try_from(color).unwrap().emoji());
-                for card in cards {
-                    print!("    ");
-                    print!("{}", card.tier.emoji());
-                    print!(" {:?}", card.points);
-                }
-                println!();
-            }
-            println!("reserved cards:");
-            for card in player.reserved_cards.iter() {
-                print!("    ");
-                print!("{}", card.card.bonus.emoji());
-                print!(" {:?}", card.card.points);
-                for cnt in card.card.requires.iter().take(5) {
-                    print!(" {:?}", cnt);
-                }
-                println!();
-            }
-            println!();
-        }
+        println!("{:#}", self.snapshot());
     }
 }
 
-impl GameContext {
+impl GameState {
     /// Get the number of players in the game.
     pub fn n_players(&self) -> usize {
         self.n_players
@@ -302,14 +303,183 @@ impl GameContext {
         self.tokens
     }
 
-    /// Create a snapshot of the game.
+    /// Get the Zobrist hash of the state.
+    ///
+    /// The hash covers the tokens, the revealed cards, the order of the decks, the holdings of
+    /// the players, the nobles, the current player and the last round flag. It is updated with
+    /// every move, so getting it is free.
+    pub fn hash(&self) -> u64 {
+        self.hash ^ self.card_pool.hash()
+    }
+
+    /// Get the points of each player.
+    pub fn points(&self) -> SmallVec<u8, MAX_PLAYERS> {
+        self.players
+            .iter()
+            .take(self.n_players)
+            .map(|p| p.points())
+            .collect()
+    }
+
+    /// Create a snapshot of the game, seen by the current player.
     pub fn snapshot(&self) -> GameSnapshot {
         GameSnapshot::from(self)
     }
 }
 
-impl From<&GameContext> for GameSnapshot {
-    fn from(ctx: &GameContext) -> Self {
+impl GameContext {
+    /// Create a new game context with thread_rng.
+    pub fn random(player_actors: SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>) -> Self {
+        GameContext::with_rng(&mut rand::thread_rng(), player_actors)
+    }
+
+    /// Create a new game context with a given random number generator.
+    ///
+    /// This can be used to create a game context with a specific seed for reproducibility.
+    pub fn with_rng<R: RngCore>(
+        rng: &mut R,
+        player_actors: SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>,
+    ) -> Self {
+        GameContext {
+            state: GameState::with_rng(rng, player_actors.len()),
+            player_actors,
+            records: Vec::new(),
+        }
+    }
+
+    /// Step the game by one turn.
+    pub async fn step(&mut self) -> Result<Option<SmallVec<usize, MAX_PLAYERS>>, StepError> {
+        let current_player = self.state.current_player;
+        let snapshot = self.snapshot();
+        let action = self.player_actors[current_player]
+            .get_action(snapshot)
+            .await?;
+        self.state.apply_action(&action)?;
+        info!("Player#{} action: {}", current_player, action);
+        self.records.push(Record::PlayerAction(ActionRecord::new(
+            current_player,
+            action,
+        )));
+
+        if self.state.needs_drop() {
+            info!("Player#{} needs to drop tokens", current_player);
+            let snapshot = self.snapshot();
+            let drop_tokens = self.player_actors[current_player]
+                .drop_tokens(snapshot)
+                .await?;
+            self.state.drop_tokens(&drop_tokens)?;
+            info!("Player#{} dropped tokens: {}", current_player, drop_tokens);
+            self.records.push(Record::DropTokens(ActionRecord::new(
+                current_player,
+                drop_tokens,
+            )));
+        }
+
+        let noble_visits = self.state.noble_visits();
+        if !noble_visits.is_empty() {
+            info!(
+                "Player#{}({:?}) can visit nobles: {:?}",
+                current_player,
+                self.state.players[current_player].development_cards.bonus,
+                noble_visits
+            );
+            let action = if noble_visits.len() > 1 {
+                let snapshot = self.snapshot();
+                self.player_actors[current_player]
+                    .select_noble(snapshot)
+                    .await?
+            } else {
+                SelectNoblesAction(noble_visits[0])
+            };
+            let noble = self.state.visit_noble(&action)?;
+            info!("Player#{} visited noble: {}", current_player, noble);
+            self.records
+                .push(Record::VisitNoble(ActionRecord::new(current_player, noble)));
+        }
+
+        let points = self.state.players[current_player].points();
+        info!(
+            "Player#{} ended turn, current points: {}",
+            current_player, points
+        );
+        if points >= 15 {
+            info!(
+                "Player#{} reached 15 points, this is the last turn",
+                current_player
+            );
+        }
+
+        let winners = self.state.end_turn();
+        match &winners {
+            Some(winners) if winners.is_empty() => {
+                self.state.pretty_print();
+                error!("All players did nothing, game stuck, {:#?}", self);
+            }
+            Some(winners) => {
+                info!("Game ended");
+                info!("Winner(s): {:?}", winners);
+            }
+            None if self.state.current_player == 0 => {
+                trace!("Round {} ended", self.state.current_round - 1);
+            }
+            None => {}
+        }
+        Ok(winners)
+    }
+
+    /// Get the state of the game.
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Get the number of players in the game.
+    pub fn n_players(&self) -> usize {
+        self.state.n_players()
+    }
+
+    /// Get is the game in the last round.
+    pub fn last_round(&self) -> bool {
+        self.state.last_round()
+    }
+
+    /// Get is the game ended.
+    pub fn game_end(&self) -> bool {
+        self.state.game_end()
+    }
+
+    /// Get the current round.
+    pub fn current_round(&self) -> usize {
+        self.state.current_round()
+    }
+
+    /// Get the current player.
+    pub fn current_player(&self) -> usize {
+        self.state.current_player()
+    }
+
+    /// Get the tokens available in the game.
+    pub fn tokens(&self) -> ColorVec {
+        self.state.tokens()
+    }
+
+    /// Get the points of each player.
+    pub fn points(&self) -> SmallVec<u8, MAX_PLAYERS> {
+        self.state.points()
+    }
+
+    /// Get the records of the game so far.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Create a snapshot of the game.
+    pub fn snapshot(&self) -> GameSnapshot {
+        self.state.snapshot()
+    }
+}
+
+impl From<&GameState> for GameSnapshot {
+    fn from(ctx: &GameState) -> Self {
         Self {
             last_round: ctx.last_round,
             current_round: ctx.current_round,
@@ -5,12 +5,22 @@
 extern crate log;
 
 mod action;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
 mod cards;
+mod compact;
 mod error;
 mod game;
 mod nobles;
 mod player;
+#[cfg(feature = "test")]
+#[doc(hidden)]
+pub mod testing;
 #[cfg(all(feature = "test", test))]
 mod tests;
+mod zobrist;
 
-pub use game::GameContext;
+pub use compact::CompactState;
+pub use error::{ActorError, InvalidActionError, StepError};
+pub use game::{GameContext, GameState};
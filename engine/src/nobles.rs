@@ -4,7 +4,7 @@ use smallvec::SmallVec;
 use splendor_core::{ColorVec, Noble, MAX_PLAYERS};
 
 /// A struct to represent the noble pool.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Nobles(pub SmallVec<Noble, { MAX_PLAYERS + 1 }>);
 
 impl Nobles {
@@ -36,7 +36,7 @@ impl Nobles {
     }
 }
 
-const NOBLES: [Noble; 10] = [
+pub(crate) const NOBLES: [Noble; 10] = [
     Noble::new(ColorVec::new(0, 0, 4, 4, 0, 0)),
     Noble::new(ColorVec::new(0, 4, 0, 0, 4, 0)),
     Noble::new(ColorVec::new(4, 0, 0, 0, 4, 0)),
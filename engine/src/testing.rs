@@ -0,0 +1,35 @@
+//! Helpers shared by the tests and the benches, not a stable API.
+
+use crate::GameState;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use smallvec::SmallVec;
+use splendor_core::naive_actors::GreedyActor;
+use splendor_core::MAX_PLAYERS;
+
+/// Play up to `turns` turns of [`GreedyActor`]s, returns the winners if the game ends.
+///
+/// The action, token drops and noble visit of every turn are the best ones of the actor, which
+/// is seeded with a fixed seed so the same state always plays the same game.
+pub fn play_greedy(state: &mut GameState, turns: usize) -> Option<SmallVec<usize, MAX_PLAYERS>> {
+    let mut greedy = GreedyActor::new(SmallRng::seed_from_u64(42));
+    for _ in 0..turns {
+        state
+            .apply_action(&greedy.best_action(&state.snapshot()))
+            .unwrap();
+        if state.needs_drop() {
+            state
+                .drop_tokens(&greedy.best_drop(&state.snapshot()))
+                .unwrap();
+        }
+        if !state.noble_visits().is_empty() {
+            state
+                .visit_noble(&greedy.best_noble(&state.snapshot()))
+                .unwrap();
+        }
+        if let Some(winners) = state.end_turn() {
+            return Some(winners);
+        }
+    }
+    None
+}
@@ -1,42 +1,94 @@
-use crate::GameContext;
+use crate::action::ActionExt;
+use crate::testing::play_greedy;
+use crate::{CompactState, GameContext, GameState};
 use rand::prelude::*;
 use smallvec::SmallVec;
-use splendor_core::naive_actors::RandomActor;
+use splendor_core::naive_actors::{GreedyActor, RandomActor};
 use splendor_core::*;
-use std::array;
+use strum::IntoEnumIterator;
 
 #[ctor::ctor]
 fn init_logger() {
     pretty_env_logger::init();
 }
 
-fn create_game(seed: Option<u64>) -> GameContext {
-    let actors: [Box<dyn PlayerActor>; MAX_PLAYERS] = array::from_fn(|_| match seed {
-        Some(seed) => Box::new(RandomActor::new(SmallRng::seed_from_u64(seed))) as _,
-        None => Box::new(RandomActor::new(SmallRng::from_entropy())) as _,
-    });
+fn create_game(n_players: usize, seed: Option<u64>) -> GameContext {
+    let actors = (0..n_players)
+        .map(|_| match seed {
+            Some(seed) => Box::new(RandomActor::new(SmallRng::seed_from_u64(seed))) as _,
+            None => Box::new(RandomActor::new(SmallRng::from_entropy())) as _,
+        })
+        .collect::<SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>>();
     match seed {
         Some(seed) => {
             let mut rng = SmallRng::seed_from_u64(seed);
-            GameContext::with_rng(&mut rng, SmallVec::from_buf(actors))
+            GameContext::with_rng(&mut rng, actors)
+        }
+        None => GameContext::random(actors),
+    }
+}
+
+/// A state of a greedy game, right after an action leading to a state which meets `pred`.
+fn find_state(pred: impl Fn(&GameState) -> bool) -> GameState {
+    for seed in 0..20 {
+        let mut state = GameState::with_rng(&mut SmallRng::seed_from_u64(seed), MAX_PLAYERS);
+        while !state.game_end() {
+            for action in state.legal_actions() {
+                let mut next = state.clone();
+                next.apply_action(&action).unwrap();
+                if pred(&next) {
+                    return next;
+                }
+            }
+            play_greedy(&mut state, 1);
         }
-        None => GameContext::random(SmallVec::from_buf(actors)),
     }
+    panic!("no state found");
+}
+
+/// Drop the excess tokens of the current player from the colors they hold most of.
+fn drop_excess(state: &GameState) -> DropTokensAction {
+    let tokens = &state.players[state.current_player].tokens;
+    let mut drop = ColorVec::default();
+    for _ in 10..tokens.total() {
+        let color = Color::iter()
+            .max_by_key(|c| tokens.get(*c) - drop.get(*c))
+            .unwrap();
+        drop.add(color, 1);
+    }
+    DropTokensAction(drop)
 }
 
 #[tokio::test]
 async fn test_game() {
     for _ in 0..100000 {
-        let mut game = create_game(None);
+        let mut game = create_game(MAX_PLAYERS, None);
         while !game.game_end() {
             game.step().await.unwrap();
         }
     }
 }
 
+#[tokio::test]
+async fn test_n_players() {
+    for n_players in 2..=MAX_PLAYERS {
+        for _ in 0..100 {
+            let mut game = create_game(n_players, None);
+            assert_eq!(game.snapshot().players.len(), n_players);
+            let winners = loop {
+                if let Some(winners) = game.step().await.unwrap() {
+                    break winners;
+                }
+            };
+            assert!(winners.iter().all(|&winner| winner < n_players));
+            assert_eq!(game.points().len(), n_players);
+        }
+    }
+}
+
 #[tokio::test]
 async fn test_serialize() {
-    let mut game = create_game(Some(42));
+    let mut game = create_game(MAX_PLAYERS, Some(42));
     while !game.game_end() {
         game.step().await.unwrap();
     }
@@ -46,8 +98,519 @@ async fn test_serialize() {
 
     assert_eq!(
         serialized,
-        r#"{"last_round":true,"current_round":34,"current_player":3,"tokens":[0,1,1,2,1,5],"card_pool":{"remaining":[0,15,8],"revealed":[[],[{"tier":1,"bonus":"white","points":2,"requires":[3,0,0,5,0,0]},{"tier":1,"bonus":"red","points":1,"requires":[3,3,0,2,0,0]},{"tier":1,"bonus":"blue","points":1,"requires":[3,2,3,0,0,0]},{"tier":1,"bonus":"white","points":2,"requires":[0,0,0,5,0,0]}],[{"tier":2,"bonus":"black","points":5,"requires":[3,0,0,7,0,0]},{"tier":2,"bonus":"white","points":3,"requires":[3,3,3,5,0,0]},{"tier":2,"bonus":"white","points":4,"requires":[7,0,0,0,0,0]},{"tier":2,"bonus":"blue","points":4,"requires":[0,0,0,0,7,0]}]]},"nobles":[{"requires":[4,0,0,4,0,0]}],"players":[{"idx":0,"points":5,"tokens":[1,0,4,2,3,0],"development_cards":{"points":5,"bonus":[1,2,3,4,2,0],"inner":[[{"tier":0,"bonus":"black","points":0,"requires":[0,1,1,1,1,0]}],[{"tier":0,"bonus":"blue","points":0,"requires":[3,0,0,0,0,0]},{"tier":0,"bonus":"blue","points":0,"requires":[0,0,2,2,1,0]}],[{"tier":0,"bonus":"green","points":0,"requires":[0,1,0,0,2,0]},{"tier":0,"bonus":"green","points":0,"requires":[1,1,0,1,1,0]},{"tier":0,"bonus":"green","points":0,"requires":[0,0,0,3,0,0]}],[{"tier":0,"bonus":"red","points":0,"requires":[0,2,1,0,0,0]},{"tier":0,"bonus":"red","points":0,"requires":[0,0,0,2,2,0]},{"tier":0,"bonus":"red","points":1,"requires":[0,0,0,0,4,0]},{"tier":1,"bonus":"red","points":3,"requires":[0,0,0,6,0,0]}],[{"tier":0,"bonus":"white","points":1,"requires":[0,0,4,0,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[2,2,0,0,0,0]}]]},"reserved_cards":[{"type":"visible","view":{"tier":2,"bonus":"white","points":5,"requires":[7,0,0,0,3,0]}},{"type":"visible","view":{"tier":0,"bonus":"white","points":0,"requires":[1,1,0,0,3,0]}},{"type":"invisible","view":2}],"nobles":[]},{"idx":1,"points":5,"tokens":[0,3,1,3,1,0],"development_cards":{"points":5,"bonus":[2,3,1,2,1,0],"inner":[[{"tier":0,"bonus":"black","points":1,"requires":[0,4,0,0,0,0]},{"tier":0,"bonus":"black","points":0,"requires":[1,0,1,3,0,0]}],[{"tier":0,"bonus":"blue","points":0,"requires":[2,0,0,0,1,0]},{"tier":0,"bonus":"blue","points":0,"requires":[1,0,1,2,1,0]},{"tier":1,"bonus":"blue","points":3,"requires":[0,6,0,0,0,0]}],[{"tier":0,"bonus":"green","points":0,"requires":[2,1,0,2,0,0]}],[{"tier":0,"bonus":"red","points":0,"requires":[3,0,0,1,1,0]},{"tier":0,"bonus":"red","points":0,"requires":[2,0,1,0,2,0]}],[{"tier":1,"bonus":"white","points":1,"requires":[2,0,3,2,0,0]}]]},"reserved_cards":[{"type":"visible","view":{"tier":1,"bonus":"black","points":2,"requires":[0,0,0,0,5,0]}},{"type":"visible","view":{"tier":1,"bonus":"red","points":2,"requires":[5,0,0,0,0,0]}},{"type":"visible","view":{"tier":2,"bonus":"blue","points":3,"requires":[5,0,3,3,3,0]}}],"nobles":[]},{"idx":2,"points":11,"tokens":[4,1,1,0,1,0],"development_cards":{"points":8,"bonus":[3,3,3,2,4,0],"inner":[[{"tier":0,"bonus":"black","points":0,"requires":[0,0,3,0,0,0]},{"tier":0,"bonus":"black","points":0,"requires":[0,0,2,1,0,0]},{"tier":0,"bonus":"black","points":0,"requires":[0,0,2,0,2,0]}],[{"tier":0,"bonus":"blue","points":0,"requires":[1,0,1,1,1,0]},{"tier":1,"bonus":"blue","points":2,"requires":[0,3,0,0,5,0]},{"tier":0,"bonus":"blue","points":1,"requires":[0,0,0,4,0,0]}],[{"tier":1,"bonus":"green","points":1,"requires":[2,3,0,0,2,0]},{"tier":1,"bonus":"green","points":2,"requires":[0,5,3,0,0,0]},{"tier":1,"bonus":"green","points":2,"requires":[1,2,0,0,4,0]}],[{"tier":0,"bonus":"red","points":0,"requires":[0,0,0,0,3,0]},{"tier":0,"bonus":"red","points":0,"requires":[1,1,1,0,2,0]}],[{"tier":0,"bonus":"white","points":0,"requires":[0,3,0,0,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,1,2,1,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,0,0,2,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,1,1,1,0,0]}]]},"reserved_cards":[{"type":"invisible","view":1},{"type":"visible","view":{"tier":2,"bonus":"red","points":5,"requires":[0,0,7,3,0,0]}},{"type":"visible","view":{"tier":2,"bonus":"blue","points":4,"requires":[3,3,0,0,6,0]}}],"nobles":[{"requires":[3,0,0,3,3,0]}]},{"idx":3,"points":15,"tokens":[2,2,0,0,1,0],"development_cards":{"points":6,"bonus":[3,2,4,1,2,0],"inner":[[{"tier":0,"bonus":"black","points":0,"requires":[0,2,0,1,2,0]},{"tier":0,"bonus":"black","points":0,"requires":[0,2,1,1,1,0]},{"tier":2,"bonus":"black","points":3,"requires":[0,3,5,3,3,0]}],[{"tier":0,"bonus":"blue","points":0,"requires":[0,1,3,1,0,0]},{"tier":0,"bonus":"blue","points":0,"requires":[2,0,2,0,0,0]}],[{"tier":0,"bonus":"green","points":1,"requires":[4,0,0,0,0,0]},{"tier":0,"bonus":"green","points":0,"requires":[0,3,1,0,1,0]},{"tier":0,"bonus":"green","points":0,"requires":[0,2,0,2,0,0]},{"tier":0,"bonus":"green","points":0,"requires":[2,1,0,1,1,0]}],[{"tier":0,"bonus":"red","points":0,"requires":[1,1,1,0,1,0]}],[{"tier":1,"bonus":"white","points":2,"requires":[2,0,1,4,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,2,2,0,0,0]}]]},"reserved_cards":[{"type":"visible","view":{"tier":2,"bonus":"green","points":4,"requires":[0,7,0,0,0,0]}},{"type":"visible","view":{"tier":2,"bonus":"green","points":3,"requires":[3,3,0,3,5,0]}}],"nobles":[{"requires":[0,4,0,0,4,0]},{"requires":[0,4,4,0,0,0]},{"requires":[0,3,3,3,0,0]}]}]}"#
+        r#"{"last_round":true,"current_round":41,"current_player":3,"tokens":[0,0,0,0,0,4],"card_pool":{"remaining":[0,5,7],"revealed":[[],[{"tier":1,"bonus":"white","points":2,"requires":[3,0,0,5,0,0]},{"tier":1,"bonus":"black","points":1,"requires":[0,2,2,0,3,0]},{"tier":1,"bonus":"green","points":2,"requires":[0,0,5,0,0,0]},{"tier":1,"bonus":"black","points":1,"requires":[2,0,3,0,3,0]}],[{"tier":2,"bonus":"black","points":5,"requires":[3,0,0,7,0,0]},{"tier":2,"bonus":"white","points":4,"requires":[7,0,0,0,0,0]},{"tier":2,"bonus":"blue","points":4,"requires":[0,0,0,0,7,0]},{"tier":2,"bonus":"red","points":3,"requires":[3,5,3,0,3,0]}]]},"nobles":[{"requires":[4,0,0,4,0,0]},{"requires":[3,0,0,3,3,0]},{"requires":[0,4,4,0,0,0]},{"requires":[0,3,3,3,0,0]}],"players":[{"idx":0,"points":15,"tokens":[2,0,0,3,1,0],"development_cards":{"points":15,"bonus":[1,2,4,5,5,0],"inner":[[{"tier":0,"bonus":"black","points":0,"requires":[0,1,1,1,1,0]}],[{"tier":0,"bonus":"blue","points":0,"requires":[3,0,0,0,0,0]},{"tier":0,"bonus":"blue","points":0,"requires":[0,0,2,2,1,0]}],[{"tier":0,"bonus":"green","points":0,"requires":[0,1,0,0,2,0]},{"tier":0,"bonus":"green","points":0,"requires":[1,1,0,1,1,0]},{"tier":0,"bonus":"green","points":0,"requires":[0,0,0,3,0,0]},{"tier":1,"bonus":"green","points":1,"requires":[0,0,2,3,3,0]}],[{"tier":0,"bonus":"red","points":0,"requires":[0,2,1,0,0,0]},{"tier":0,"bonus":"red","points":0,"requires":[0,0,0,2,2,0]},{"tier":0,"bonus":"red","points":1,"requires":[0,0,0,0,4,0]},{"tier":1,"bonus":"red","points":3,"requires":[0,0,0,6,0,0]},{"tier":2,"bonus":"red","points":4,"requires":[0,0,7,0,0,0]}],[{"tier":0,"bonus":"white","points":1,"requires":[0,0,4,0,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[2,2,0,0,0,0]},{"tier":1,"bonus":"white","points":2,"requires":[0,0,0,5,0,0]},{"tier":1,"bonus":"white","points":3,"requires":[0,0,0,0,6,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,1,0,0,3,0]}]]},"reserved_cards":[{"type":"visible","view":{"tier":2,"bonus":"white","points":5,"requires":[7,0,0,0,3,0]}}],"nobles":[]},{"idx":1,"points":11,"tokens":[0,3,3,1,3,0],"development_cards":{"points":11,"bonus":[3,4,1,3,2,0],"inner":[[{"tier":0,"bonus":"black","points":1,"requires":[0,4,0,0,0,0]},{"tier":0,"bonus":"black","points":0,"requires":[1,0,1,3,0,0]},{"tier":1,"bonus":"black","points":2,"requires":[0,5,0,3,0,0]}],[{"tier":0,"bonus":"blue","points":0,"requires":[2,0,0,0,1,0]},{"tier":0,"bonus":"blue","points":0,"requires":[1,0,1,2,1,0]},{"tier":1,"bonus":"blue","points":3,"requires":[0,6,0,0,0,0]},{"tier":1,"bonus":"blue","points":2,"requires":[4,0,0,1,2,0]}],[{"tier":0,"bonus":"green","points":0,"requires":[2,1,0,2,0,0]}],[{"tier":0,"bonus":"red","points":0,"requires":[3,0,0,1,1,0]},{"tier":0,"bonus":"red","points":0,"requires":[2,0,1,0,2,0]},{"tier":1,"bonus":"red","points":1,"requires":[3,3,0,2,0,0]}],[{"tier":1,"bonus":"white","points":1,"requires":[2,0,3,2,0,0]},{"tier":1,"bonus":"white","points":1,"requires":[0,3,0,3,2,0]}]]},"reserved_cards":[{"type":"visible","view":{"tier":1,"bonus":"black","points":2,"requires":[0,0,0,0,5,0]}},{"type":"visible","view":{"tier":1,"bonus":"red","points":2,"requires":[5,0,0,0,0,0]}},{"type":"visible","view":{"tier":2,"bonus":"blue","points":3,"requires":[5,0,3,3,3,0]}}],"nobles":[]},{"idx":2,"points":12,"tokens":[3,1,2,2,2,0],"development_cards":{"points":9,"bonus":[3,4,3,2,4,0],"inner":[[{"tier":0,"bonus":"black","points":0,"requires":[0,0,3,0,0,0]},{"tier":0,"bonus":"black","points":0,"requires":[0,0,2,1,0,0]},{"tier":0,"bonus":"black","points":0,"requires":[0,0,2,0,2,0]}],[{"tier":0,"bonus":"blue","points":0,"requires":[1,0,1,1,1,0]},{"tier":1,"bonus":"blue","points":2,"requires":[0,3,0,0,5,0]},{"tier":0,"bonus":"blue","points":1,"requires":[0,0,0,4,0,0]},{"tier":1,"bonus":"blue","points":1,"requires":[3,2,3,0,0,0]}],[{"tier":1,"bonus":"green","points":1,"requires":[2,3,0,0,2,0]},{"tier":1,"bonus":"green","points":2,"requires":[0,5,3,0,0,0]},{"tier":1,"bonus":"green","points":2,"requires":[1,2,0,0,4,0]}],[{"tier":0,"bonus":"red","points":0,"requires":[0,0,0,0,3,0]},{"tier":0,"bonus":"red","points":0,"requires":[1,1,1,0,2,0]}],[{"tier":0,"bonus":"white","points":0,"requires":[0,3,0,0,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,1,2,1,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,0,0,2,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,1,1,1,0,0]}]]},"reserved_cards":[{"type":"invisible","view":1},{"type":"visible","view":{"tier":2,"bonus":"red","points":5,"requires":[0,0,7,3,0,0]}},{"type":"visible","view":{"tier":2,"bonus":"blue","points":4,"requires":[3,3,0,0,6,0]}}],"nobles":[{"requires":[0,4,0,0,4,0]}]},{"idx":3,"points":9,"tokens":[2,3,2,1,1,1],"development_cards":{"points":9,"bonus":[3,3,4,2,2,0],"inner":[[{"tier":0,"bonus":"black","points":0,"requires":[0,2,0,1,2,0]},{"tier":0,"bonus":"black","points":0,"requires":[0,2,1,1,1,0]},{"tier":2,"bonus":"black","points":3,"requires":[0,3,5,3,3,0]}],[{"tier":0,"bonus":"blue","points":0,"requires":[0,1,3,1,0,0]},{"tier":0,"bonus":"blue","points":0,"requires":[2,0,2,0,0,0]},{"tier":1,"bonus":"blue","points":1,"requires":[0,2,2,3,0,0]}],[{"tier":0,"bonus":"green","points":1,"requires":[4,0,0,0,0,0]},{"tier":0,"bonus":"green","points":0,"requires":[0,3,1,0,1,0]},{"tier":0,"bonus":"green","points":0,"requires":[0,2,0,2,0,0]},{"tier":0,"bonus":"green","points":0,"requires":[2,1,0,1,1,0]}],[{"tier":0,"bonus":"red","points":0,"requires":[1,1,1,0,1,0]},{"tier":1,"bonus":"red","points":2,"requires":[5,0,0,0,3,0]}],[{"tier":1,"bonus":"white","points":2,"requires":[2,0,1,4,0,0]},{"tier":0,"bonus":"white","points":0,"requires":[1,2,2,0,0,0]}]]},"reserved_cards":[{"type":"visible","view":{"tier":2,"bonus":"green","points":4,"requires":[0,7,0,0,0,0]}},{"type":"visible","view":{"tier":2,"bonus":"green","points":3,"requires":[3,3,0,3,5,0]}},{"type":"visible","view":{"tier":2,"bonus":"white","points":3,"requires":[3,3,3,5,0,0]}}],"nobles":[]}]}"#
+    );
+}
+
+#[tokio::test]
+async fn test_random_noble() {
+    // the random actor only selects a noble the current player can visit
+    let mut rng = SmallRng::seed_from_u64(42);
+    let mut visits = 0;
+    for seed in 0..20 {
+        let mut actor = RandomActor::new(SmallRng::seed_from_u64(seed));
+        let mut state = GameState::with_rng(&mut SmallRng::seed_from_u64(seed), MAX_PLAYERS);
+        loop {
+            let action = *state.legal_actions().choose(&mut rng).unwrap();
+            state.apply_action(&action).unwrap();
+            if state.needs_drop() {
+                let action = actor.drop_tokens(state.snapshot()).await.unwrap();
+                state.drop_tokens(&action).unwrap();
+            }
+            let nobles = state.noble_visits();
+            if !nobles.is_empty() {
+                let action = actor.select_noble(state.snapshot()).await.unwrap();
+                assert!(nobles.contains(&action.0), "{action:?} not in {nobles:?}");
+                state.visit_noble(&action).unwrap();
+                visits += 1;
+            }
+            if state.end_turn().is_some() {
+                break;
+            }
+        }
+    }
+    assert!(visits > 0);
+}
+
+#[tokio::test]
+async fn test_legal_actions() {
+    for seed in 0..20 {
+        let mut game = create_game(MAX_PLAYERS, Some(seed));
+        while !game.game_end() {
+            let snapshot = game.snapshot();
+            let mask = snapshot.legal_action_mask();
+            for action in snapshot.legal_actions() {
+                action.is_valid(game.state()).unwrap();
+                assert_eq!(action.notation().parse::<PlayerAction>(), Ok(action));
+                let index = action.action_index().unwrap();
+                assert!(mask[index]);
+                assert_eq!(snapshot.decode_action(index), Some(action));
+            }
+            let observation = snapshot.encode();
+            assert_eq!(observation.len(), OBSERVATION_SIZE);
+            let players = &encoding_spec().fields[5];
+            assert_eq!(
+                observation[players.offset + 1],
+                snapshot.current_player().points as f32
+            );
+            game.step().await.unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_greedy() {
+    async fn play(seed: u64, greedy: usize) -> (SmallVec<usize, MAX_PLAYERS>, Vec<Record>) {
+        let actors = (0..MAX_PLAYERS)
+            .map(|i| {
+                let rng = SmallRng::seed_from_u64(seed + i as u64);
+                if i == greedy {
+                    Box::new(GreedyActor::new(rng)) as _
+                } else {
+                    Box::new(RandomActor::new(rng)) as _
+                }
+            })
+            .collect::<SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>>();
+        let mut game = GameContext::with_rng(&mut SmallRng::seed_from_u64(seed), actors);
+        let winners = loop {
+            if let Some(winners) = game.step().await.unwrap() {
+                break winners;
+            }
+        };
+        (winners, game.records().to_vec())
+    }
+
+    let mut wins = 0;
+    for seed in 0..20 {
+        let greedy = seed as usize % MAX_PLAYERS;
+        let (winners, records) = play(seed, greedy).await;
+        if winners.contains(&greedy) {
+            wins += 1;
+        }
+        // deterministic under the same seed
+        let (_, replayed) = play(seed, greedy).await;
+        assert_eq!(
+            serde_json::to_string(&records).unwrap(),
+            serde_json::to_string(&replayed).unwrap()
+        );
+    }
+    assert!(wins >= 15, "greedy actor only won {wins} of 20 games");
+}
+
+#[tokio::test]
+async fn test_determinize() {
+    let mut rng = SmallRng::seed_from_u64(42);
+    for seed in 0..20 {
+        let mut game = create_game(MAX_PLAYERS, Some(seed));
+        while !game.game_end() {
+            let snapshot = game.snapshot();
+            let state = GameState::determinize(&snapshot, &mut rng);
+            // the current player cannot tell the sampled state from the real one
+            assert_eq!(
+                serde_json::to_string(&state.snapshot()).unwrap(),
+                serde_json::to_string(&snapshot).unwrap()
+            );
+            game.step().await.unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_zobrist() {
+    fn full_hash(state: &GameState) -> u64 {
+        state.compute_hash() ^ state.card_pool.compute_hash()
+    }
+
+    for seed in 0..20 {
+        let mut state =
+            GameState::with_rng(&mut SmallRng::seed_from_u64(seed), 2 + seed as usize % 3);
+        while !state.game_end() {
+            assert_eq!(state.hash(), full_hash(&state));
+            // the hash follows every step of the turn of every legal action
+            for action in state.legal_actions() {
+                let mut state = state.clone();
+                state.apply_action(&action).unwrap();
+                assert_eq!(state.hash(), full_hash(&state));
+                if state.needs_drop() {
+                    state.drop_tokens(&drop_excess(&state)).unwrap();
+                    assert_eq!(state.hash(), full_hash(&state));
+                }
+                if let Some(&noble) = state.noble_visits().first() {
+                    state.visit_noble(&SelectNoblesAction(noble)).unwrap();
+                    assert_eq!(state.hash(), full_hash(&state));
+                }
+                state.end_turn();
+                assert_eq!(state.hash(), full_hash(&state));
+            }
+            play_greedy(&mut state, 1);
+        }
+        assert_eq!(state.hash(), full_hash(&state));
+    }
+
+    // the same position reached by different orders of moves
+    let take = |colors: [Color; 3]| {
+        let mut tokens = ColorVec::default();
+        for color in colors {
+            tokens.add(color, 1);
+        }
+        PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(tokens))
+    };
+    let a = take([Color::Black, Color::Blue, Color::Green]);
+    let b = take([Color::Blue, Color::Green, Color::Red]);
+    let c = take([Color::Red, Color::White, Color::Black]);
+    let initial = GameState::with_rng(&mut SmallRng::seed_from_u64(0), 2);
+    let play = |actions: [PlayerAction; 3]| {
+        let mut state = initial.clone();
+        for action in actions.iter() {
+            state.apply_action(action).unwrap();
+            state.end_turn();
+        }
+        state.hash()
+    };
+    assert_eq!(play([a, b, c]), play([c, b, a]));
+    assert_ne!(play([a, b, c]), play([b, a, c]));
+    assert_ne!(play([a, b, c]), initial.hash());
+}
+
+#[test]
+fn test_compact() {
+    for seed in 0..20 {
+        let mut state =
+            GameState::with_rng(&mut SmallRng::seed_from_u64(seed), 2 + seed as usize % 3);
+        while !state.game_end() {
+            let compact = CompactState::from(&state);
+            assert_eq!(GameState::from(&compact).hash(), state.hash());
+            // every legal action plays the same turn on both states
+            for action in state.legal_actions() {
+                let mut state = state.clone();
+                let mut compact = compact;
+                // invalid actions are rejected in the same way
+                if let PlayerAction::BuyCard(buy) = action {
+                    let invalid = PlayerAction::BuyCard(BuyCardAction {
+                        uses: buy.uses + ColorVec::new(0, 0, 0, 0, 0, 1),
+                        ..buy
+                    });
+                    let expected = state.clone().apply_action(&invalid).map_err(|e| e.reason);
+                    let actual = compact.apply_action(&invalid).map_err(|e| e.reason);
+                    assert_eq!(expected, actual);
+                }
+                state.apply_action(&action).unwrap();
+                compact.apply_action(&action).unwrap();
+                assert_eq!(CompactState::from(&state), compact);
+                assert_eq!(state.needs_drop(), compact.needs_drop());
+                if state.needs_drop() {
+                    let action = drop_excess(&state);
+                    state.drop_tokens(&action).unwrap();
+                    compact.drop_tokens(&action).unwrap();
+                }
+                assert_eq!(state.noble_visits(), compact.noble_visits());
+                if let Some(&noble) = state.noble_visits().first() {
+                    state.visit_noble(&SelectNoblesAction(noble)).unwrap();
+                    compact.visit_noble(&SelectNoblesAction(noble)).unwrap();
+                }
+                assert_eq!(state.end_turn(), compact.end_turn());
+                assert_eq!(CompactState::from(&state), compact);
+            }
+            play_greedy(&mut state, 1);
+        }
+    }
+}
+
+#[test]
+fn test_compact_crafted() {
+    // counts a malicious client could send, which do not fit in the packed tokens
+    let crafted = |tokens: ColorVec| {
+        Color::iter().flat_map(move |color| {
+            [16, 17, 0xf0, u8::MAX].map(|cnt| {
+                let mut tokens = tokens;
+                tokens.set(color, cnt);
+                tokens
+            })
+        })
+    };
+    for seed in 0..5 {
+        let mut state =
+            GameState::with_rng(&mut SmallRng::seed_from_u64(seed), 2 + seed as usize % 3);
+        while !state.game_end() {
+            let compact = CompactState::from(&state);
+            for action in state.legal_actions() {
+                let invalid = match action {
+                    PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(tokens)) => {
+                        crafted(tokens)
+                            .map(|tokens| {
+                                PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(tokens))
+                            })
+                            .collect()
+                    }
+                    PlayerAction::TakeTokens(TakeTokenAction::TwoSame(tokens)) => crafted(tokens)
+                        .map(|tokens| PlayerAction::TakeTokens(TakeTokenAction::TwoSame(tokens)))
+                        .collect(),
+                    PlayerAction::BuyCard(buy) => crafted(buy.uses)
+                        .map(|uses| PlayerAction::BuyCard(BuyCardAction { uses, ..buy }))
+                        .collect(),
+                    _ => vec![],
+                };
+                for invalid in invalid.iter() {
+                    let mut crafted = compact;
+                    let expected = invalid.is_valid(&state).map_err(|e| e.reason);
+                    let actual = crafted.apply_action(invalid).map_err(|e| e.reason);
+                    assert!(expected.is_err(), "{invalid:?}");
+                    assert_eq!(expected, actual, "{invalid:?}");
+                    assert_eq!(crafted, compact);
+                }
+
+                let mut state = state.clone();
+                let mut compact = compact;
+                state.apply_action(&action).unwrap();
+                compact.apply_action(&action).unwrap();
+                if state.needs_drop() {
+                    for drop in crafted(drop_excess(&state).0).map(DropTokensAction) {
+                        let mut crafted = compact;
+                        let expected = drop.is_valid(&state).map_err(|e| e.reason);
+                        let actual = crafted.drop_tokens(&drop).map_err(|e| e.reason);
+                        assert!(expected.is_err(), "{drop:?}");
+                        assert_eq!(expected, actual, "{drop:?}");
+                        assert_eq!(crafted, compact);
+                    }
+                }
+            }
+            play_greedy(&mut state, 1);
+        }
+    }
+}
+
+#[test]
+fn test_crafted_actions() {
+    // actions a malicious client could send, rejected without touching the state
+    let state = GameState::with_rng(&mut SmallRng::seed_from_u64(42), 2);
+    let max = ColorVec::new(u8::MAX, u8::MAX, u8::MAX, u8::MAX, u8::MAX, u8::MAX);
+    let actions = [
+        PlayerAction::TakeTokens(TakeTokenAction::ThreeDifferent(max)),
+        PlayerAction::TakeTokens(TakeTokenAction::TwoSame(max)),
+        PlayerAction::BuyCard(BuyCardAction {
+            source: BuyCardSource::Revealed {
+                tier: Tier::III,
+                idx: usize::MAX,
+            },
+            uses: ColorVec::default(),
+        }),
+        PlayerAction::BuyCard(BuyCardAction {
+            source: BuyCardSource::Revealed {
+                tier: Tier::I,
+                idx: 0,
+            },
+            uses: max,
+        }),
+        PlayerAction::BuyCard(BuyCardAction {
+            source: BuyCardSource::Reserved(0),
+            uses: ColorVec::default(),
+        }),
+        PlayerAction::ReserveCard(ReserveCardAction {
+            tier: Tier::II,
+            idx: Some(4),
+        }),
+    ];
+    for action in actions.iter() {
+        let mut crafted = state.clone();
+        assert!(crafted.apply_action(action).is_err(), "{action:?}");
+        assert_eq!(crafted.snapshot(), state.snapshot());
+    }
+
+    let mut crafted = state.clone();
+    assert!(crafted.drop_tokens(&DropTokensAction(max)).is_err());
+    for idx in [state.nobles.len(), usize::MAX] {
+        assert!(crafted.visit_noble(&SelectNoblesAction(idx)).is_err());
+    }
+    assert_eq!(crafted.snapshot(), state.snapshot());
+
+    // the counts which do not fit in the tokens of a player
+    let state = find_state(|state| state.needs_drop());
+    let excess = drop_excess(&state).0;
+    let mut crafted = state.clone();
+    for color in Color::iter() {
+        for cnt in [11, 16, u8::MAX] {
+            let mut tokens = excess;
+            tokens.set(color, cnt);
+            assert!(crafted.drop_tokens(&DropTokensAction(tokens)).is_err());
+        }
+    }
+    assert!(crafted.drop_tokens(&DropTokensAction(max)).is_err());
+    assert_eq!(crafted.snapshot(), state.snapshot());
+
+    // the cards which are not there
+    let state = find_state(|state| {
+        !state.players[state.current_player]
+            .reserved_cards
+            .is_empty()
+    });
+    let reserved = state.players[state.current_player].reserved_cards.len();
+    let mut actions = vec![];
+    for idx in [reserved, 3, usize::MAX] {
+        actions.push(BuyCardSource::Reserved(idx));
+    }
+    for tier in [Tier::I, Tier::II, Tier::III] {
+        let revealed = state.card_pool.revealed()[tier as usize];
+        for idx in [revealed, 4, usize::MAX] {
+            actions.push(BuyCardSource::Revealed { tier, idx });
+        }
+    }
+    for source in actions {
+        let action = PlayerAction::BuyCard(BuyCardAction {
+            source,
+            uses: ColorVec::default(),
+        });
+        let mut crafted = state.clone();
+        assert_eq!(
+            crafted.apply_action(&action).map_err(|e| e.reason),
+            Err("card index out of range"),
+            "{action:?}"
+        );
+        assert_eq!(crafted.snapshot(), state.snapshot());
+    }
+    // a tier which does not exist is not even an action
+    let json = r#"{"type":"buy_card","action":{"source":{"type":"revealed","location":{"tier":3,"idx":0}},"uses":[0,0,0,0,0,0]}}"#;
+    assert!(serde_json::from_str::<PlayerAction>(json).is_err());
+}
+
+#[test]
+fn test_noble_index() {
+    // the index right after the last noble was accepted, and visiting it panicked
+    let state = find_state(|state| !state.noble_visits().is_empty());
+    let mut crafted = state.clone();
+    let action = SelectNoblesAction(state.nobles.len());
+    assert_eq!(
+        crafted.visit_noble(&action).map_err(|e| e.reason),
+        Err("noble index out of range")
     );
+    assert_eq!(crafted.snapshot(), state.snapshot());
+    crafted
+        .visit_noble(&SelectNoblesAction(state.noble_visits()[0]))
+        .unwrap();
+}
+
+mod invariants {
+    use super::*;
+    use crate::cards::CardPool;
+    use proptest::prelude::*;
+
+    fn color_vec(max: u8) -> impl Strategy<Value = ColorVec> {
+        prop::array::uniform6(0..=max)
+            .prop_map(|[k, b, g, r, w, y]| ColorVec::new(k, b, g, r, w, y))
+    }
+
+    fn tier() -> impl Strategy<Value = Tier> {
+        prop_oneof![Just(Tier::I), Just(Tier::II), Just(Tier::III)]
+    }
+
+    /// Any action of a turn, most of them breaking the rules.
+    fn player_action() -> impl Strategy<Value = PlayerAction> {
+        prop_oneof![
+            color_vec(3).prop_map(|tokens| PlayerAction::TakeTokens(
+                TakeTokenAction::ThreeDifferent(tokens)
+            )),
+            color_vec(3)
+                .prop_map(|tokens| PlayerAction::TakeTokens(TakeTokenAction::TwoSame(tokens))),
+            (tier(), 0..5usize, color_vec(8)).prop_map(|(tier, idx, uses)| {
+                PlayerAction::BuyCard(BuyCardAction {
+                    source: BuyCardSource::Revealed { tier, idx },
+                    uses,
+                })
+            }),
+            (0..4usize, color_vec(8)).prop_map(|(idx, uses)| {
+                PlayerAction::BuyCard(BuyCardAction {
+                    source: BuyCardSource::Reserved(idx),
+                    uses,
+                })
+            }),
+            (tier(), prop::option::of(0..5usize))
+                .prop_map(|(tier, idx)| PlayerAction::ReserveCard(ReserveCardAction { tier, idx })),
+            Just(PlayerAction::Nop),
+        ]
+    }
+
+    /// A step of a game: an arbitrary action, an arbitrary drop and an arbitrary noble index,
+    /// with the index of the legal action taken when the arbitrary action is rejected.
+    fn step() -> impl Strategy<Value = (usize, PlayerAction, ColorVec, usize)> {
+        (
+            any::<usize>(),
+            player_action(),
+            color_vec(4),
+            0..MAX_PLAYERS + 3,
+        )
+    }
+
+    fn check_invariants(state: &GameState, initial_tokens: ColorVec) {
+        let tokens = state
+            .players
+            .iter()
+            .fold(state.tokens, |acc, p| acc + p.tokens);
+        assert_eq!(tokens, initial_tokens, "tokens are not conserved");
+
+        let pool = &state.card_pool;
+        let cards = pool.remaining().iter().sum::<usize>()
+            + pool.revealed().iter().sum::<usize>()
+            + state
+                .players
+                .iter()
+                .map(|p| p.reserved_cards.len() + p.development_cards.iter().count())
+                .sum::<usize>();
+        let all_cards = [Tier::I, Tier::II, Tier::III]
+            .map(|tier| CardPool::cards(tier).len())
+            .iter()
+            .sum::<usize>();
+        assert_eq!(cards, all_cards, "cards are not conserved");
+
+        for player in state.players.iter() {
+            let card_points = player
+                .development_cards
+                .iter()
+                .map(|c| c.points)
+                .sum::<u8>();
+            assert_eq!(player.points(), card_points + 3 * player.nobles.len() as u8);
+        }
+    }
 
-    game.records
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_invariants(
+            seed in any::<u64>(),
+            n_players in 2..=MAX_PLAYERS,
+            steps in prop::collection::vec(step(), 1..300),
+        ) {
+            let mut state = GameState::with_rng(&mut SmallRng::seed_from_u64(seed), n_players);
+            let initial_tokens = state.tokens;
+            for (choice, action, drop, noble) in steps {
+                check_invariants(&state, initial_tokens);
+                // the arbitrary action is played once accepted, otherwise a legal one
+                if state.apply_action(&action).is_err() {
+                    let actions = state.legal_actions();
+                    state.apply_action(&actions[choice % actions.len()]).unwrap();
+                }
+                check_invariants(&state, initial_tokens);
+
+                if state.drop_tokens(&DropTokensAction(drop)).is_ok() {
+                    check_invariants(&state, initial_tokens);
+                }
+                if state.needs_drop() {
+                    state.drop_tokens(&drop_excess(&state)).unwrap();
+                }
+                prop_assert!(state.players.iter().all(|p| p.tokens.total() <= 10));
+
+                if state.visit_noble(&SelectNoblesAction(noble)).is_err() {
+                    let visits = state.noble_visits();
+                    if !visits.is_empty() {
+                        let action = SelectNoblesAction(visits[noble % visits.len()]);
+                        state.visit_noble(&action).unwrap();
+                    }
+                }
+
+                if state.end_turn().is_some() {
+                    break;
+                }
+            }
+            check_invariants(&state, initial_tokens);
+        }
+    }
 }
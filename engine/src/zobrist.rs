@@ -0,0 +1,139 @@
+//! Zobrist keys of the game state.
+//!
+//! Every feature of the state has a key, and the hash of a state is the XOR of the keys of
+//! its features, so a move only toggles the keys of the features it changes. The keys are
+//! derived from the features with SplitMix64 instead of a random table, which keeps the
+//! hashes stable across runs and platforms.
+//!
+//! Token owners are `0` for the bank and `idx + 1` for the players. The order of the
+//! revealed cards, reserved cards and nobles doesn't matter, the order of the decks does.
+
+use crate::game::GameState;
+use splendor_core::{Card, ColorVec, Noble, ReservedCard};
+
+const TOKENS: u64 = 1;
+const REVEALED: u64 = 2;
+const DECK: u64 = 3;
+const DEVELOPMENT: u64 = 4;
+const RESERVED: u64 = 5;
+const NOBLE: u64 = 6;
+const CURRENT_PLAYER: u64 = 7;
+const LAST_ROUND: u64 = 8;
+
+/// The owner of the tokens and nobles in the bank.
+pub(crate) const BANK: usize = 0;
+
+const fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[inline(always)]
+fn key(feature: u64, owner: usize, position: usize, value: u64) -> u64 {
+    splitmix64(feature << 60 | (owner as u64) << 56 | (position as u64) << 48 | value)
+}
+
+#[inline(always)]
+fn pack_colors(colors: &ColorVec) -> u64 {
+    colors.iter().fold(0, |acc, cnt| acc << 4 | cnt as u64)
+}
+
+#[inline(always)]
+fn pack_card(card: &Card) -> u64 {
+    (card.tier as u64) << 40
+        | (card.bonus as u64) << 32
+        | (card.points as u64) << 24
+        | pack_colors(&card.requires)
+}
+
+/// The key of the tokens of an owner.
+#[inline(always)]
+pub(crate) fn tokens(owner: usize, tokens: &ColorVec) -> u64 {
+    tokens.iter().enumerate().fold(0, |acc, (color, cnt)| {
+        acc ^ key(TOKENS, owner, color, cnt as u64)
+    })
+}
+
+/// The key of a revealed card.
+#[inline(always)]
+pub(crate) fn revealed(card: &Card) -> u64 {
+    key(REVEALED, 0, 0, pack_card(card))
+}
+
+/// The key of a card in a deck, the depth counts from the bottom.
+#[inline(always)]
+pub(crate) fn deck(depth: usize, card: &Card) -> u64 {
+    key(DECK, 0, depth, pack_card(card))
+}
+
+/// The key of a development card of a player.
+#[inline(always)]
+pub(crate) fn development(player: usize, card: &Card) -> u64 {
+    key(DEVELOPMENT, player, 0, pack_card(card))
+}
+
+/// The key of a reserved card of a player.
+#[inline(always)]
+pub(crate) fn reserved(player: usize, card: &ReservedCard) -> u64 {
+    key(
+        RESERVED,
+        player,
+        card.invisible as usize,
+        pack_card(&card.card),
+    )
+}
+
+/// The key of a noble of an owner.
+#[inline(always)]
+pub(crate) fn noble(owner: usize, noble: &Noble) -> u64 {
+    key(NOBLE, owner, 0, pack_colors(&noble.requires))
+}
+
+/// The key of the current player.
+#[inline(always)]
+pub(crate) fn current_player(player: usize) -> u64 {
+    key(CURRENT_PLAYER, 0, player, 0)
+}
+
+/// The key of the last round flag.
+#[inline(always)]
+pub(crate) fn last_round() -> u64 {
+    key(LAST_ROUND, 0, 0, 0)
+}
+
+impl GameState {
+    /// Compute the hash of the state without the card pool from scratch.
+    pub(crate) fn compute_hash(&self) -> u64 {
+        let mut hash = tokens(BANK, &self.tokens) ^ current_player(self.current_player);
+        if self.last_round {
+            hash ^= last_round();
+        }
+        for noble in self.nobles.iter() {
+            hash ^= self::noble(BANK, noble);
+        }
+        for player in self.players.iter() {
+            let owner = player.idx + 1;
+            hash ^= tokens(owner, &player.tokens);
+            for card in player.development_cards.iter() {
+                hash ^= development(player.idx, card);
+            }
+            for card in player.reserved_cards.iter() {
+                hash ^= reserved(player.idx, card);
+            }
+            for noble in player.nobles.iter() {
+                hash ^= self::noble(owner, noble);
+            }
+        }
+        hash
+    }
+
+    /// Toggle the keys of the tokens of the bank and the current player,
+    /// before and after they change.
+    #[inline(always)]
+    pub(crate) fn toggle_tokens(&mut self) {
+        let player = &self.players[self.current_player];
+        self.hash ^= tokens(BANK, &self.tokens) ^ tokens(player.idx + 1, &player.tokens);
+    }
+}
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use splendor_core::PlayerAction;
+use splendor_fuzz::{fuzz_action, Input};
+
+fuzz_target!(|input: Input| {
+    fuzz_action(&input, |state, action: &PlayerAction| {
+        state.apply_action(action)
+    });
+});
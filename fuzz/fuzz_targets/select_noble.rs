@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use splendor_core::SelectNoblesAction;
+use splendor_fuzz::{fuzz_action, Input};
+
+fuzz_target!(|input: Input| {
+    fuzz_action(&input, |state, action: &SelectNoblesAction| {
+        state.visit_noble(action)
+    });
+});
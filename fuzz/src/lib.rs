@@ -0,0 +1,80 @@
+//! Shared setup of the fuzz targets.
+#![deny(missing_docs)]
+
+use arbitrary::Arbitrary;
+use rand::prelude::*;
+use serde::de::DeserializeOwned;
+use splendor_core::naive_actors::GreedyActor;
+use splendor_core::MAX_PLAYERS;
+use splendor_engine::{GameState, InvalidActionError};
+
+/// The input of a fuzz target: a random state and the JSON sent by a client.
+#[derive(Debug, Arbitrary)]
+pub struct Input {
+    /// The seed of the game.
+    pub seed: u64,
+    /// The number of plies played before the action.
+    pub plies: u8,
+    /// The action sent by the client.
+    pub json: String,
+}
+
+/// Play random plies of a seeded game.
+///
+/// A ply is a player action, a drop, a noble visit or the end of a turn, so the state can be
+/// left in the middle of a turn, waiting for a drop or a noble. Like in a real game, a player
+/// visits at most one noble per turn.
+pub fn random_state(seed: u64, plies: u8) -> GameState {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let greedy = GreedyActor::new(SmallRng::seed_from_u64(seed));
+    let n_players = rng.gen_range(2..=MAX_PLAYERS);
+    let mut state = GameState::with_rng(&mut rng, n_players);
+    let mut acted = false;
+    let mut visited = false;
+    for _ in 0..plies {
+        if !acted {
+            let action = *state.legal_actions().choose(&mut rng).unwrap();
+            state.apply_action(&action).unwrap();
+            acted = true;
+        } else if state.needs_drop() {
+            state
+                .drop_tokens(&greedy.best_drop(&state.snapshot()))
+                .unwrap();
+        } else if !visited && !state.noble_visits().is_empty() {
+            let noble = *state.noble_visits().choose(&mut rng).unwrap();
+            state
+                .visit_noble(&splendor_core::SelectNoblesAction(noble))
+                .unwrap();
+            visited = true;
+        } else if state.end_turn().is_some() {
+            break;
+        } else {
+            acted = false;
+            visited = false;
+        }
+    }
+    state
+}
+
+/// Deserialize the JSON of the input and apply it to a random state.
+///
+/// Any action must either apply without panicking or be rejected before touching the state.
+pub fn fuzz_action<A, T>(
+    input: &Input,
+    apply: impl FnOnce(&mut GameState, &A) -> Result<T, InvalidActionError>,
+) where
+    A: DeserializeOwned,
+{
+    let Ok(action) = serde_json::from_str::<A>(&input.json) else {
+        return;
+    };
+    let mut state = random_state(input.seed, input.plies);
+    let before = state.snapshot();
+    if apply(&mut state, &action).is_err() {
+        assert_eq!(
+            state.snapshot(),
+            before,
+            "a rejected action changed the state"
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng};
+use splendor_core::naive_actors::GreedyActor;
+use splendor_core::{GameSnapshot, SelectNoblesAction, MAX_PLAYERS};
+use splendor_engine::GameState;
+
+/// The configuration of an [`Env`].
+#[derive(Debug, Copy, Clone)]
+pub struct EnvConfig {
+    /// The number of players.
+    pub n_players: usize,
+    /// The game is truncated after this many rounds.
+    pub max_rounds: usize,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            n_players: 2,
+            max_rounds: 100,
+        }
+    }
+}
+
+/// An error of the environment.
+#[derive(Debug, thiserror::Error)]
+pub enum EnvError {
+    /// The number of players is not supported.
+    #[error("the number of players must be between 2 and {MAX_PLAYERS}")]
+    Players,
+    /// The action index is not legal for the current player.
+    #[error("action {0} is not legal")]
+    IllegalAction(usize),
+    /// The game is over.
+    #[error("the game is over, reset the environment")]
+    GameOver,
+}
+
+/// The result of a step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepResult {
+    /// The reward of each player: `1` for the winners and `-1` for the others when the game
+    /// ends, `0` otherwise.
+    pub rewards: Vec<f32>,
+    /// The game ended by the rules.
+    pub terminated: bool,
+    /// The game is cut at the round limit.
+    pub truncated: bool,
+}
+
+/// A multi-agent self-play environment backed by the game engine.
+///
+/// Every step is the turn of the current player, who takes an action of the action space
+/// of [`GameSnapshot::encode`]. Dropping tokens and visiting nobles are resolved by the
+/// [`GreedyActor`] heuristic.
+#[derive(Debug)]
+pub struct Env {
+    config: EnvConfig,
+    rng: SmallRng,
+    state: GameState,
+    done: bool,
+    resolver: GreedyActor<SmallRng>,
+}
+
+impl Env {
+    /// Create a new environment, the first game is dealt with the seed.
+    pub fn new(config: EnvConfig, seed: u64) -> Result<Self, EnvError> {
+        if !(2..=MAX_PLAYERS).contains(&config.n_players) {
+            return Err(EnvError::Players);
+        }
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let state = GameState::with_rng(&mut rng, config.n_players);
+        Ok(Env {
+            config,
+            rng,
+            state,
+            done: false,
+            resolver: GreedyActor::new(SmallRng::seed_from_u64(seed)),
+        })
+    }
+
+    /// Deal a new game, with the seed or the next seed of the environment.
+    pub fn reset(&mut self, seed: Option<u64>) {
+        if let Some(seed) = seed {
+            self.rng = SmallRng::seed_from_u64(seed);
+        }
+        let mut rng = SmallRng::seed_from_u64(self.rng.next_u64());
+        self.state = GameState::with_rng(&mut rng, self.config.n_players);
+        self.done = false;
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    /// Get the state of the game.
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Get the snapshot seen by the current player.
+    pub fn snapshot(&self) -> GameSnapshot {
+        self.state.snapshot()
+    }
+
+    /// Get the current player.
+    pub fn current_player(&self) -> usize {
+        self.state.current_player()
+    }
+
+    /// Get is the game terminated or truncated.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Get the observation of the current player.
+    pub fn observation(&self) -> Vec<f32> {
+        self.snapshot().encode()
+    }
+
+    /// Get the mask of the legal actions of the current player.
+    pub fn action_mask(&self) -> Vec<bool> {
+        self.snapshot().legal_action_mask()
+    }
+
+    /// Take the action of the index for the current player, and end the turn.
+    pub fn step(&mut self, index: usize) -> Result<StepResult, EnvError> {
+        if self.done {
+            return Err(EnvError::GameOver);
+        }
+        let action = self
+            .snapshot()
+            .decode_action(index)
+            .ok_or(EnvError::IllegalAction(index))?;
+        self.state
+            .apply_action(&action)
+            .expect("legal action should be valid");
+        if self.state.needs_drop() {
+            let action = self.resolver.best_drop(&self.snapshot());
+            self.state
+                .drop_tokens(&action)
+                .expect("greedy drop should be valid");
+        }
+        let visits = self.state.noble_visits();
+        if !visits.is_empty() {
+            let action = if visits.len() > 1 {
+                self.resolver.best_noble(&self.snapshot())
+            } else {
+                SelectNoblesAction(visits[0])
+            };
+            self.state
+                .visit_noble(&action)
+                .expect("greedy noble should be visitable");
+        }
+
+        let n_players = self.config.n_players;
+        let mut rewards = vec![0.0; n_players];
+        let winners = self.state.end_turn();
+        // no winner if the game is stuck
+        if let Some(winners) = winners.as_ref().filter(|w| !w.is_empty()) {
+            for (idx, reward) in rewards.iter_mut().enumerate() {
+                *reward = if winners.contains(&idx) { 1.0 } else { -1.0 };
+            }
+        }
+        let terminated = winners.is_some();
+        let truncated = !terminated && self.state.current_round() >= self.config.max_rounds;
+        self.done = terminated || truncated;
+        Ok(StepResult {
+            rewards,
+            terminated,
+            truncated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::IteratorRandom;
+
+    fn play(seed: u64) -> (Vec<usize>, StepResult) {
+        let mut env = Env::new(EnvConfig::default(), seed).unwrap();
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut actions = vec![];
+        loop {
+            let index = env
+                .action_mask()
+                .iter()
+                .enumerate()
+                .filter(|(_, legal)| **legal)
+                .map(|(index, _)| index)
+                .choose(&mut rng)
+                .unwrap();
+            actions.push(index);
+            let result = env.step(index).unwrap();
+            if env.is_done() {
+                assert!(matches!(env.step(index), Err(EnvError::GameOver)));
+                return (actions, result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_self_play() {
+        for seed in 0..10 {
+            let (actions, result) = play(seed);
+            assert_eq!(play(seed), (actions, result.clone()));
+            assert!(result.terminated || result.truncated);
+            if result.terminated {
+                assert!(result.rewards.iter().any(|r| *r != 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_illegal_action() {
+        let mut env = Env::new(EnvConfig::default(), 0).unwrap();
+        let mask = env.action_mask();
+        let illegal = mask.iter().position(|legal| !legal).unwrap();
+        assert!(matches!(
+            env.step(illegal),
+            Err(EnvError::IllegalAction(index)) if index == illegal
+        ));
+        assert!(Env::new(
+            EnvConfig {
+                n_players: 5,
+                ..Default::default()
+            },
+            0
+        )
+        .is_err());
+    }
+}
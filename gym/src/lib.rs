@@ -0,0 +1,12 @@
+//! # Splendor Gym
+//!
+//! A gym-style self-play environment backed by the game engine,
+//! also built as the `splendor_gym` Python module with the `python` feature.
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+
+mod env;
+#[cfg(feature = "python")]
+mod python;
+
+pub use env::*;
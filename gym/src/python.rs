@@ -0,0 +1,133 @@
+use crate::{Env, EnvConfig, EnvError};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use splendor_core::{ACTION_SPACE_SIZE, OBSERVATION_SIZE};
+
+impl From<EnvError> for PyErr {
+    fn from(error: EnvError) -> Self {
+        match error {
+            EnvError::GameOver => PyRuntimeError::new_err(error.to_string()),
+            _ => PyValueError::new_err(error.to_string()),
+        }
+    }
+}
+
+/// A multi-agent self-play environment of Splendor.
+///
+/// Every step is the turn of the current player, the observation and the action mask
+/// are always of the player to move.
+#[pyclass(name = "SplendorEnv", module = "splendor_gym")]
+struct PyEnv {
+    env: Env,
+}
+
+#[pymethods]
+impl PyEnv {
+    #[new]
+    #[pyo3(signature = (n_players = 2, seed = 0, max_rounds = 100))]
+    fn new(n_players: usize, seed: u64, max_rounds: usize) -> PyResult<Self> {
+        let config = EnvConfig {
+            n_players,
+            max_rounds,
+        };
+        Ok(PyEnv {
+            env: Env::new(config, seed)?,
+        })
+    }
+
+    /// Deal a new game, returns `(observation, info)`.
+    #[pyo3(signature = (seed = None))]
+    fn reset<'py>(
+        &mut self,
+        py: Python<'py>,
+        seed: Option<u64>,
+    ) -> PyResult<(Vec<f32>, Bound<'py, PyDict>)> {
+        self.env.reset(seed);
+        Ok((self.env.observation(), self.info(py)?))
+    }
+
+    /// Take an action for the current player,
+    /// returns `(observation, rewards, terminated, truncated, info)`.
+    ///
+    /// The rewards are of every player, not only the player who took the action.
+    #[allow(clippy::type_complexity)]
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        action: usize,
+    ) -> PyResult<(Vec<f32>, Vec<f32>, bool, bool, Bound<'py, PyDict>)> {
+        let result = self.env.step(action)?;
+        Ok((
+            self.env.observation(),
+            result.rewards,
+            result.terminated,
+            result.truncated,
+            self.info(py)?,
+        ))
+    }
+
+    /// The observation of the current player.
+    fn observation(&self) -> Vec<f32> {
+        self.env.observation()
+    }
+
+    /// The mask of the legal actions of the current player.
+    fn action_mask(&self) -> Vec<bool> {
+        self.env.action_mask()
+    }
+
+    /// The board as text.
+    fn render(&self) -> String {
+        format!("{:#}", self.env.snapshot())
+    }
+
+    /// The player to move.
+    #[getter]
+    fn current_player(&self) -> usize {
+        self.env.current_player()
+    }
+
+    /// The number of players.
+    #[getter]
+    fn n_players(&self) -> usize {
+        self.env.config().n_players
+    }
+
+    /// The size of an observation.
+    #[getter]
+    fn observation_size(&self) -> usize {
+        OBSERVATION_SIZE
+    }
+
+    /// The size of the action space.
+    #[getter]
+    fn action_space_size(&self) -> usize {
+        ACTION_SPACE_SIZE
+    }
+}
+
+impl PyEnv {
+    fn info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let info = PyDict::new_bound(py);
+        info.set_item("current_player", self.env.current_player())?;
+        info.set_item("action_mask", self.env.action_mask())?;
+        Ok(info)
+    }
+}
+
+/// The layout of the observations and the action space, in JSON.
+#[pyfunction]
+fn encoding_spec() -> String {
+    serde_json::to_string(&splendor_core::encoding_spec())
+        .expect("encoding spec serialization should not fail")
+}
+
+#[pymodule]
+fn splendor_gym(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyEnv>()?;
+    m.add_function(wrap_pyfunction!(encoding_spec, m)?)?;
+    m.add("OBSERVATION_SIZE", OBSERVATION_SIZE)?;
+    m.add("ACTION_SPACE_SIZE", ACTION_SPACE_SIZE)?;
+    Ok(())
+}
@@ -5,3 +5,15 @@ pub mod controller {
 pub mod supervisor {
     tonic::include_proto!("supervisor");
 }
+
+impl From<supervisor::game_ends_message::EndReason> for controller::game_result::EndReason {
+    fn from(reason: supervisor::game_ends_message::EndReason) -> Self {
+        use supervisor::game_ends_message::EndReason;
+        match reason {
+            EndReason::Normal => Self::Normal,
+            EndReason::Timeout => Self::Timeout,
+            EndReason::StepError => Self::StepError,
+            EndReason::Draw => Self::Draw,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use rand::prelude::*;
+use splendor_core::naive_actors::{GreedyActor, GreedyWeights};
+use splendor_sdk::WebSocketActorClient;
+
+/// Plays with the greedy actor.
+///
+/// The heuristic can be tuned with `GREEDY_WEIGHTS`, a JSON object of [`GreedyWeights`],
+/// and the actor is deterministic when `GREEDY_SEED` is set.
+#[tokio::main]
+async fn main() {
+    pretty_env_logger::init();
+    let rng = match std::env::var("GREEDY_SEED") {
+        Ok(seed) => SmallRng::seed_from_u64(seed.parse().expect("invalid GREEDY_SEED")),
+        Err(_) => SmallRng::from_entropy(),
+    };
+    let weights = match std::env::var("GREEDY_WEIGHTS") {
+        Ok(weights) => serde_json::from_str(&weights).expect("invalid GREEDY_WEIGHTS"),
+        Err(_) => GreedyWeights::default(),
+    };
+    let actor = GreedyActor::with_weights(rng, weights);
+    let mut client = WebSocketActorClient::from_env(actor).await.unwrap();
+    client.run().await.unwrap();
+}
@@ -0,0 +1,18 @@
+use rand::prelude::*;
+use splendor_bots::{MctsActor, MctsConfig};
+use splendor_sdk::WebSocketActorClient;
+
+/// Plays with the MCTS reference bot.
+///
+/// The search can be tuned with `MCTS_CONFIG`, a JSON object of [`MctsConfig`].
+#[tokio::main]
+async fn main() {
+    pretty_env_logger::init();
+    let config = match std::env::var("MCTS_CONFIG") {
+        Ok(config) => serde_json::from_str(&config).expect("invalid MCTS_CONFIG"),
+        Err(_) => MctsConfig::default(),
+    };
+    let actor = MctsActor::new(SmallRng::from_entropy(), config);
+    let mut client = WebSocketActorClient::from_env(actor).await.unwrap();
+    client.run().await.unwrap();
+}
@@ -85,23 +85,23 @@ impl<A: PlayerActor> WebSocketActorClient<A> {
             }
             let ActionRequest { ty, snapshot }: ActionRequest =
                 serde_json::from_str(msg.to_text()?)?;
-            info!("Received action request: {:?}", ty);
+            info!("Received action request: {}", ty);
             let action = match ty {
                 ActionType::GetAction => {
                     let action = self.actor.get_action(snapshot).await?;
-                    info!("Took action: {:?}", action);
+                    info!("Took action: {}", action);
                     serde_json::to_string(&action)
                         .expect("PlayerAction serialization should not fail")
                 }
                 ActionType::DropTokens => {
                     let action = self.actor.drop_tokens(snapshot).await?;
-                    info!("Dropped tokens: {:?}", action);
+                    info!("Dropped tokens: {}", action);
                     serde_json::to_string(&action)
                         .expect("DropTokensAction serialization should not fail")
                 }
                 ActionType::SelectNoble => {
                     let action = self.actor.select_noble(snapshot).await?;
-                    info!("Selected noble: {:?}", action);
+                    info!("Selected noble: {}", action);
                     serde_json::to_string(&action)
                         .expect("SelectNoblesAction serialization should not fail")
                 }
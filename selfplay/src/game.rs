@@ -0,0 +1,198 @@
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use splendor_bots::{MctsActor, MctsConfig};
+use splendor_core::naive_actors::{GreedyActor, RandomActor};
+use splendor_core::{PlayerActor, Record, SelectNoblesAction, MAX_PLAYERS};
+use splendor_engine::{GameContext, GameState, InvalidActionError, StepError};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A bot playing the self-play games.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bot {
+    /// The [`RandomActor`].
+    Random,
+    /// The [`GreedyActor`].
+    Greedy,
+    /// The [`MctsActor`].
+    Mcts,
+}
+
+/// An unknown bot name.
+#[derive(Debug, thiserror::Error)]
+#[error("unknown bot `{0}`, expected random, greedy or mcts")]
+pub struct UnknownBotError(String);
+
+impl FromStr for Bot {
+    type Err = UnknownBotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Bot::Random),
+            "greedy" => Ok(Bot::Greedy),
+            "mcts" => Ok(Bot::Mcts),
+            _ => Err(UnknownBotError(s.to_string())),
+        }
+    }
+}
+
+impl Display for Bot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Bot::Random => "random",
+            Bot::Greedy => "greedy",
+            Bot::Mcts => "mcts",
+        })
+    }
+}
+
+impl Bot {
+    /// Create the actor of the bot.
+    pub fn actor(&self, rng: SmallRng, mcts: &MctsConfig) -> Box<dyn PlayerActor> {
+        match self {
+            Bot::Random => Box::new(RandomActor::new(rng)),
+            Bot::Greedy => Box::new(GreedyActor::new(rng)),
+            Bot::Mcts => Box::new(MctsActor::new(rng, mcts.clone())),
+        }
+    }
+}
+
+/// A finished self-play game.
+#[derive(Debug)]
+pub struct GameRecord {
+    /// The index of the game.
+    pub game: u64,
+    /// The index of the bot of each seat, in the bots of the config.
+    pub seats: SmallVec<usize, MAX_PLAYERS>,
+    /// The state the game is dealt with.
+    pub initial: GameState,
+    /// The records of the game.
+    pub records: Vec<Record>,
+    /// The winners, `None` if the game is cut at the round limit,
+    /// and empty if the game is stuck.
+    pub winners: Option<SmallVec<usize, MAX_PLAYERS>>,
+}
+
+/// A decision of a player, with the outcome of the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// The index of the game.
+    pub game: u64,
+    /// The round of the decision.
+    pub round: u32,
+    /// The player who decided.
+    pub player: usize,
+    /// The index of the bot who decided, in the bots of the config.
+    pub bot: usize,
+    /// The observation of the player, see [`GameSnapshot::encode`](splendor_core::GameSnapshot::encode).
+    pub observation: Vec<f32>,
+    /// The mask of the legal actions.
+    pub legal_mask: Vec<bool>,
+    /// The index of the chosen action.
+    pub action: usize,
+    /// The outcome for the player: `1` for a win, `-1` for a loss,
+    /// and `0` if the game is cut or stuck.
+    pub outcome: f32,
+}
+
+/// Derive the seed of a game from the seed of the run.
+pub fn game_seed(seed: u64, game: u64) -> u64 {
+    seed ^ game.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+/// Play a game between the bots, the seats rotate with the index of the game.
+///
+/// The game only depends on the seed and the index, unless a time limit of MCTS is set.
+pub async fn play_game(
+    bots: &[Bot],
+    mcts: &MctsConfig,
+    seed: u64,
+    game: u64,
+    max_rounds: usize,
+) -> Result<GameRecord, StepError> {
+    let n_players = bots.len();
+    let mut rng = SmallRng::seed_from_u64(game_seed(seed, game));
+    let seats = (0..n_players)
+        .map(|seat| (seat + game as usize) % n_players)
+        .collect::<SmallVec<_, MAX_PLAYERS>>();
+    let actors = seats
+        .iter()
+        .map(|bot| bots[*bot].actor(SmallRng::seed_from_u64(rng.next_u64()), mcts))
+        .collect();
+    let initial = GameState::with_rng(&mut rng.clone(), n_players);
+    let mut ctx = GameContext::with_rng(&mut rng, actors);
+    let winners = loop {
+        if let Some(winners) = ctx.step().await? {
+            break Some(winners);
+        }
+        if ctx.current_round() >= max_rounds {
+            break None;
+        }
+    };
+    Ok(GameRecord {
+        game,
+        seats,
+        initial,
+        records: ctx.records().to_vec(),
+        winners,
+    })
+}
+
+impl GameRecord {
+    /// Get the outcome of the player.
+    pub fn outcome(&self, player: usize) -> f32 {
+        match &self.winners {
+            Some(winners) if winners.contains(&player) => 1.0,
+            Some(winners) if !winners.is_empty() => -1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Replay the records from the initial state, and collect the decision of every turn.
+    pub fn samples(&self) -> Result<Vec<Sample>, InvalidActionError> {
+        let mut state = self.initial.clone();
+        let mut samples = vec![];
+        for record in self.records.iter() {
+            match record {
+                Record::PlayerAction(record) => {
+                    if !samples.is_empty() {
+                        state.end_turn();
+                    }
+                    let snapshot = state.snapshot();
+                    debug_assert_eq!(snapshot.current_player, record.player);
+                    samples.push(Sample {
+                        game: self.game,
+                        round: snapshot.current_round as u32,
+                        player: record.player,
+                        bot: self.seats[record.player],
+                        observation: snapshot.encode(),
+                        legal_mask: snapshot.legal_action_mask(),
+                        action: record
+                            .action
+                            .action_index()
+                            .expect("every action is in the action space"),
+                        outcome: self.outcome(record.player),
+                    });
+                    state.apply_action(&record.action)?;
+                }
+                Record::DropTokens(record) => state.drop_tokens(&record.action)?,
+                Record::VisitNoble(record) => {
+                    let noble = state
+                        .snapshot()
+                        .nobles
+                        .iter()
+                        .position(|noble| *noble == record.action)
+                        .ok_or(InvalidActionError {
+                            player: record.player,
+                            reason: "noble not found",
+                        })?;
+                    state.visit_noble(&SelectNoblesAction(noble))?;
+                }
+            }
+        }
+        Ok(samples)
+    }
+}
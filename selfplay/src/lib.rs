@@ -0,0 +1,15 @@
+//! # Splendor Self-Play
+//!
+//! Plays many in-process games between the bots, and writes the decisions of the players
+//! with the outcome of the games as training data.
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+
+#[macro_use]
+extern crate log;
+
+mod game;
+mod shard;
+
+pub use game::*;
+pub use shard::*;
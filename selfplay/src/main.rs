@@ -0,0 +1,50 @@
+use splendor_selfplay::{generate, SelfPlayConfig};
+use std::error::Error;
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+usage: splendor-selfplay [--bots BOT,BOT...] [--games N] [--games-per-shard N] [--seed SEED]
+                         [--max-rounds N] [--threads N] [--format jsonl|binary]
+                         [--mcts-config JSON] [--out DIR]
+
+play games between the bots (random, greedy or mcts, one per seat) and write
+(observation, legal mask, action, outcome) samples to the shards of DIR";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    pretty_env_logger::init();
+
+    let mut config = SelfPlayConfig::default();
+    let mut dir = PathBuf::from("selfplay");
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value of {arg}\n{USAGE}"))?;
+        match arg.as_str() {
+            "--bots" => config.bots = value.split(',').map(str::parse).collect::<Result<_, _>>()?,
+            "--games" => config.games = value.parse()?,
+            "--games-per-shard" => config.games_per_shard = value.parse()?,
+            "--seed" => config.seed = value.parse()?,
+            "--max-rounds" => config.max_rounds = value.parse()?,
+            "--threads" => config.threads = value.parse()?,
+            "--format" => config.format = value.parse()?,
+            "--mcts-config" => config.mcts = serde_json::from_str(&value)?,
+            "--out" => dir = PathBuf::from(value),
+            _ => return Err(format!("unknown argument {arg}\n{USAGE}").into()),
+        }
+    }
+
+    let manifest = generate(&config, &dir)?;
+    let samples = manifest.shards.iter().map(|s| s.samples).sum::<u64>();
+    println!(
+        "{} games, {samples} samples in {} shards written to {}",
+        config.games,
+        manifest.shards.len(),
+        dir.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,327 @@
+use crate::{play_game, Bot, Sample};
+use serde::{Deserialize, Serialize};
+use splendor_bots::MctsConfig;
+use splendor_core::{
+    encoding_spec, EncodingSpec, ACTION_SPACE_SIZE, MAX_PLAYERS, OBSERVATION_SIZE,
+};
+use splendor_engine::{InvalidActionError, StepError};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The size of a sample in the binary format.
+pub const BINARY_SAMPLE_SIZE: usize =
+    8 + 4 + 1 + 1 + 1 + 4 + ACTION_SPACE_SIZE + 4 * OBSERVATION_SIZE;
+
+/// The format of the shards.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// A JSON object of [`Sample`] per line.
+    Jsonl,
+    /// Fixed size little endian records of [`BINARY_SAMPLE_SIZE`] bytes:
+    ///
+    /// | field         | type                       |
+    /// |---------------|----------------------------|
+    /// | `game`        | `u64`                      |
+    /// | `round`       | `u32`                      |
+    /// | `player`      | `u8`                       |
+    /// | `bot`         | `u8`                       |
+    /// | `action`      | `u8`                       |
+    /// | `outcome`     | `f32`                      |
+    /// | `legal_mask`  | `u8` × `ACTION_SPACE_SIZE` |
+    /// | `observation` | `f32` × `OBSERVATION_SIZE` |
+    Binary,
+}
+
+/// An unknown format name.
+#[derive(Debug, thiserror::Error)]
+#[error("unknown format `{0}`, expected jsonl or binary")]
+pub struct UnknownFormatError(String);
+
+impl FromStr for Format {
+    type Err = UnknownFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" => Ok(Format::Jsonl),
+            "binary" => Ok(Format::Binary),
+            _ => Err(UnknownFormatError(s.to_string())),
+        }
+    }
+}
+
+impl Format {
+    /// Get the extension of the shard files.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Jsonl => "jsonl",
+            Format::Binary => "bin",
+        }
+    }
+
+    /// Write a sample.
+    pub fn write<W: Write>(&self, writer: &mut W, sample: &Sample) -> io::Result<()> {
+        match self {
+            Format::Jsonl => {
+                serde_json::to_writer(&mut *writer, sample)?;
+                writer.write_all(b"\n")
+            }
+            Format::Binary => {
+                writer.write_all(&sample.game.to_le_bytes())?;
+                writer.write_all(&sample.round.to_le_bytes())?;
+                writer.write_all(&[sample.player as u8, sample.bot as u8, sample.action as u8])?;
+                writer.write_all(&sample.outcome.to_le_bytes())?;
+                let mask = sample
+                    .legal_mask
+                    .iter()
+                    .map(|legal| *legal as u8)
+                    .collect::<Vec<_>>();
+                writer.write_all(&mask)?;
+                for feature in sample.observation.iter() {
+                    writer.write_all(&feature.to_le_bytes())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The config of a self-play run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfPlayConfig {
+    /// The bots, one per seat.
+    pub bots: Vec<Bot>,
+    /// The config of the MCTS bots.
+    pub mcts: MctsConfig,
+    /// The seed of the run.
+    pub seed: u64,
+    /// The number of games.
+    pub games: u64,
+    /// The number of games of each shard.
+    pub games_per_shard: u64,
+    /// Games are cut after this many rounds.
+    pub max_rounds: usize,
+    /// The number of worker threads, which doesn't change the output.
+    pub threads: usize,
+    /// The format of the shards.
+    pub format: Format,
+}
+
+impl Default for SelfPlayConfig {
+    fn default() -> Self {
+        SelfPlayConfig {
+            bots: vec![Bot::Greedy, Bot::Greedy],
+            mcts: MctsConfig::default(),
+            seed: 0,
+            games: 1000,
+            games_per_shard: 100,
+            max_rounds: 100,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            format: Format::Jsonl,
+        }
+    }
+}
+
+/// A written shard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardSummary {
+    /// The file name of the shard.
+    pub file: String,
+    /// The first game of the shard.
+    pub first_game: u64,
+    /// The number of games.
+    pub games: u64,
+    /// The number of samples.
+    pub samples: u64,
+}
+
+/// The manifest of a self-play run, written as `manifest.json` next to the shards.
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    /// The config of the run.
+    pub config: SelfPlayConfig,
+    /// The shards, in the order of the games.
+    pub shards: Vec<ShardSummary>,
+    /// The layout of the observations and the action space.
+    pub encoding: EncodingSpec,
+}
+
+/// An error of a self-play run.
+#[derive(Debug, thiserror::Error)]
+pub enum SelfPlayError {
+    /// The number of bots is not a number of players.
+    #[error("the number of bots must be between 2 and {MAX_PLAYERS}")]
+    Players,
+    /// The shards cannot be written.
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    /// A game failed.
+    #[error("game {game}: {source}")]
+    Step {
+        /// The index of the game.
+        game: u64,
+        /// The error of the step.
+        source: StepError,
+    },
+    /// The records of a game cannot be replayed.
+    #[error("game {game}: replay failed: {source}")]
+    Replay {
+        /// The index of the game.
+        game: u64,
+        /// The error of the replay.
+        source: InvalidActionError,
+    },
+}
+
+/// Play the games of the config in parallel, and write the shards and the manifest to the
+/// directory.
+///
+/// Each shard only depends on the seed of the config and its index, so the output is the
+/// same with any number of threads.
+pub fn generate(config: &SelfPlayConfig, dir: &Path) -> Result<Manifest, SelfPlayError> {
+    if !(2..=MAX_PLAYERS).contains(&config.bots.len()) {
+        return Err(SelfPlayError::Players);
+    }
+    std::fs::create_dir_all(dir)?;
+    let games_per_shard = config.games_per_shard.max(1);
+    let n_shards = config.games.div_ceil(games_per_shard);
+    let next = AtomicU64::new(0);
+    let results = std::thread::scope(|scope| {
+        let handles = (0..config.threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+                    let mut shards = vec![];
+                    loop {
+                        let shard = next.fetch_add(1, Ordering::Relaxed);
+                        if shard >= n_shards {
+                            return Ok(shards);
+                        }
+                        let first_game = shard * games_per_shard;
+                        let games = games_per_shard.min(config.games - first_game);
+                        shards.push(
+                            runtime.block_on(write_shard(config, dir, shard, first_game, games))?,
+                        );
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<Result<Vec<_>, SelfPlayError>>>()
+    });
+
+    let mut shards = vec![];
+    for result in results {
+        shards.extend(result?);
+    }
+    shards.sort_by_key(|shard| shard.first_game);
+    let manifest = Manifest {
+        config: config.clone(),
+        shards,
+        encoding: encoding_spec(),
+    };
+    let file = File::create(dir.join("manifest.json"))?;
+    serde_json::to_writer_pretty(file, &manifest).map_err(io::Error::from)?;
+    Ok(manifest)
+}
+
+async fn write_shard(
+    config: &SelfPlayConfig,
+    dir: &Path,
+    shard: u64,
+    first_game: u64,
+    games: u64,
+) -> Result<ShardSummary, SelfPlayError> {
+    let file = format!("shard-{shard:05}.{}", config.format.extension());
+    let mut writer = BufWriter::new(File::create(dir.join(&file))?);
+    let mut samples = 0;
+    for game in first_game..first_game + games {
+        let record = play_game(
+            &config.bots,
+            &config.mcts,
+            config.seed,
+            game,
+            config.max_rounds,
+        )
+        .await
+        .map_err(|source| SelfPlayError::Step { game, source })?;
+        let game_samples = record
+            .samples()
+            .map_err(|source| SelfPlayError::Replay { game, source })?;
+        for sample in game_samples.iter() {
+            config.format.write(&mut writer, sample)?;
+        }
+        samples += game_samples.len() as u64;
+        debug!("game {game}: {} samples", game_samples.len());
+    }
+    writer.flush()?;
+    info!("{file}: {games} games, {samples} samples");
+    Ok(ShardSummary {
+        file,
+        first_game,
+        games,
+        samples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(threads: usize, format: Format) -> (Manifest, Vec<Vec<u8>>) {
+        let config = SelfPlayConfig {
+            bots: vec![Bot::Random, Bot::Greedy],
+            games: 5,
+            games_per_shard: 2,
+            threads,
+            format,
+            ..Default::default()
+        };
+        let dir = std::env::temp_dir().join(format!(
+            "splendor-selfplay-{}-{threads}-{}",
+            std::process::id(),
+            format.extension()
+        ));
+        let manifest = generate(&config, &dir).unwrap();
+        let shards = manifest
+            .shards
+            .iter()
+            .map(|shard| std::fs::read(dir.join(&shard.file)).unwrap())
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+        (manifest, shards)
+    }
+
+    #[test]
+    fn test_generate() {
+        let (manifest, jsonl) = run(1, Format::Jsonl);
+        assert_eq!(manifest.shards.len(), 3);
+        assert_eq!(manifest.shards[2].games, 1);
+        assert_eq!(run(3, Format::Jsonl).1, jsonl);
+
+        let samples = jsonl
+            .iter()
+            .flat_map(|shard| shard.split(|b| *b == b'\n').filter(|line| !line.is_empty()))
+            .map(|line| serde_json::from_slice::<Sample>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            samples.len() as u64,
+            manifest.shards.iter().map(|s| s.samples).sum::<u64>()
+        );
+        for sample in samples.iter() {
+            assert_eq!(sample.observation.len(), OBSERVATION_SIZE);
+            assert!(sample.legal_mask[sample.action]);
+        }
+
+        let (_, binary) = run(2, Format::Binary);
+        assert_eq!(
+            binary.iter().map(Vec::len).sum::<usize>(),
+            samples.len() * BINARY_SAMPLE_SIZE
+        );
+    }
+}
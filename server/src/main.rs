@@ -7,12 +7,13 @@ use rand::distributions::{Alphanumeric, DistString};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use smallvec::SmallVec;
+use splendor_config::ServerConfig;
 use splendor_core::{PlayerActor, MAX_PLAYERS};
 use splendor_engine::GameContext;
-use std::env;
-use std::time::Duration;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::Sender;
-use uuid::Uuid;
 
 mod actor;
 mod error;
@@ -23,20 +24,18 @@ mod utils;
 async fn main() -> anyhow::Result<()> {
     pretty_env_logger::init();
 
-    let game_id = env::var("GAME_ID")?.parse::<Uuid>()?;
-    let mut supervisor = supervisor::Supervisor::new(game_id).await?;
-    let n_players = env::var("N_PLAYERS")?.parse::<usize>()?;
-    // random_seed use to deterministically reproduce the game.
-    let random_seed = env::var("RANDOM_SEED")
-        .ok()
-        .and_then(|s| s.parse::<u64>().ok());
-    let step_timeout = env::var("STEP_TIMEOUT")?.parse::<u64>()?;
+    let config = ServerConfig::from_env()?;
+    info!("Server config: {config:?}");
+    let mut supervisor = supervisor::Supervisor::new(&config).await?;
+    let n_players = config.n_players;
+    let step_timeout = config.step_timeout;
 
     let secrets = gen_secrets(n_players);
-    write_secrets(secrets).await?;
-    let actors = start_server(secrets).await?;
+    write_secrets(&config.secrets_path, secrets).await?;
+    let actors = start_server(config.server_addr, secrets).await?;
 
-    let mut game = match random_seed {
+    // the seed is used to deterministically reproduce the game.
+    let mut game = match config.seed {
         None => GameContext::random(actors),
         Some(seed) => {
             let mut rng = ChaCha20Rng::seed_from_u64(seed);
@@ -44,10 +43,26 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    supervisor.report_game_starts().await;
+    let mut reported_records = 0;
     while !game.game_end() {
         let current_player = game.current_player();
+        let current_round = game.current_round();
         supervisor.prepare_player_change(current_player).await?;
+        let started_at = Instant::now();
         let step = tokio::time::timeout(Duration::from_secs(step_timeout), game.step()).await;
+        if let Ok(Ok(_)) = step {
+            supervisor
+                .report_turn_ends(
+                    current_player,
+                    current_round,
+                    &game.records()[reported_records..],
+                    &game.points(),
+                    started_at.elapsed(),
+                )
+                .await;
+            reported_records = game.records().len();
+        }
         match step {
             Ok(Ok(None)) => continue,
             Ok(Ok(Some(winner))) => {
@@ -83,23 +98,26 @@ fn gen_secrets(n: usize) -> &'static [String] {
     Box::leak(secrets)
 }
 
-async fn write_secrets(secrets: &[String]) -> anyhow::Result<()> {
-    let path = env::var("SECRETS_PATH")?;
+async fn write_secrets(path: &Path, secrets: &[String]) -> anyhow::Result<()> {
     for (idx, secret) in secrets.iter().enumerate() {
-        let dir = format!("{path}/player{idx}");
+        let dir = path.join(format!("player{idx}"));
         tokio::fs::create_dir_all(&dir).await?;
-        tokio::fs::write(format!("{dir}/secret"), secret).await?;
-        info!("Player#{idx} secret written to: {dir}/secret={secret}");
+        let path = dir.join("secret");
+        tokio::fs::write(&path, secret).await?;
+        info!(
+            "Player#{idx} secret written to: {}={secret}",
+            path.display()
+        );
     }
     Ok(())
 }
 
 async fn start_server(
+    addr: SocketAddr,
     secrets: &'static [String],
 ) -> anyhow::Result<SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS>> {
     let (tx, mut rx) = tokio::sync::mpsc::channel(secrets.len());
-    let addr = env::var("SERVER_ADDR")?;
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let listener = tokio::net::TcpListener::bind(addr).await?;
     info!("Listening on: {}", addr);
     let server = tokio::spawn(async move {
         loop {
@@ -1,14 +1,28 @@
 use super::SupervisorError;
-use uuid::Uuid;
+use splendor_config::ServerConfig;
+use splendor_core::Record;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct Supervisor;
 
 impl Supervisor {
-    pub async fn new(_uuid: Uuid) -> Result<Self, SupervisorError> {
+    pub async fn new(_config: &ServerConfig) -> Result<Self, SupervisorError> {
         Ok(Self)
     }
 
+    pub async fn report_game_starts(&mut self) {}
+
+    pub async fn report_turn_ends(
+        &mut self,
+        _player: usize,
+        _round: usize,
+        _records: &[Record],
+        _points: &[u8],
+        _elapsed: Duration,
+    ) {
+    }
+
     pub async fn report_game_ends(
         &mut self,
         _winners: &[usize],
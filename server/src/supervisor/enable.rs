@@ -1,10 +1,15 @@
 use super::SupervisorError;
+use splendor_config::ServerConfig;
+use splendor_core::Record;
 use splendor_proto::supervisor::{
-    game_ends_message::EndReason, supervisor_client::SupervisorClient, GameEndsMessage,
-    PreparePlayerChangeMessage,
+    game_ends_message::EndReason, game_event::Event, supervisor_client::SupervisorClient,
+    GameEndsMessage, GameEvent, GameStartsMessage, PreparePlayerChangeMessage, TurnEndsMessage,
 };
-use std::env;
+use std::time::Duration;
 use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::transport::{Endpoint, Uri};
 use tower::service_fn;
 use uuid::Uuid;
@@ -13,17 +18,26 @@ use uuid::Uuid;
 pub struct Supervisor {
     uuid: Uuid,
     client: SupervisorClient<tonic::transport::Channel>,
+    events: Option<EventStream>,
+}
+
+#[derive(Debug)]
+struct EventStream {
+    sender: mpsc::Sender<GameEvent>,
+    handle: JoinHandle<Result<(), tonic::Status>>,
 }
 
 impl Supervisor {
-    pub async fn new(uuid: Uuid) -> Result<Self, SupervisorError> {
+    pub async fn new(config: &ServerConfig) -> Result<Self, SupervisorError> {
+        let path = config
+            .supervisor_socket
+            .clone()
+            .ok_or(SupervisorError::MissingSocket)?;
         let channel = Endpoint::try_from("http://[::]:50051")
             .expect("unreachable")
-            .connect_with_connector(service_fn(|_: Uri| {
-                let path = env::var("SUPERVISOR_SOCKET").expect("SUPERVISOR_SOCKET must be set");
-
+            .connect_with_connector(service_fn(move |_: Uri| {
                 // Connect to a Uds socket
-                UnixStream::connect(path)
+                UnixStream::connect(path.clone())
             }))
             .await
             .map_err(|e| {
@@ -32,17 +46,91 @@ impl Supervisor {
             })?;
 
         Ok(Self {
-            uuid,
+            uuid: config.game_id,
             client: SupervisorClient::new(channel),
+            events: None,
         })
     }
 
+    pub async fn report_game_starts(&mut self) {
+        let (sender, receiver) = mpsc::channel(16);
+        let mut client = self.client.clone();
+        let handle = tokio::spawn(async move {
+            client
+                .report_events(ReceiverStream::new(receiver))
+                .await
+                .map(|_| ())
+        });
+        self.events = Some(EventStream { sender, handle });
+        self.send_event(Event::GameStarts(GameStartsMessage {
+            game_id: self.uuid.to_string(),
+        }))
+        .await
+    }
+
+    pub async fn report_turn_ends(
+        &mut self,
+        player: usize,
+        round: usize,
+        records: &[Record],
+        points: &[u8],
+        elapsed: Duration,
+    ) {
+        let records = records
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Record serialization should not fail");
+        self.send_event(Event::TurnEnds(TurnEndsMessage {
+            game_id: self.uuid.to_string(),
+            player: player as i32,
+            round: round as u64,
+            records,
+            points: points.iter().map(|p| *p as u32).collect(),
+            elapsed_ms: elapsed.as_millis() as u64,
+        }))
+        .await
+    }
+
+    /// Send an event to the supervisor.
+    ///
+    /// The events are only used for replays and watching, so a failed stream is logged
+    /// and dropped, the game goes on without it.
+    async fn send_event(&mut self, event: Event) {
+        let Some(events) = self.events.as_ref() else {
+            return;
+        };
+        if events
+            .sender
+            .send(GameEvent { event: Some(event) })
+            .await
+            .is_err()
+        {
+            // the stream has ended, its error is logged when it is closed
+            self.close_events().await;
+        }
+    }
+
+    /// Close the event stream and wait until all events are delivered.
+    async fn close_events(&mut self) {
+        let Some(EventStream { sender, handle }) = self.events.take() else {
+            return;
+        };
+        drop(sender);
+        match handle.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Failed to report events to supervisor: {}", e),
+            Err(e) => error!("Event stream to supervisor panicked: {}", e),
+        }
+    }
+
     pub async fn report_game_ends(
         &mut self,
         winners: &[usize],
         timeout: bool,
         error: bool,
     ) -> Result<(), SupervisorError> {
+        self.close_events().await;
         let reason = if timeout {
             EndReason::Timeout
         } else if error {
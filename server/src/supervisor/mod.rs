@@ -13,4 +13,10 @@ pub enum SupervisorError {
     #[cfg(feature = "supervisor")]
     #[error("channel error")]
     ChannelError,
+    #[cfg(feature = "supervisor")]
+    #[error(
+        "{} must be set for a supervised server",
+        splendor_config::SUPERVISOR_SOCKET
+    )]
+    MissingSocket,
 }
@@ -0,0 +1,369 @@
+use crate::game::{update_queue_positions, Game};
+use crate::limits::PlayerLimits;
+use crate::runtime::GameSpec;
+use crate::{parse_game_id, unknown_game, GameController};
+use futures_util::{stream, Stream};
+use splendor_config::{MAX_PLAYERS, MIN_PLAYERS};
+use splendor_proto::controller::{
+    controller_server::Controller, game_result::EndReason, CancelGameRequest, CreateGameRequest,
+    CreateGameResponse, FreezeMode, GameInfo, GameLogs, GameStatus, GetGameLogsRequest,
+    GetGameRequest, ListGamesRequest, ListGamesResponse, StartGameRequest, WatchGameRequest,
+};
+use std::pin::Pin;
+use std::time::Duration;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+#[tonic::async_trait]
+impl Controller for GameController {
+    async fn create_game(
+        &self,
+        request: Request<CreateGameRequest>,
+    ) -> Result<Response<CreateGameResponse>, Status> {
+        let CreateGameRequest {
+            server_image,
+            player_images,
+            seed,
+            step_timeout,
+            game_timeout,
+            player_limits,
+            freeze_mode,
+            priority,
+        } = request.into_inner();
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&player_images.len()) {
+            return Err(Status::invalid_argument("Invalid number of players"));
+        }
+        let player_limits = player_limits
+            .map(PlayerLimits::try_from)
+            .transpose()
+            .map_err(Status::invalid_argument)?
+            .unwrap_or_default();
+        let freeze_mode = FreezeMode::try_from(freeze_mode)
+            .map_err(|_| Status::invalid_argument("Invalid FreezeMode"))?;
+        let step_timeout = step_timeout.unwrap_or(60 * 5); // 5 minutes
+        if step_timeout == 0 {
+            return Err(Status::invalid_argument("Step timeout must be positive"));
+        }
+
+        let game_id = Uuid::new_v4();
+        let spec = GameSpec {
+            id: game_id,
+            server_image,
+            player_images,
+            seed,
+            step_timeout,
+            player_limits,
+            freeze_mode,
+        };
+        let game = Game::new(
+            spec,
+            priority,
+            game_timeout.map(Duration::from_secs),
+            Duration::from_secs(step_timeout) + self.heartbeat_grace,
+        );
+        {
+            let mut guard = self.games.lock().await;
+            guard.insert(game_id, game);
+            update_queue_positions(&guard);
+        }
+        info!("Game#{game_id} queued with priority {priority}");
+        self.schedule.notify_one();
+        Ok(Response::new(CreateGameResponse {
+            game_id: game_id.to_string(),
+        }))
+    }
+
+    async fn start_game(&self, request: Request<StartGameRequest>) -> Result<Response<()>, Status> {
+        let game_id = parse_game_id(&request.into_inner().game_id, "start_game")?;
+        let guard = self.games.lock().await;
+        let game = guard
+            .get(&game_id)
+            .ok_or_else(|| unknown_game(&game_id, "start_game"))?;
+        // games are started by the scheduler
+        if game.status() == GameStatus::Ended {
+            return Err(Status::failed_precondition("Game already ended"));
+        }
+        Ok(Response::new(()))
+    }
+
+    async fn list_games(
+        &self,
+        request: Request<ListGamesRequest>,
+    ) -> Result<Response<ListGamesResponse>, Status> {
+        let status = request
+            .into_inner()
+            .status
+            .map(|status| {
+                GameStatus::try_from(status)
+                    .map_err(|_| Status::invalid_argument("Invalid GameStatus"))
+            })
+            .transpose()?;
+        let mut games = self
+            .games
+            .lock()
+            .await
+            .values()
+            .filter(|game| status.map_or(true, |status| game.status() == status))
+            .map(Game::info)
+            .collect::<Vec<_>>();
+        games.sort_by_key(|info| info.created_at);
+        Ok(Response::new(ListGamesResponse { games }))
+    }
+
+    async fn get_game(
+        &self,
+        request: Request<GetGameRequest>,
+    ) -> Result<Response<GameInfo>, Status> {
+        let game_id = parse_game_id(&request.into_inner().game_id, "get_game")?;
+        let guard = self.games.lock().await;
+        let game = guard
+            .get(&game_id)
+            .ok_or_else(|| unknown_game(&game_id, "get_game"))?;
+        Ok(Response::new(game.info()))
+    }
+
+    async fn cancel_game(
+        &self,
+        request: Request<CancelGameRequest>,
+    ) -> Result<Response<()>, Status> {
+        let game_id = parse_game_id(&request.into_inner().game_id, "cancel_game")?;
+        self.end_game(&game_id, vec![], EndReason::Cancelled)
+            .await?;
+        info!("Game#{game_id} cancelled");
+        Ok(Response::new(()))
+    }
+
+    async fn get_game_logs(
+        &self,
+        request: Request<GetGameLogsRequest>,
+    ) -> Result<Response<GameLogs>, Status> {
+        let GetGameLogsRequest { game_id, role } = request.into_inner();
+        let game_id = parse_game_id(&game_id, "get_game_logs")?;
+        let guard = self.games.lock().await;
+        let game = guard
+            .get(&game_id)
+            .ok_or_else(|| unknown_game(&game_id, "get_game_logs"))?;
+        if game.status() != GameStatus::Ended {
+            return Err(Status::failed_precondition("Game not ended yet"));
+        }
+        let containers = game
+            .reports()
+            .iter()
+            .filter(|report| {
+                role.as_ref().map_or(true, |role| {
+                    report
+                        .status
+                        .as_ref()
+                        .is_some_and(|status| &status.role == role)
+                })
+            })
+            .cloned()
+            .collect();
+        Ok(Response::new(GameLogs {
+            game_id: game_id.to_string(),
+            containers,
+        }))
+    }
+
+    type WatchGameStream = Pin<Box<dyn Stream<Item = Result<GameInfo, Status>> + Send>>;
+
+    async fn watch_game(
+        &self,
+        request: Request<WatchGameRequest>,
+    ) -> Result<Response<Self::WatchGameStream>, Status> {
+        let game_id = parse_game_id(&request.into_inner().game_id, "watch_game")?;
+        let receiver = self
+            .games
+            .lock()
+            .await
+            .get(&game_id)
+            .ok_or_else(|| unknown_game(&game_id, "watch_game"))?
+            .subscribe();
+        let stream = stream::unfold((Some(receiver), true), |(receiver, first)| async move {
+            let mut receiver = receiver?;
+            if !first && receiver.changed().await.is_err() {
+                return None;
+            }
+            let info = receiver.borrow_and_update().clone();
+            let receiver = (info.status() != GameStatus::Ended).then_some(receiver);
+            Some((Ok(info), (receiver, false)))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{controller, create_game, game_request};
+    use futures_util::StreamExt;
+    use tonic::Code;
+
+    const UNKNOWN_GAME: &str = "00000000-0000-0000-0000-000000000000";
+
+    fn end_reason(info: &GameInfo) -> EndReason {
+        info.result.as_ref().unwrap().reason()
+    }
+
+    #[tokio::test]
+    async fn test_create_game() {
+        let controller = controller();
+        for n_players in MIN_PLAYERS..=MAX_PLAYERS {
+            let game_id = create_game(&controller, game_request(n_players)).await;
+            let info = controller
+                .get_game(Request::new(GetGameRequest { game_id }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(info.player_images.len(), n_players);
+        }
+        for n_players in [0, MIN_PLAYERS - 1, MAX_PLAYERS + 1] {
+            let error = controller
+                .create_game(Request::new(game_request(n_players)))
+                .await
+                .unwrap_err();
+            assert_eq!(error.code(), Code::InvalidArgument, "{n_players} players");
+        }
+        let error = controller
+            .create_game(Request::new(CreateGameRequest {
+                step_timeout: Some(0),
+                ..game_request(2)
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_list_games() {
+        let controller = controller();
+        let list = |status: Option<GameStatus>| {
+            let controller = controller.clone();
+            async move {
+                controller
+                    .list_games(Request::new(ListGamesRequest {
+                        status: status.map(|status| status as i32),
+                    }))
+                    .await
+                    .map(|response| {
+                        let mut ids = response
+                            .into_inner()
+                            .games
+                            .into_iter()
+                            .map(|info| info.game_id)
+                            .collect::<Vec<_>>();
+                        ids.sort();
+                        ids
+                    })
+            }
+        };
+        assert!(list(None).await.unwrap().is_empty());
+
+        let mut ids = vec![
+            create_game(&controller, game_request(2)).await,
+            create_game(&controller, game_request(3)).await,
+        ];
+        ids.sort();
+        assert_eq!(list(None).await.unwrap(), ids);
+        assert_eq!(list(Some(GameStatus::Queued)).await.unwrap(), ids);
+        assert!(list(Some(GameStatus::Running)).await.unwrap().is_empty());
+
+        let error = controller
+            .list_games(Request::new(ListGamesRequest { status: Some(42) }))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_get_game() {
+        let controller = controller();
+        let game_id = create_game(&controller, game_request(2)).await;
+        let get = |game_id: &str| {
+            controller.get_game(Request::new(GetGameRequest {
+                game_id: game_id.to_string(),
+            }))
+        };
+        let info = get(&game_id).await.unwrap().into_inner();
+        assert_eq!(info.game_id, game_id);
+        assert_eq!(info.status(), GameStatus::Queued);
+        assert_eq!(info.player_images.len(), 2);
+        assert_eq!(info.queue_position, Some(0));
+        assert!(info.result.is_none());
+
+        assert_eq!(get(UNKNOWN_GAME).await.unwrap_err().code(), Code::NotFound);
+        assert_eq!(get("game").await.unwrap_err().code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_game() {
+        let controller = controller();
+        let first = create_game(&controller, game_request(2)).await;
+        let second = create_game(&controller, game_request(2)).await;
+        let cancel = |game_id: &str| {
+            controller.cancel_game(Request::new(CancelGameRequest {
+                game_id: game_id.to_string(),
+            }))
+        };
+        let get = |game_id: &str| {
+            controller.get_game(Request::new(GetGameRequest {
+                game_id: game_id.to_string(),
+            }))
+        };
+        cancel(&first).await.unwrap();
+        let info = get(&first).await.unwrap().into_inner();
+        assert_eq!(info.status(), GameStatus::Ended);
+        assert_eq!(end_reason(&info), EndReason::Cancelled);
+        assert_eq!(info.queue_position, None);
+        // the next game moves up the queue
+        assert_eq!(
+            get(&second).await.unwrap().into_inner().queue_position,
+            Some(0)
+        );
+
+        assert_eq!(
+            cancel(&first).await.unwrap_err().code(),
+            Code::FailedPrecondition
+        );
+        assert_eq!(
+            cancel(UNKNOWN_GAME).await.unwrap_err().code(),
+            Code::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_game() {
+        let controller = controller();
+        let game_id = create_game(&controller, game_request(2)).await;
+        let watch = |game_id: &str| {
+            controller.watch_game(Request::new(WatchGameRequest {
+                game_id: game_id.to_string(),
+            }))
+        };
+        let mut stream = watch(&game_id).await.unwrap().into_inner();
+        let info = stream.next().await.unwrap().unwrap();
+        assert_eq!(info.status(), GameStatus::Queued);
+
+        controller
+            .cancel_game(Request::new(CancelGameRequest {
+                game_id: game_id.clone(),
+            }))
+            .await
+            .unwrap();
+        let info = stream.next().await.unwrap().unwrap();
+        assert_eq!(info.status(), GameStatus::Ended);
+        assert_eq!(end_reason(&info), EndReason::Cancelled);
+        assert!(stream.next().await.is_none());
+
+        // an ended game is streamed once
+        let mut stream = watch(&game_id).await.unwrap().into_inner();
+        assert_eq!(
+            stream.next().await.unwrap().unwrap().status(),
+            GameStatus::Ended
+        );
+        assert!(stream.next().await.is_none());
+
+        let error = watch(UNKNOWN_GAME).await.err().unwrap();
+        assert_eq!(error.code(), Code::NotFound);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::runtime::{GameInstance, GameSpec};
+use splendor_proto::controller::{
+    game_result::EndReason, ContainerReport, GameInfo, GameResult, GameStatus,
+};
+use splendor_proto::supervisor::TurnEndsMessage;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+use uuid::Uuid;
+
+/// A game tracked by the supervisor.
+///
+/// A game is queued until a slot is free, then its instance is created and started.
+/// The game keeps its status after the instance is cleaned up,
+/// so that the result can still be queried.
+#[derive(Debug)]
+pub struct Game {
+    /// The spec of a queued game, taken when it leaves the queue.
+    spec: Option<GameSpec>,
+    queued_at: Instant,
+    instance: Option<Arc<dyn GameInstance>>,
+    info: watch::Sender<GameInfo>,
+    timeout: Option<Duration>,
+    started_at: Option<Instant>,
+    heartbeat_timeout: Duration,
+    last_heartbeat: Instant,
+    replay: Vec<TurnEndsMessage>,
+    reports: Vec<ContainerReport>,
+    /// Set once the instance is taken to end the game, before the game is marked as ended.
+    ending: bool,
+}
+
+impl Game {
+    pub fn new(
+        spec: GameSpec,
+        priority: i32,
+        timeout: Option<Duration>,
+        heartbeat_timeout: Duration,
+    ) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let info = GameInfo {
+            game_id: spec.id.to_string(),
+            status: GameStatus::Queued as i32,
+            server_image: spec.server_image.clone(),
+            player_images: spec.player_images.clone(),
+            seed: spec.seed,
+            created_at,
+            current_player: None,
+            turns: 0,
+            result: None,
+            round: 0,
+            points: vec![],
+            freeze_mode: spec.freeze_mode as i32,
+            player_change_total_us: 0,
+            player_change_max_us: 0,
+            priority,
+            queue_position: None,
+        };
+        Self {
+            spec: Some(spec),
+            queued_at: Instant::now(),
+            instance: None,
+            info: watch::Sender::new(info),
+            timeout,
+            started_at: None,
+            heartbeat_timeout,
+            last_heartbeat: Instant::now(),
+            replay: vec![],
+            reports: vec![],
+            ending: false,
+        }
+    }
+
+    /// Get the current status of the game.
+    pub fn info(&self) -> GameInfo {
+        self.info.borrow().clone()
+    }
+
+    /// Subscribe to the status changes of the game.
+    pub fn subscribe(&self) -> watch::Receiver<GameInfo> {
+        self.info.subscribe()
+    }
+
+    /// The number of players of the game.
+    pub fn n_players(&self) -> usize {
+        self.info.borrow().player_images.len()
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.info.borrow().status()
+    }
+
+    /// Whether the game takes a slot, i.e. it is created, starting or running.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.status(),
+            GameStatus::Created | GameStatus::Starting | GameStatus::Running
+        )
+    }
+
+    /// The order of a queued game in the queue, greater starts first.
+    pub fn queue_key(&self) -> Option<(i32, Reverse<Instant>)> {
+        (self.status() == GameStatus::Queued)
+            .then(|| (self.info.borrow().priority, Reverse(self.queued_at)))
+    }
+
+    /// Take the game out of the queue, returns the spec to create its instance.
+    pub fn dequeue(&mut self) -> Option<GameSpec> {
+        let spec = self.spec.take()?;
+        self.info.send_modify(|info| {
+            info.set_status(GameStatus::Created);
+            info.queue_position = None;
+        });
+        Some(spec)
+    }
+
+    /// Get the instance, `None` if the game is queued, being created or has ended.
+    pub fn instance(&self) -> Option<Arc<dyn GameInstance>> {
+        self.instance.clone()
+    }
+
+    /// Record the created instance, which is being started.
+    pub fn set_starting(&mut self, instance: Arc<dyn GameInstance>) {
+        self.instance = Some(instance);
+        self.info
+            .send_modify(|info| info.set_status(GameStatus::Starting));
+    }
+
+    pub fn set_running(&mut self) {
+        self.last_heartbeat = Instant::now();
+        self.info
+            .send_modify(|info| info.set_status(GameStatus::Running));
+    }
+
+    /// Mark the game server as alive.
+    pub fn heartbeat(&mut self) {
+        self.last_heartbeat = Instant::now();
+    }
+
+    /// Check if a running game should be ended by the supervisor.
+    ///
+    /// Returns the reason to end the game if it has exceeded its timeout
+    /// or the game server has stopped sending heartbeats.
+    pub fn expired(&self) -> Option<EndReason> {
+        if self.status() != GameStatus::Running || self.ending {
+            return None;
+        }
+        if let (Some(started_at), Some(timeout)) = (self.started_at, self.timeout) {
+            if started_at.elapsed() > timeout {
+                return Some(EndReason::Timeout);
+            }
+        }
+        (self.last_heartbeat.elapsed() > self.heartbeat_timeout).then_some(EndReason::Abandoned)
+    }
+
+    /// Record the player of the next turn, and the time it took to switch to it.
+    pub fn set_current_player(&self, player: usize, change_overhead: Duration) {
+        let overhead = change_overhead.as_micros() as u64;
+        self.info.send_modify(|info| {
+            info.current_player = Some(player as i32);
+            info.turns += 1;
+            info.player_change_total_us += overhead;
+            info.player_change_max_us = info.player_change_max_us.max(overhead);
+        });
+    }
+
+    /// Mark the beginning of the game play, the game timeout counts from here.
+    pub fn set_started(&mut self) {
+        self.started_at = Some(Instant::now());
+    }
+
+    /// Record a finished turn.
+    ///
+    /// Returns `true` if the game has exceeded its timeout.
+    pub fn record_turn(&mut self, turn: TurnEndsMessage) -> bool {
+        self.info.send_modify(|info| {
+            info.round = turn.round;
+            info.points = turn.points.clone();
+        });
+        self.replay.push(turn);
+        match (self.started_at, self.timeout) {
+            (Some(started_at), Some(timeout)) => started_at.elapsed() > timeout,
+            _ => false,
+        }
+    }
+
+    /// Take the recorded turns of the game.
+    pub fn take_replay(&mut self) -> Vec<TurnEndsMessage> {
+        mem::take(&mut self.replay)
+    }
+
+    /// Get the exit status and logs of the containers, empty until the game has ended.
+    pub fn reports(&self) -> &[ContainerReport] {
+        &self.reports
+    }
+
+    /// Whether the game has ended or is being ended.
+    pub fn is_ending(&self) -> bool {
+        self.ending || self.status() == GameStatus::Ended
+    }
+
+    /// Start ending the game, returns the instance to collect the reports from and clean up.
+    ///
+    /// The game keeps its status until it is [finished](Self::finish).
+    pub fn take_instance(&mut self) -> Option<Arc<dyn GameInstance>> {
+        self.ending = true;
+        self.spec = None;
+        self.instance.take()
+    }
+
+    /// Record the result of the game, with the exit status and logs of the containers.
+    pub fn finish(&mut self, winners: Vec<i32>, reason: EndReason, reports: Vec<ContainerReport>) {
+        self.spec = None;
+        self.instance = None;
+        let containers = reports
+            .iter()
+            .filter_map(|report| report.status.clone())
+            .collect::<Vec<_>>();
+        let server_exit_code = containers
+            .iter()
+            .find(|status| status.role == "server")
+            .and_then(|status| status.exit_code);
+        self.info.send_modify(|info| {
+            info.set_status(GameStatus::Ended);
+            info.queue_position = None;
+            info.result = Some(GameResult {
+                winners,
+                reason: reason as i32,
+                server_exit_code,
+                containers,
+            });
+        });
+        self.reports = reports;
+    }
+}
+
+/// Update the queue position of every queued game.
+pub fn update_queue_positions(games: &HashMap<Uuid, Game>) {
+    let mut queued = games
+        .values()
+        .filter_map(|game| game.queue_key().map(|key| (key, game)))
+        .collect::<Vec<_>>();
+    queued.sort_by_key(|(key, _)| Reverse(*key));
+    for (position, (_, game)) in queued.into_iter().enumerate() {
+        game.info.send_if_modified(|info| {
+            let modified = info.queue_position != Some(position as u32);
+            info.queue_position = Some(position as u32);
+            modified
+        });
+    }
+}
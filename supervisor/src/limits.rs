@@ -0,0 +1,216 @@
+use bollard::models::HostConfig;
+use splendor_proto::controller::ResourceLimits;
+
+/// The minimum memory limit accepted by docker.
+const MIN_MEMORY: i64 = 6 * 1024 * 1024;
+
+/// Resource and privilege limits applied to each player container.
+///
+/// Player containers always run with `no-new-privileges`, a memory limit and a pids limit,
+/// swap is disabled by setting the swap limit to the memory limit. The other restrictions can
+/// break existing player images, so they are opt-in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerLimits {
+    pub memory: i64,
+    pub pids_limit: i64,
+    pub read_only_rootfs: bool,
+    /// The size of the writable `/tmp` of a read-only rootfs.
+    pub tmpfs_size: i64,
+    pub drop_capabilities: bool,
+    /// The content of the seccomp profile in JSON, docker passes it to the daemon as is.
+    pub seccomp_profile: Option<String>,
+}
+
+impl Default for PlayerLimits {
+    fn default() -> Self {
+        Self {
+            memory: 512 * 1024 * 1024,
+            pids_limit: 256,
+            read_only_rootfs: false,
+            tmpfs_size: 64 * 1024 * 1024,
+            drop_capabilities: false,
+            seccomp_profile: None,
+        }
+    }
+}
+
+impl TryFrom<ResourceLimits> for PlayerLimits {
+    type Error = &'static str;
+
+    fn try_from(limits: ResourceLimits) -> Result<Self, Self::Error> {
+        let default = PlayerLimits::default();
+        let this = Self {
+            memory: limits.memory.unwrap_or(default.memory),
+            pids_limit: limits.pids_limit.unwrap_or(default.pids_limit),
+            read_only_rootfs: limits.read_only_rootfs.unwrap_or(default.read_only_rootfs),
+            tmpfs_size: limits.tmpfs_size.unwrap_or(default.tmpfs_size),
+            drop_capabilities: limits
+                .drop_capabilities
+                .unwrap_or(default.drop_capabilities),
+            seccomp_profile: limits.seccomp_profile,
+        };
+        if this.memory < MIN_MEMORY {
+            return Err("memory limit must be at least 6MB");
+        }
+        if this.pids_limit <= 0 {
+            return Err("pids limit must be positive");
+        }
+        if this.tmpfs_size <= 0 {
+            return Err("tmpfs size must be positive");
+        }
+        if let Some(profile) = &this.seccomp_profile {
+            if serde_json::from_str::<serde_json::Value>(profile).is_err() {
+                return Err("seccomp profile must be the JSON content of the profile");
+            }
+        }
+        Ok(this)
+    }
+}
+
+impl PlayerLimits {
+    /// Apply the limits to the host config of a player container.
+    pub fn apply(&self, host_config: &mut HostConfig) {
+        host_config.memory = Some(self.memory);
+        host_config.memory_swap = Some(self.memory);
+        host_config.pids_limit = Some(self.pids_limit);
+        if self.drop_capabilities {
+            host_config.cap_drop = Some(vec!["ALL".to_string()]);
+        }
+        let mut security_opt = vec!["no-new-privileges".to_string()];
+        if let Some(profile) = &self.seccomp_profile {
+            security_opt.push(format!("seccomp={profile}"));
+        }
+        host_config.security_opt = Some(security_opt);
+        host_config.readonly_rootfs = Some(self.read_only_rootfs);
+        if self.read_only_rootfs {
+            host_config.tmpfs = Some(
+                [(
+                    "/tmp".to_string(),
+                    format!("rw,noexec,nosuid,size={}", self.tmpfs_size),
+                )]
+                .into_iter()
+                .collect(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &str = r#"{"defaultAction":"SCMP_ACT_ALLOW"}"#;
+
+    fn apply(limits: ResourceLimits) -> HostConfig {
+        let mut host_config = HostConfig::default();
+        PlayerLimits::try_from(limits)
+            .unwrap()
+            .apply(&mut host_config);
+        host_config
+    }
+
+    #[test]
+    fn test_default_limits() {
+        assert_eq!(
+            PlayerLimits::try_from(ResourceLimits::default()).unwrap(),
+            PlayerLimits::default()
+        );
+        let host_config = apply(ResourceLimits::default());
+        assert_eq!(host_config.memory, Some(512 * 1024 * 1024));
+        assert_eq!(host_config.memory_swap, host_config.memory);
+        assert_eq!(host_config.pids_limit, Some(256));
+        assert_eq!(host_config.cap_drop, None);
+        assert_eq!(
+            host_config.security_opt,
+            Some(vec!["no-new-privileges".to_string()])
+        );
+        assert_eq!(host_config.readonly_rootfs, Some(false));
+        assert_eq!(host_config.tmpfs, None);
+    }
+
+    #[test]
+    fn test_all_limits() {
+        let host_config = apply(ResourceLimits {
+            memory: Some(64 * 1024 * 1024),
+            pids_limit: Some(32),
+            read_only_rootfs: Some(true),
+            tmpfs_size: Some(1024),
+            drop_capabilities: Some(true),
+            seccomp_profile: Some(PROFILE.to_string()),
+        });
+        assert_eq!(host_config.memory, Some(64 * 1024 * 1024));
+        assert_eq!(host_config.memory_swap, host_config.memory);
+        assert_eq!(host_config.pids_limit, Some(32));
+        assert_eq!(host_config.cap_drop, Some(vec!["ALL".to_string()]));
+        assert_eq!(
+            host_config.security_opt,
+            Some(vec![
+                "no-new-privileges".to_string(),
+                format!("seccomp={PROFILE}")
+            ])
+        );
+        assert_eq!(host_config.readonly_rootfs, Some(true));
+        assert_eq!(
+            host_config.tmpfs.unwrap()["/tmp"],
+            "rw,noexec,nosuid,size=1024"
+        );
+    }
+
+    #[test]
+    fn test_single_limits() {
+        let host_config = apply(ResourceLimits {
+            read_only_rootfs: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(host_config.readonly_rootfs, Some(true));
+        assert_eq!(
+            host_config.tmpfs.unwrap()["/tmp"],
+            "rw,noexec,nosuid,size=67108864"
+        );
+        assert_eq!(host_config.cap_drop, None);
+
+        let host_config = apply(ResourceLimits {
+            drop_capabilities: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(host_config.cap_drop, Some(vec!["ALL".to_string()]));
+        assert_eq!(host_config.readonly_rootfs, Some(false));
+        assert_eq!(host_config.tmpfs, None);
+
+        let host_config = apply(ResourceLimits {
+            pids_limit: Some(128),
+            ..Default::default()
+        });
+        assert_eq!(host_config.pids_limit, Some(128));
+        assert_eq!(host_config.cap_drop, None);
+    }
+
+    #[test]
+    fn test_invalid_limits() {
+        let invalid = [
+            ResourceLimits {
+                memory: Some(MIN_MEMORY - 1),
+                ..Default::default()
+            },
+            ResourceLimits {
+                pids_limit: Some(0),
+                ..Default::default()
+            },
+            ResourceLimits {
+                tmpfs_size: Some(0),
+                ..Default::default()
+            },
+            // a path instead of the content of the profile
+            ResourceLimits {
+                seccomp_profile: Some("/etc/docker/seccomp.json".to_string()),
+                ..Default::default()
+            },
+        ];
+        for limits in invalid {
+            assert!(
+                PlayerLimits::try_from(limits.clone()).is_err(),
+                "{limits:?}"
+            );
+        }
+    }
+}
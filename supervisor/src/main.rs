@@ -1,192 +1,222 @@
 #[macro_use]
 extern crate log;
 
-use crate::instance::GameInstance;
-use bollard::Docker;
-use futures_util::{stream::FuturesUnordered, StreamExt};
+use crate::game::{update_queue_positions, Game};
+use crate::runtime::{DockerRuntime, GameRuntime, ProcessRuntime};
+use crate::scheduler::Scheduler;
+use crate::watchdog::Watchdog;
+use futures_util::future;
 use splendor_proto::{
-    controller::{
-        controller_server::{Controller, ControllerServer},
-        CreateGameRequest, CreateGameResponse, StartGameRequest,
-    },
-    supervisor::{
-        game_ends_message::EndReason,
-        supervisor_server::{Supervisor, SupervisorServer},
-        GameEndsMessage, PreparePlayerChangeMessage,
-    },
+    controller::{controller_server::ControllerServer, game_result::EndReason, GameStatus},
+    supervisor::{supervisor_server::SupervisorServer, TurnEndsMessage},
 };
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::UnixListener;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use tokio_stream::wrappers::UnixListenerStream;
 use tonic::transport::Server;
-use tonic::{Request, Response, Status};
+use tonic::Status;
 use uuid::Uuid;
 
-mod instance;
+mod controller;
+mod game;
+mod limits;
+mod runtime;
+mod scheduler;
+mod supervisor;
+#[cfg(test)]
+mod testing;
+mod watchdog;
 
 #[derive(Clone)]
 struct GameController {
-    docker: Docker,
-    games: Arc<Mutex<HashMap<Uuid, GameInstance>>>,
+    runtime: Arc<dyn GameRuntime>,
+    games: Arc<Mutex<HashMap<Uuid, Game>>>,
+    replays_path: Option<PathBuf>,
+    heartbeat_grace: Duration,
+    max_concurrent_games: usize,
+    /// Wakes up the [`Scheduler`] when a game is queued or a slot is freed.
+    schedule: Arc<Notify>,
 }
 
 impl GameController {
-    pub fn new() -> Result<Self, bollard::errors::Error> {
-        let docker = Docker::connect_with_local_defaults()?;
-        Ok(Self {
-            docker,
+    pub fn new(runtime: Arc<dyn GameRuntime>) -> Self {
+        Self {
+            runtime,
             games: Arc::new(Mutex::new(HashMap::new())),
-        })
+            replays_path: std::env::var_os("REPLAYS_PATH").map(PathBuf::from),
+            heartbeat_grace: Duration::from_secs(env_or("HEARTBEAT_GRACE", 60)),
+            max_concurrent_games: env_or(
+                "MAX_CONCURRENT_GAMES",
+                std::thread::available_parallelism().map_or(1, |n| n.get()),
+            ),
+            schedule: Arc::new(Notify::new()),
+        }
     }
 
-    pub async fn cleanup(&self) {
-        let mut guard = self.games.lock().await;
-        let mut futures = FuturesUnordered::new();
-        for (_, game) in guard.drain() {
-            futures.push(game.cleanup());
+    /// Record the result of a game, then release its resources and save its replay.
+    pub async fn end_game(
+        &self,
+        game_id: &Uuid,
+        winners: Vec<i32>,
+        reason: EndReason,
+    ) -> Result<(), Status> {
+        let instance = {
+            let mut guard = self.games.lock().await;
+            let game = guard
+                .get_mut(game_id)
+                .ok_or_else(|| unknown_game(game_id, "end_game"))?;
+            if game.is_ending() {
+                return Err(Status::failed_precondition("Game already ended"));
+            }
+            match game.take_instance() {
+                Some(instance) => instance,
+                None => {
+                    let queued = game.status() == GameStatus::Queued;
+                    game.finish(winners, reason, vec![]);
+                    if queued {
+                        update_queue_positions(&guard);
+                    }
+                    drop(guard);
+                    self.schedule.notify_one();
+                    return Ok(());
+                }
+            }
+        };
+        // collect the reports before the game is marked as ended,
+        // so that the watchers of the game receive them with the result
+        let reports = match instance.collect_reports().await {
+            Ok(reports) => {
+                if matches!(reason, EndReason::Crashed | EndReason::Abandoned) {
+                    if let Some(server) = reports.first() {
+                        error!(
+                            "Game#{game_id} server status: {:?}, stderr:\n{}",
+                            server.status,
+                            String::from_utf8_lossy(&server.stderr)
+                        );
+                    }
+                }
+                reports
+            }
+            Err(e) => {
+                error!("Failed to collect reports of game#{game_id}: {}", e);
+                vec![]
+            }
+        };
+        let replay = {
+            let mut guard = self.games.lock().await;
+            let game = guard.get_mut(game_id).expect("games are never removed");
+            game.finish(winners, reason, reports);
+            game.take_replay()
+        };
+        self.schedule.notify_one();
+        if let Err(e) = instance.cleanup().await {
+            error!("Failed to cleanup game: {}", e);
         }
-        while let Some(ret) = futures.next().await {
-            if let Err(e) = ret {
-                error!("Failed to cleanup game: {}", e);
+        if let Some(path) = &self.replays_path {
+            if let Err(e) = save_replay(path, game_id, &replay).await {
+                error!("Failed to save replay of game#{game_id}: {}", e);
             }
         }
+        Ok(())
     }
-}
 
-#[tonic::async_trait]
-impl Controller for GameController {
-    async fn create_game(
-        &self,
-        request: Request<CreateGameRequest>,
-    ) -> Result<Response<CreateGameResponse>, Status> {
-        let CreateGameRequest {
-            server_image,
-            player_images,
-            seed,
-            step_timeout,
-        } = request.into_inner();
-        if player_images.len() != 3 && player_images.len() != 4 {
-            return Err(Status::invalid_argument("Invalid number of players"));
-        }
-
-        let game = GameInstance::new(
-            self.docker.clone(),
-            &server_image,
-            &player_images,
-            seed,
-            step_timeout.unwrap_or(60 * 5), // 5 minutes
+    pub async fn cleanup(&self) {
+        let game_ids = self
+            .games
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, game)| game.status() != GameStatus::Ended)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        future::join_all(
+            game_ids
+                .iter()
+                .map(|game_id| self.end_game(game_id, vec![], EndReason::Cancelled)),
         )
-        .await
-        .map_err(|e| {
-            error!("Failed to create game: {}", e);
-            Status::internal(format!("Failed to create game: {}", e))
-        })?;
-        let game_id = game.id;
-        self.games.lock().await.insert(game_id, game);
-        Ok(Response::new(CreateGameResponse {
-            game_id: game_id.to_string(),
-        }))
+        .await;
     }
+}
 
-    async fn start_game(&self, request: Request<StartGameRequest>) -> Result<Response<()>, Status> {
-        let game_id = request.into_inner().game_id.parse::<Uuid>().map_err(|_| {
-            error!("Received invalid UUID while handling start_game");
-            Status::invalid_argument("Invalid UUID")
-        })?;
-        let guard = self.games.lock().await;
-        let game = guard.get(&game_id).ok_or_else(|| {
-            error!("Received unknown game ID while handling start_game");
-            Status::not_found("Unknown game ID")
-        })?;
-        game.start().await.map_err(|e| {
-            error!("Failed to start game: {}", e);
-            Status::internal(format!("Failed to start game: {}", e))
-        })?;
-        Ok(Response::new(()))
+/// Write the turns of a game to `{path}/{game_id}.jsonl`, one turn per line.
+async fn save_replay(
+    path: &Path,
+    game_id: &Uuid,
+    replay: &[TurnEndsMessage],
+) -> anyhow::Result<()> {
+    let mut lines = String::new();
+    for turn in replay {
+        let records = turn
+            .records
+            .iter()
+            .map(|record| serde_json::from_str(record))
+            .collect::<Result<Vec<serde_json::Value>, _>>()?;
+        let line = serde_json::json!({
+            "player": turn.player,
+            "round": turn.round,
+            "records": records,
+            "points": turn.points,
+            "elapsed_ms": turn.elapsed_ms,
+        });
+        lines.push_str(&line.to_string());
+        lines.push('\n');
     }
+    tokio::fs::create_dir_all(path).await?;
+    tokio::fs::write(path.join(format!("{game_id}.jsonl")), lines).await?;
+    Ok(())
 }
 
-#[tonic::async_trait]
-impl Supervisor for GameController {
-    async fn report_game_ends(
-        &self,
-        request: Request<GameEndsMessage>,
-    ) -> Result<Response<()>, Status> {
-        let GameEndsMessage {
-            game_id,
-            winners,
-            reason,
-        } = request.into_inner();
-        let game_id = game_id.parse::<Uuid>().map_err(|_| {
-            error!("Received invalid UUID while handling report_game_ends");
-            Status::invalid_argument("Invalid UUID")
-        })?;
-        let reason = EndReason::try_from(reason).map_err(|_| {
-            error!(
-                "Received invalid EndReasom while handling report_game_ends, reason: {}",
-                reason
-            );
-            Status::invalid_argument("Invalid EndReasom")
-        })?;
-        info!("Game#{game_id} Ends ({reason:?}), winners: {winners:?}");
-        let game = self.games.lock().await.remove(&game_id).unwrap();
-        if let Err(e) = game.cleanup().await {
-            error!("Failed to cleanup game: {}", e);
-        }
-        Ok(Response::new(()))
-    }
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{name} must be a valid value, got {value}"))
+        })
+        .unwrap_or(default)
+}
 
-    async fn prepare_player_change(
-        &self,
-        request: Request<PreparePlayerChangeMessage>,
-    ) -> Result<Response<()>, Status> {
-        let req = request.into_inner();
-        let game_id = Uuid::from_str(&req.game_id).map_err(|_| {
-            error!(
-                "Received invalid UUID while handling prepare_player_change: {}",
-                req.game_id
-            );
-            Status::invalid_argument("Invalid UUID")
-        })?;
-        let guard = self.games.lock().await;
-        let game = guard.get(&game_id).ok_or_else(|| {
-            error!(
-                "Received unknown game ID while handling prepare_player_change: {}",
-                req.game_id
-            );
-            Status::not_found("Unknown game ID")
-        })?;
-        info!(
-            "Prepare player change for game#{game_id}, next player: {}",
-            req.next_player
-        );
-        game.prepare_player_change(req.next_player as usize)
-            .await
-            .map_err(|e| {
-                error!(
-                    "Failed to prepare player change for game ID: {}, cause: {e}",
-                    req.game_id
-                );
-                Status::internal(format!("Failed to prepare player change: {}", e))
-            })?;
-        Ok(Response::new(()))
-    }
+fn parse_game_id(game_id: &str, method: &str) -> Result<Uuid, Status> {
+    game_id.parse::<Uuid>().map_err(|_| {
+        error!("Received invalid UUID while handling {method}: {game_id}");
+        Status::invalid_argument("Invalid UUID")
+    })
+}
+
+fn unknown_game(game_id: &Uuid, method: &str) -> Status {
+    error!("Received unknown game ID while handling {method}: {game_id}");
+    Status::not_found("Unknown game ID")
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     pretty_env_logger::init();
 
-    let game_supervisor = GameController::new()?;
     let shared_volume_path = PathBuf::from(
         std::env::var_os("SHARED_VOLUME_PATH")
             .expect("SHARED_VOLUME_PATH must be set to the path of the UDS socket"),
     );
     let socket_path = shared_volume_path.join("supervisor.sock");
+    let runtime: Arc<dyn GameRuntime> = match env_or("RUNTIME", "docker".to_string()).as_str() {
+        "docker" => Arc::new(DockerRuntime::new(
+            std::env::var("SHARED_VOLUME").expect("SHARED_VOLUME must be set"),
+        )?),
+        "process" => {
+            let work_dir = std::env::var_os("PROCESS_WORK_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| std::env::temp_dir().join("splendor"));
+            Arc::new(ProcessRuntime::new(work_dir, socket_path.clone()))
+        }
+        runtime => anyhow::bail!("RUNTIME must be docker or process, got {runtime}"),
+    };
+    info!("Using runtime: {runtime:?}");
+    let game_supervisor = GameController::new(runtime);
 
     let supervisor_server = {
         let game_supervisor = game_supervisor.clone();
@@ -212,7 +242,20 @@ async fn main() -> anyhow::Result<()> {
         )
     };
 
+    let watchdog = {
+        let interval = Duration::from_secs(env_or("WATCHDOG_INTERVAL", 10));
+        tokio::spawn(Watchdog::new(game_supervisor.clone(), interval).run())
+    };
+
+    let scheduler = tokio::spawn(Scheduler::new(game_supervisor.clone()).run());
+
     tokio::select! {
+        _ = scheduler => {
+            error!("Scheduler terminated unexpectedly");
+        }
+        _ = watchdog => {
+            error!("Watchdog terminated unexpectedly");
+        }
         _ = supervisor_server => {
             error!("Supervisor server terminated unexpectedly");
         }
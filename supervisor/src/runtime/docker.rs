@@ -0,0 +1,512 @@
+use super::{keep_tail, ExitEvent, GameInstance, GameRuntime, GameSpec, RuntimeError};
+use async_trait::async_trait;
+use bollard::container::{LogOutput, LogsOptions, UpdateContainerOptions};
+use bollard::{
+    container::{CreateContainerOptions, RemoveContainerOptions},
+    models::*,
+    network::CreateNetworkOptions,
+    system::EventsOptions,
+    volume::{CreateVolumeOptions, RemoveVolumeOptions},
+    Docker,
+};
+use futures_util::{future, stream::BoxStream, FutureExt, StreamExt, TryFutureExt, TryStreamExt};
+use splendor_config::ServerConfig;
+use splendor_proto::controller::{ContainerReport, ContainerStatus, FreezeMode};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Label of the game id attached to every container of a game.
+const GAME_ID_LABEL: &str = "splendor.game-id";
+/// Label of the role (`server` or `player{idx}`) attached to every container of a game.
+const ROLE_LABEL: &str = "splendor.role";
+
+/// Ignore the conflict of pausing a paused or stopped container, or resuming a running one.
+fn ignore_conflict(
+    result: Result<(), bollard::errors::Error>,
+) -> Result<(), bollard::errors::Error> {
+    match result {
+        Err(bollard::errors::Error::DockerResponseServerError {
+            status_code: 409,
+            message,
+        }) => {
+            debug!("ignored conflict: {message}");
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn labels(id: &Uuid, role: String) -> HashMap<String, String> {
+    [
+        (GAME_ID_LABEL.to_string(), id.to_string()),
+        (ROLE_LABEL.to_string(), role),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// Runs each game in docker containers.
+///
+/// The game server and each player run in their own container,
+/// each player can only reach the server through its own internal network.
+#[derive(Debug)]
+pub struct DockerRuntime {
+    docker: Docker,
+    /// The docker volume containing the supervisor socket.
+    shared_volume: String,
+}
+
+impl DockerRuntime {
+    pub fn new(shared_volume: String) -> Result<Self, bollard::errors::Error> {
+        Ok(Self {
+            docker: Docker::connect_with_local_defaults()?,
+            shared_volume,
+        })
+    }
+}
+
+#[async_trait]
+impl GameRuntime for DockerRuntime {
+    async fn create(&self, spec: &GameSpec) -> Result<Box<dyn GameInstance>, RuntimeError> {
+        let instance = DockerInstance::new(self.docker.clone(), &self.shared_volume, spec).await?;
+        Ok(Box::new(instance))
+    }
+
+    fn exits(&self) -> BoxStream<'static, Result<ExitEvent, RuntimeError>> {
+        let filters = [
+            ("type", vec!["container"]),
+            ("event", vec!["die"]),
+            ("label", vec![GAME_ID_LABEL]),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+        self.docker
+            .events(Some(EventsOptions {
+                filters,
+                ..Default::default()
+            }))
+            .map_err(RuntimeError::from)
+            .try_filter_map(|event| async move {
+                let attributes = event
+                    .actor
+                    .and_then(|actor| actor.attributes)
+                    .unwrap_or_default();
+                let Some(game_id) = attributes
+                    .get(GAME_ID_LABEL)
+                    .and_then(|id| id.parse::<Uuid>().ok())
+                else {
+                    return Ok(None);
+                };
+                Ok(Some(ExitEvent {
+                    game_id,
+                    role: attributes.get(ROLE_LABEL).cloned().unwrap_or_default(),
+                    exit_code: attributes
+                        .get("exitCode")
+                        .and_then(|code| code.parse().ok()),
+                }))
+            })
+            .boxed()
+    }
+}
+
+#[derive(Debug)]
+struct DockerInstance {
+    id: Uuid,
+    docker: Docker,
+    networks: Vec<String>,
+    volumes: Vec<String>,
+    server: Option<String>,
+    players: Vec<String>,
+    freeze_mode: FreezeMode,
+    /// The player of the current turn, `None` before the first turn.
+    current_player: Mutex<Option<usize>>,
+}
+
+impl DockerInstance {
+    async fn new(
+        docker: Docker,
+        shared_volume: &str,
+        spec: &GameSpec,
+    ) -> Result<Self, RuntimeError> {
+        let id = spec.id;
+        let server_img = &spec.server_image;
+        let player_imgs = &spec.player_images;
+        let seed = spec.seed;
+        let step_timeout = spec.step_timeout;
+        let limits = &spec.player_limits;
+        let n_players = player_imgs.len();
+
+        let networks = future::try_join_all((0..n_players).map(|idx| {
+            docker
+                .create_network(CreateNetworkOptions {
+                    name: format!("game-{id}-player{idx}"),
+                    check_duplicate: true,
+                    internal: true,
+                    ..Default::default()
+                })
+                .map_ok(|resp| resp.id.unwrap())
+        }))
+        .await?;
+
+        let volumes = future::try_join_all((0..n_players).map(|idx| {
+            docker
+                .create_volume(CreateVolumeOptions {
+                    name: format!("game-{id}-player{idx}"),
+                    ..Default::default()
+                })
+                .map_ok(|resp| resp.name)
+        }))
+        .await?;
+
+        let server_config = ServerConfig {
+            game_id: id,
+            n_players,
+            seed,
+            step_timeout,
+            secrets_path: PathBuf::from("/app/secrets"),
+            server_addr: SocketAddr::from(([0, 0, 0, 0], 8080)),
+            supervisor_socket: Some(PathBuf::from("/var/run/splendor/supervisor.sock")),
+        };
+        let server_env = std::iter::once("RUST_LOG=info".to_string())
+            .chain(
+                server_config
+                    .to_env()?
+                    .into_iter()
+                    .map(|(name, value)| format!("{name}={value}")),
+            )
+            .collect::<Vec<_>>();
+
+        let mut mounts = volumes
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| Mount {
+                target: Some(format!("/app/secrets/player{idx}", idx = idx)),
+                source: Some(name.clone()),
+                typ: Some(MountTypeEnum::VOLUME),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        mounts.push(Mount {
+            target: Some("/var/run/splendor".to_string()),
+            source: Some(shared_volume.to_string()),
+            typ: Some(MountTypeEnum::VOLUME),
+            ..Default::default()
+        });
+
+        // create server container
+        let server = docker
+            .create_container(
+                Some(CreateContainerOptions {
+                    name: format!("game-{id}-server"),
+                    ..Default::default()
+                }),
+                bollard::container::Config {
+                    image: Some(server_img.to_string()),
+                    hostname: Some("server".to_string()),
+                    labels: Some(labels(&id, "server".to_string())),
+                    host_config: Some(HostConfig {
+                        mounts: Some(mounts),
+                        ..Default::default()
+                    }),
+                    env: Some(server_env),
+                    networking_config: Some(bollard::container::NetworkingConfig {
+                        endpoints_config: networks
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, net_id)| {
+                                (
+                                    format!("game-{id}-player{idx}-net"),
+                                    EndpointSettings {
+                                        aliases: Some(vec!["server".to_string()]),
+                                        network_id: Some(net_id.to_string()),
+                                        ..Default::default()
+                                    },
+                                )
+                            })
+                            .collect(),
+                    }),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .id;
+        debug!("server container created: {server}");
+
+        // create player containers
+        let players = future::try_join_all(
+            player_imgs
+                .iter()
+                .zip(volumes.iter().zip(networks.iter()))
+                .enumerate()
+                .map(|(idx, (img, (volume_id, net_id)))| {
+                    let mut host_config = HostConfig {
+                        mounts: Some(vec![Mount {
+                            target: Some("/app/secrets".to_string()),
+                            source: Some(volume_id.to_string()),
+                            typ: Some(MountTypeEnum::VOLUME),
+                            read_only: Some(true),
+                            volume_options: Some(MountVolumeOptions {
+                                no_copy: Some(true),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    };
+                    limits.apply(&mut host_config);
+                    docker
+                        .create_container(
+                            Some(CreateContainerOptions {
+                                name: format!("game-{id}-player{idx}"),
+                                ..Default::default()
+                            }),
+                            bollard::container::Config {
+                                image: Some(img.to_string()),
+                                hostname: Some(format!("player{idx}")),
+                                labels: Some(labels(&id, format!("player{idx}"))),
+                                host_config: Some(host_config),
+                                env: Some(vec![
+                                    "RPC_URL=ws://server:8080".to_string(),
+                                    "CLIENT_SECRET=/app/secrets/secret".to_string(),
+                                    format!("STEP_TIMEOUT={}", step_timeout),
+                                ]),
+                                networking_config: Some(bollard::container::NetworkingConfig {
+                                    endpoints_config: [(
+                                        format!("game-{id}-player{idx}-net", id = id),
+                                        EndpointSettings {
+                                            network_id: Some(net_id.to_string()),
+                                            ..Default::default()
+                                        },
+                                    )]
+                                    .into_iter()
+                                    .collect(),
+                                }),
+                                ..Default::default()
+                            },
+                        )
+                        .map_ok(|resp| resp.id)
+                }),
+        )
+        .await?;
+        debug!("player containers created: {players:?}");
+
+        Ok(DockerInstance {
+            id,
+            docker,
+            networks,
+            volumes,
+            server: Some(server),
+            players,
+            freeze_mode: spec.freeze_mode,
+            current_player: Mutex::new(None),
+        })
+    }
+
+    async fn collect_report(
+        &self,
+        role: String,
+        container: &str,
+    ) -> Result<ContainerReport, bollard::errors::Error> {
+        let state = self
+            .docker
+            .inspect_container(container, None)
+            .await?
+            .state
+            .unwrap_or_default();
+        let mut stdout = vec![];
+        let mut stderr = vec![];
+        let mut logs = self.docker.logs(
+            container,
+            Some(LogsOptions {
+                stdout: true,
+                stderr: true,
+                tail: "all".to_string(),
+                ..Default::default()
+            }),
+        );
+        while let Some(output) = logs.try_next().await? {
+            match output {
+                LogOutput::StdOut { message } | LogOutput::Console { message } => {
+                    stdout.extend_from_slice(&message)
+                }
+                LogOutput::StdErr { message } => stderr.extend_from_slice(&message),
+                LogOutput::StdIn { .. } => {}
+            }
+        }
+        Ok(ContainerReport {
+            status: Some(ContainerStatus {
+                role,
+                exit_code: state.exit_code.filter(|_| state.running != Some(true)),
+                oom_killed: state.oom_killed.unwrap_or_default(),
+            }),
+            stdout: keep_tail(stdout),
+            stderr: keep_tail(stderr),
+        })
+    }
+
+    /// Throttle the other players to 1% of a CPU.
+    async fn throttle(&self, next_player: usize) -> Result<(), bollard::errors::Error> {
+        // "freeze" other players
+        future::try_join_all(
+            self.players
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| *idx != next_player)
+                .map(|(_, player)| {
+                    self.docker.update_container(
+                        player,
+                        UpdateContainerOptions::<String> {
+                            cpu_period: Some(100000),
+                            cpu_quota: Some(1000), // allow to use 1%
+                            ..Default::default()
+                        },
+                    )
+                }),
+        )
+        .await?;
+        // unfreeze next player
+        self.docker
+            .update_container(
+                &self.players[next_player],
+                UpdateContainerOptions::<String> {
+                    cpu_period: Some(100000),
+                    cpu_quota: Some(950000), // allow to use 95%, reserve 5% for dispatcher
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Pause the other players with the cgroup freezer.
+    ///
+    /// The next player is resumed first, so it is running when the server sends its request.
+    /// The current player is only recorded once the containers are switched,
+    /// so a failed change is retried in full.
+    async fn pause(&self, next_player: usize) -> Result<(), bollard::errors::Error> {
+        let previous = *self.current_player.lock().unwrap();
+        match previous {
+            Some(previous) if previous == next_player => {}
+            Some(previous) => {
+                ignore_conflict(
+                    self.docker
+                        .unpause_container(&self.players[next_player])
+                        .await,
+                )?;
+                ignore_conflict(self.docker.pause_container(&self.players[previous]).await)?;
+            }
+            // all players are running before the first turn
+            None => {
+                future::try_join_all(
+                    self.players
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| *idx != next_player)
+                        .map(|(_, player)| {
+                            self.docker.pause_container(player).map(ignore_conflict)
+                        }),
+                )
+                .await?;
+            }
+        }
+        *self.current_player.lock().unwrap() = Some(next_player);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GameInstance for DockerInstance {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    async fn start(&self) -> Result<(), RuntimeError> {
+        self.docker
+            .start_container::<String>(self.server.as_ref().unwrap(), None)
+            .await?;
+        future::try_join_all(
+            self.players
+                .iter()
+                .map(|player| self.docker.start_container::<String>(player, None)),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn prepare_player_change(&self, next_player: usize) -> Result<(), RuntimeError> {
+        match self.freeze_mode {
+            FreezeMode::Throttle => self.throttle(next_player).await?,
+            FreezeMode::Pause => self.pause(next_player).await?,
+        }
+        Ok(())
+    }
+
+    async fn collect_reports(&self) -> Result<Vec<ContainerReport>, RuntimeError> {
+        let containers = self
+            .server
+            .iter()
+            .map(|server| ("server".to_string(), server))
+            .chain(
+                self.players
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, player)| (format!("player{idx}"), player)),
+            );
+        future::try_join_all(
+            containers.map(|(role, container)| self.collect_report(role, container)),
+        )
+        .await
+        .map_err(RuntimeError::from)
+    }
+
+    async fn cleanup(&self) -> Result<(), RuntimeError> {
+        if self.freeze_mode == FreezeMode::Pause {
+            // paused containers cannot be killed
+            future::try_join_all(
+                self.players
+                    .iter()
+                    .map(|player| self.docker.unpause_container(player).map(ignore_conflict)),
+            )
+            .await?;
+        }
+        future::try_join_all(self.players.iter().map(|player| {
+            debug!("removing player container {player}");
+            self.docker.remove_container(
+                player,
+                Some(RemoveContainerOptions {
+                    force: true,
+                    ..Default::default()
+                }),
+            )
+        }))
+        .await?;
+        if let Some(server) = &self.server {
+            debug!("removing server container {server}");
+            self.docker
+                .remove_container(
+                    server,
+                    Some(RemoveContainerOptions {
+                        force: true,
+                        ..Default::default()
+                    }),
+                )
+                .await?;
+        }
+        future::try_join_all(self.networks.iter().map(|network| {
+            debug!("removing network {network}");
+            self.docker.remove_network(network)
+        }))
+        .await?;
+        future::try_join_all(self.volumes.iter().map(|network| {
+            debug!("removing volume {network}");
+            self.docker
+                .remove_volume(network, Some(RemoveVolumeOptions { force: true }))
+        }))
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use crate::limits::PlayerLimits;
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use splendor_proto::controller::{ContainerReport, FreezeMode};
+use std::fmt::Debug;
+use uuid::Uuid;
+
+mod docker;
+mod process;
+
+pub use docker::DockerRuntime;
+pub use process::ProcessRuntime;
+
+/// The maximum size of each log stream kept for a server or player.
+pub const MAX_LOG_SIZE: usize = 1 << 20;
+
+fn keep_tail(mut log: Vec<u8>) -> Vec<u8> {
+    if log.len() > MAX_LOG_SIZE {
+        log.drain(..log.len() - MAX_LOG_SIZE);
+    }
+    log
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RuntimeError {
+    #[error("docker error: {0}")]
+    Docker(#[from] bollard::errors::Error),
+    #[error("invalid server config: {0}")]
+    Config(#[from] splendor_config::ConfigError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to signal process: {0}")]
+    Signal(#[from] nix::errno::Errno),
+    #[error("game server did not become ready in time")]
+    ServerTimeout,
+}
+
+/// Everything needed to create a game.
+#[derive(Debug, Clone)]
+pub struct GameSpec {
+    pub id: Uuid,
+    /// The image of the game server, or its command for process based runtimes.
+    pub server_image: String,
+    /// The images of the players, or their commands for process based runtimes.
+    pub player_images: Vec<String>,
+    pub seed: Option<u64>,
+    pub step_timeout: u64,
+    pub player_limits: PlayerLimits,
+    pub freeze_mode: FreezeMode,
+}
+
+/// Notification of an exited game server or player.
+#[derive(Debug, Clone)]
+pub struct ExitEvent {
+    pub game_id: Uuid,
+    /// `server` or `player{idx}`.
+    pub role: String,
+    pub exit_code: Option<i64>,
+}
+
+/// A backend which runs the game server and players of games.
+#[async_trait]
+pub trait GameRuntime: Send + Sync + Debug {
+    /// Create the resources of a game without starting it.
+    async fn create(&self, spec: &GameSpec) -> Result<Box<dyn GameInstance>, RuntimeError>;
+
+    /// Stream the exits of game servers and players of all games.
+    fn exits(&self) -> BoxStream<'static, Result<ExitEvent, RuntimeError>>;
+}
+
+/// A game created by a [`GameRuntime`].
+#[async_trait]
+pub trait GameInstance: Send + Sync + Debug {
+    fn id(&self) -> Uuid;
+
+    async fn start(&self) -> Result<(), RuntimeError>;
+
+    /// Let the next player run, and "freeze" the other players.
+    async fn prepare_player_change(&self, next_player: usize) -> Result<(), RuntimeError>;
+
+    /// Collect the exit status and logs of the server and players, server first.
+    ///
+    /// Each log stream keeps at most the last [`MAX_LOG_SIZE`] bytes.
+    async fn collect_reports(&self) -> Result<Vec<ContainerReport>, RuntimeError>;
+
+    /// Stop the game and release all its resources.
+    ///
+    /// The game may still be starting, then starting it fails.
+    async fn cleanup(&self) -> Result<(), RuntimeError>;
+}
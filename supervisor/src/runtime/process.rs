@@ -0,0 +1,384 @@
+use super::{keep_tail, ExitEvent, GameInstance, GameRuntime, GameSpec, RuntimeError};
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, StreamExt};
+use nix::errno::Errno;
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
+use splendor_config::ServerConfig;
+use splendor_proto::controller::{ContainerReport, ContainerStatus};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use uuid::Uuid;
+
+/// How long the game server may take to write the player secrets and listen.
+const SERVER_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs each game as local processes, without a docker daemon.
+///
+/// The server and player "images" are shell commands, run with `sh -c` in their own process
+/// group. Players are always frozen with `SIGSTOP`/`SIGCONT` on their process group, whatever
+/// the [`FreezeMode`](splendor_proto::controller::FreezeMode) of the game.
+///
+/// Player limits and network isolation are not enforced, only use it with trusted players,
+/// e.g. on a dev machine or in CI.
+#[derive(Debug)]
+pub struct ProcessRuntime {
+    /// Each game gets a `{work_dir}/{game_id}` directory for its secrets and logs.
+    work_dir: PathBuf,
+    supervisor_socket: PathBuf,
+    exits: broadcast::Sender<ExitEvent>,
+}
+
+impl ProcessRuntime {
+    pub fn new(work_dir: PathBuf, supervisor_socket: PathBuf) -> Self {
+        Self {
+            work_dir,
+            supervisor_socket,
+            exits: broadcast::channel(64).0,
+        }
+    }
+}
+
+#[async_trait]
+impl GameRuntime for ProcessRuntime {
+    async fn create(&self, spec: &GameSpec) -> Result<Box<dyn GameInstance>, RuntimeError> {
+        let n_players = spec.player_images.len();
+        let dir = self.work_dir.join(spec.id.to_string());
+        let secrets = dir.join("secrets");
+        tokio::fs::create_dir_all(&secrets).await?;
+        // let the OS pick a free port for the server
+        let server_addr = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?.local_addr()?;
+
+        let server_config = ServerConfig {
+            game_id: spec.id,
+            n_players,
+            seed: spec.seed,
+            step_timeout: spec.step_timeout,
+            secrets_path: secrets.clone(),
+            server_addr,
+            supervisor_socket: Some(self.supervisor_socket.clone()),
+        };
+        let server_env = std::iter::once(("RUST_LOG".to_string(), "info".to_string()))
+            .chain(
+                server_config
+                    .to_env()?
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value)),
+            )
+            .collect();
+        let players = spec
+            .player_images
+            .iter()
+            .enumerate()
+            .map(|(idx, command)| {
+                let env = vec![
+                    ("RPC_URL".to_string(), format!("ws://{server_addr}")),
+                    (
+                        "CLIENT_SECRET".to_string(),
+                        secrets
+                            .join(format!("player{idx}"))
+                            .join("secret")
+                            .display()
+                            .to_string(),
+                    ),
+                    ("STEP_TIMEOUT".to_string(), spec.step_timeout.to_string()),
+                ];
+                ProcessSpec {
+                    role: format!("player{idx}"),
+                    command: command.clone(),
+                    env,
+                }
+            })
+            .collect();
+        debug!("game#{} created in {dir:?}", spec.id);
+
+        Ok(Box::new(ProcessInstance {
+            id: spec.id,
+            dir,
+            server_addr,
+            server: ProcessSpec {
+                role: "server".to_string(),
+                command: spec.server_image.clone(),
+                env: server_env,
+            },
+            players,
+            processes: Mutex::new(vec![]),
+            exits: self.exits.clone(),
+        }))
+    }
+
+    fn exits(&self) -> BoxStream<'static, Result<ExitEvent, RuntimeError>> {
+        BroadcastStream::new(self.exits.subscribe())
+            .filter_map(|event| async move {
+                match event {
+                    Ok(event) => Some(Ok(event)),
+                    Err(e) => {
+                        warn!("Exit events lagged: {}", e);
+                        None
+                    }
+                }
+            })
+            .boxed()
+    }
+}
+
+#[derive(Debug)]
+struct ProcessSpec {
+    role: String,
+    command: String,
+    env: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+struct Process {
+    role: String,
+    /// The process group, which is led by the `sh` process.
+    pgid: Pid,
+    status: Arc<Mutex<Option<ExitStatus>>>,
+}
+
+#[derive(Debug)]
+struct ProcessInstance {
+    id: Uuid,
+    dir: PathBuf,
+    server_addr: SocketAddr,
+    server: ProcessSpec,
+    players: Vec<ProcessSpec>,
+    /// The server followed by the players, once started.
+    processes: Mutex<Vec<Process>>,
+    exits: broadcast::Sender<ExitEvent>,
+}
+
+impl ProcessInstance {
+    fn spawn(&self, spec: &ProcessSpec) -> Result<Process, RuntimeError> {
+        let stdout = std::fs::File::create(self.log_path(&spec.role, "stdout"))?;
+        let stderr = std::fs::File::create(self.log_path(&spec.role, "stderr"))?;
+        let mut command = std::process::Command::new("sh");
+        command
+            .arg("-c")
+            .arg(&spec.command)
+            .envs(spec.env.iter().cloned())
+            .current_dir(&self.dir)
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr)
+            .process_group(0);
+        // the game dies with the runtime instead of running forever, e.g. at the end of a test
+        let mut child = Command::from(command).kill_on_drop(true).spawn()?;
+        let pgid = Pid::from_raw(child.id().expect("process just spawned") as i32);
+        debug!("game#{} {} spawned with pid {pgid}", self.id, spec.role);
+
+        let status = Arc::new(Mutex::new(None));
+        tokio::spawn({
+            let game_id = self.id;
+            let role = spec.role.clone();
+            let status = status.clone();
+            let exits = self.exits.clone();
+            async move {
+                let exit_code = match child.wait().await {
+                    Ok(exit_status) => {
+                        *status.lock().unwrap() = Some(exit_status);
+                        exit_code(exit_status)
+                    }
+                    Err(e) => {
+                        error!("Failed to wait for game#{game_id} {role}: {}", e);
+                        None
+                    }
+                };
+                // no receivers is fine
+                let _ = exits.send(ExitEvent {
+                    game_id,
+                    role,
+                    exit_code,
+                });
+            }
+        });
+        Ok(Process {
+            role: spec.role.clone(),
+            pgid,
+            status,
+        })
+    }
+
+    fn log_path(&self, role: &str, stream: &str) -> PathBuf {
+        self.dir.join(format!("{role}.{stream}"))
+    }
+
+    /// Wait until the server has written all player secrets and accepts connections.
+    async fn wait_server_ready(&self) -> Result<(), RuntimeError> {
+        let secrets = self
+            .players
+            .iter()
+            .filter_map(|player| player.env.iter().find(|(name, _)| name == "CLIENT_SECRET"))
+            .map(|(_, path)| Path::new(path))
+            .collect::<Vec<_>>();
+        let ready = async {
+            loop {
+                if secrets.iter().all(|path| path.exists())
+                    && TcpStream::connect(self.server_addr).await.is_ok()
+                {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        };
+        tokio::time::timeout(SERVER_READY_TIMEOUT, ready)
+            .await
+            .map_err(|_| RuntimeError::ServerTimeout)
+    }
+
+    fn signal(&self, process: &Process, signal: Signal) -> Result<(), RuntimeError> {
+        match killpg(process.pgid, signal) {
+            // the process group has already exited
+            Ok(()) | Err(Errno::ESRCH) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl GameInstance for ProcessInstance {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    async fn start(&self) -> Result<(), RuntimeError> {
+        let server = self.spawn(&self.server)?;
+        self.processes.lock().unwrap().push(server);
+        self.wait_server_ready().await?;
+        for player in self.players.iter() {
+            let player = self.spawn(player)?;
+            self.processes.lock().unwrap().push(player);
+        }
+        Ok(())
+    }
+
+    async fn prepare_player_change(&self, next_player: usize) -> Result<(), RuntimeError> {
+        let processes = self.processes.lock().unwrap();
+        let players = processes.iter().skip(1);
+        // freeze other players
+        for (_, player) in players
+            .clone()
+            .enumerate()
+            .filter(|(idx, _)| *idx != next_player)
+        {
+            self.signal(player, Signal::SIGSTOP)?;
+        }
+        // unfreeze next player
+        if let Some(player) = players.clone().nth(next_player) {
+            self.signal(player, Signal::SIGCONT)?;
+        }
+        Ok(())
+    }
+
+    async fn collect_reports(&self) -> Result<Vec<ContainerReport>, RuntimeError> {
+        let processes = self
+            .processes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|process| (process.role.clone(), *process.status.lock().unwrap()))
+            .collect::<Vec<_>>();
+        let mut reports = vec![];
+        for (role, status) in processes {
+            let stdout = tokio::fs::read(self.log_path(&role, "stdout")).await?;
+            let stderr = tokio::fs::read(self.log_path(&role, "stderr")).await?;
+            reports.push(ContainerReport {
+                status: Some(ContainerStatus {
+                    role,
+                    exit_code: status.and_then(exit_code),
+                    oom_killed: false,
+                }),
+                stdout: keep_tail(stdout),
+                stderr: keep_tail(stderr),
+            });
+        }
+        Ok(reports)
+    }
+
+    async fn cleanup(&self) -> Result<(), RuntimeError> {
+        for process in self.processes.lock().unwrap().iter() {
+            debug!("killing game#{} {}", self.id, process.role);
+            self.signal(process, Signal::SIGKILL)?;
+        }
+        tokio::fs::remove_dir_all(&self.dir).await?;
+        Ok(())
+    }
+}
+
+/// The exit code of a process, `128 + signal` if killed by a signal like a shell reports it.
+fn exit_code(status: ExitStatus) -> Option<i64> {
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .map(i64::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::PlayerLimits;
+    use crate::testing::{crashing_server, waiting_player};
+    use splendor_proto::controller::FreezeMode;
+
+    #[tokio::test]
+    async fn test_process_runtime() {
+        let work_dir = std::env::temp_dir().join(format!("splendor-test-{}", Uuid::new_v4()));
+        let runtime = ProcessRuntime::new(work_dir.clone(), work_dir.join("supervisor.sock"));
+        let mut exits = runtime.exits();
+        let spec = GameSpec {
+            id: Uuid::new_v4(),
+            server_image: crashing_server(3),
+            player_images: vec![waiting_player("go"); 3],
+            seed: Some(42),
+            step_timeout: 1,
+            player_limits: PlayerLimits::default(),
+            freeze_mode: FreezeMode::Pause,
+        };
+        let instance = runtime.create(&spec).await.unwrap();
+        instance.start().await.unwrap();
+        instance.prepare_player_change(1).await.unwrap();
+        std::fs::write(work_dir.join(spec.id.to_string()).join("go"), "").unwrap();
+
+        // player0 and player2 are frozen, so only the server and player1 exit
+        let mut exited = vec![];
+        while exited.len() < 2 {
+            let exit = exits.next().await.unwrap().unwrap();
+            assert_eq!(exit.game_id, spec.id);
+            exited.push((exit.role, exit.exit_code));
+        }
+        exited.sort();
+        assert_eq!(
+            exited,
+            [
+                ("player1".to_string(), Some(0)),
+                ("server".to_string(), Some(3))
+            ]
+        );
+
+        let reports = instance.collect_reports().await.unwrap();
+        assert_eq!(reports.len(), 4);
+        let server = reports[0].status.as_ref().unwrap();
+        assert_eq!(server.role, "server");
+        assert_eq!(server.exit_code, Some(3));
+        assert_eq!(reports[0].stdout, b"seed=42\n");
+        let player = reports[2].status.as_ref().unwrap();
+        assert_eq!(player.role, "player1");
+        assert_eq!(player.exit_code, Some(0));
+        assert_eq!(reports[2].stdout, b"secret\n");
+        assert_eq!(reports[2].stderr, b"oops\n");
+        assert_eq!(reports[1].status.as_ref().unwrap().exit_code, None);
+
+        instance.cleanup().await.unwrap();
+        assert!(!work_dir.join(spec.id.to_string()).exists());
+        std::fs::remove_dir_all(work_dir).unwrap();
+    }
+}
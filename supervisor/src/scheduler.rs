@@ -0,0 +1,226 @@
+use crate::game::update_queue_positions;
+use crate::runtime::{GameInstance, GameSpec, RuntimeError};
+use crate::GameController;
+use splendor_proto::controller::{game_result::EndReason, GameStatus};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Starts queued games while there are free slots.
+///
+/// Games with a higher priority start first, games with the same priority start in
+/// creation order. At most `max_concurrent_games` games are created, starting or running at
+/// once.
+pub struct Scheduler {
+    controller: GameController,
+}
+
+impl Scheduler {
+    pub fn new(controller: GameController) -> Self {
+        Self { controller }
+    }
+
+    pub async fn run(self) {
+        loop {
+            self.controller.schedule.notified().await;
+            while let Some((game_id, spec)) = self.dequeue().await {
+                if let Err(e) = self.launch(&game_id, &spec).await {
+                    error!("Failed to start game#{game_id}: {}", e);
+                    if let Err(e) = self
+                        .controller
+                        .end_game(&game_id, vec![], EndReason::Crashed)
+                        .await
+                    {
+                        error!("Failed to end game#{game_id}: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Take the next game out of the queue if there is a free slot.
+    async fn dequeue(&self) -> Option<(Uuid, GameSpec)> {
+        let mut guard = self.controller.games.lock().await;
+        let active = guard.values().filter(|game| game.is_active()).count();
+        if active >= self.controller.max_concurrent_games {
+            return None;
+        }
+        let (_, game_id) = guard
+            .iter()
+            .filter_map(|(game_id, game)| game.queue_key().map(|key| (key, *game_id)))
+            .max()?;
+        let spec = guard.get_mut(&game_id)?.dequeue()?;
+        update_queue_positions(&guard);
+        debug!("Game#{game_id} dequeued, {} games active", active + 1);
+        Some((game_id, spec))
+    }
+
+    async fn launch(&self, game_id: &Uuid, spec: &GameSpec) -> Result<(), RuntimeError> {
+        let instance = Arc::<dyn GameInstance>::from(self.controller.runtime.create(spec).await?);
+        let starting = self
+            .controller
+            .games
+            .lock()
+            .await
+            .get_mut(game_id)
+            .filter(|game| game.status() == GameStatus::Created)
+            .map(|game| game.set_starting(instance.clone()))
+            .is_some();
+        if !starting {
+            info!("Game#{game_id} cancelled while being created");
+            return instance.cleanup().await;
+        }
+
+        // starting can take a while, the RPCs and the watchdog are not blocked meanwhile
+        let started = instance.start().await;
+        let mut guard = self.controller.games.lock().await;
+        let Some(game) = guard
+            .get_mut(game_id)
+            .filter(|game| game.status() == GameStatus::Starting)
+        else {
+            // the game has been ended and cleaned up by someone else
+            info!("Game#{game_id} ended while starting");
+            return Ok(());
+        };
+        // a failed game is ended by the caller, which cleans up the instance
+        started?;
+        game.set_running();
+        info!("Game#{game_id} started");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{controller, create_game, game_request, wait_for, waiting_server};
+    use splendor_proto::controller::{
+        controller_server::Controller, CancelGameRequest, CreateGameRequest, GetGameLogsRequest,
+        GetGameRequest, ListGamesRequest,
+    };
+    use splendor_proto::supervisor::{
+        game_ends_message, supervisor_server::Supervisor, GameEndsMessage,
+    };
+    use tonic::Request;
+
+    #[tokio::test]
+    async fn test_schedule() {
+        let mut controller = controller();
+        controller.max_concurrent_games = 1;
+        tokio::spawn(Scheduler::new(controller.clone()).run());
+        let get = |game_id: &str| {
+            let controller = controller.clone();
+            let game_id = game_id.to_string();
+            async move {
+                controller
+                    .get_game(Request::new(GetGameRequest { game_id }))
+                    .await
+                    .unwrap()
+                    .into_inner()
+            }
+        };
+        let cancel = |game_id: &str| {
+            controller.cancel_game(Request::new(CancelGameRequest {
+                game_id: game_id.to_string(),
+            }))
+        };
+
+        let first = create_game(&controller, game_request(2)).await;
+        wait_for(&controller, &first, GameStatus::Running).await;
+        let queued = create_game(&controller, game_request(3)).await;
+        let urgent = create_game(
+            &controller,
+            CreateGameRequest {
+                priority: 1,
+                ..game_request(2)
+            },
+        )
+        .await;
+        // the slot is taken, the game with a higher priority goes first
+        assert_eq!(get(&urgent).await.queue_position, Some(0));
+        assert_eq!(get(&queued).await.queue_position, Some(1));
+
+        controller
+            .report_game_ends(Request::new(GameEndsMessage {
+                game_id: first.clone(),
+                winners: vec![1],
+                reason: game_ends_message::EndReason::Normal as i32,
+            }))
+            .await
+            .unwrap();
+        let info = get(&first).await;
+        assert_eq!(info.status(), GameStatus::Ended);
+        let result = info.result.unwrap();
+        assert_eq!(result.winners, [1]);
+        assert_eq!(result.reason(), EndReason::Normal);
+        let logs = controller
+            .get_game_logs(Request::new(GetGameLogsRequest {
+                game_id: first.clone(),
+                role: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let roles = logs
+            .containers
+            .iter()
+            .map(|report| report.status.as_ref().unwrap().role.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(roles, ["server", "player0", "player1"]);
+        assert_eq!(logs.containers[0].stdout, b"seed=42\n");
+
+        // the freed slot goes to the next game of the queue
+        wait_for(&controller, &urgent, GameStatus::Running).await;
+        assert_eq!(get(&queued).await.queue_position, Some(0));
+        cancel(&urgent).await.unwrap();
+        wait_for(&controller, &queued, GameStatus::Running).await;
+        cancel(&queued).await.unwrap();
+        assert_eq!(get(&queued).await.status(), GameStatus::Ended);
+    }
+
+    #[tokio::test]
+    async fn test_slow_start() {
+        let mut controller = controller();
+        controller.max_concurrent_games = 3;
+        tokio::spawn(Scheduler::new(controller.clone()).run());
+        let request = CreateGameRequest {
+            server_image: waiting_server("go"),
+            ..game_request(2)
+        };
+        let game_id = create_game(&controller, request.clone()).await;
+        wait_for(&controller, &game_id, GameStatus::Starting).await;
+        // the games can be used while the server is not ready
+        let other = create_game(&controller, game_request(2)).await;
+        let games = controller
+            .list_games(Request::new(ListGamesRequest::default()))
+            .await
+            .unwrap()
+            .into_inner()
+            .games;
+        assert_eq!(games.len(), 2);
+        let info = controller
+            .get_game(Request::new(GetGameRequest {
+                game_id: game_id.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(info.status(), GameStatus::Starting);
+
+        std::fs::write(controller.game_dir(&game_id).join("go"), "").unwrap();
+        wait_for(&controller, &game_id, GameStatus::Running).await;
+        wait_for(&controller, &other, GameStatus::Running).await;
+
+        // a game cancelled while starting is cleaned up
+        let cancelled = create_game(&controller, request).await;
+        wait_for(&controller, &cancelled, GameStatus::Starting).await;
+        controller
+            .cancel_game(Request::new(CancelGameRequest {
+                game_id: cancelled.clone(),
+            }))
+            .await
+            .unwrap();
+        let info = wait_for(&controller, &cancelled, GameStatus::Ended).await;
+        assert_eq!(info.result.unwrap().reason(), EndReason::Cancelled);
+        assert!(!controller.game_dir(&cancelled).exists());
+    }
+}
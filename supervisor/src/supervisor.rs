@@ -0,0 +1,224 @@
+use crate::{parse_game_id, unknown_game, GameController};
+use splendor_proto::controller::game_result;
+use splendor_proto::supervisor::{
+    game_ends_message::EndReason, game_event::Event, supervisor_server::Supervisor,
+    GameEndsMessage, GameEvent, PreparePlayerChangeMessage,
+};
+use std::time::Instant;
+use tonic::{Request, Response, Status, Streaming};
+
+#[tonic::async_trait]
+impl Supervisor for GameController {
+    async fn report_game_ends(
+        &self,
+        request: Request<GameEndsMessage>,
+    ) -> Result<Response<()>, Status> {
+        let GameEndsMessage {
+            game_id,
+            winners,
+            reason,
+        } = request.into_inner();
+        let game_id = parse_game_id(&game_id, "report_game_ends")?;
+        let reason = EndReason::try_from(reason).map_err(|_| {
+            error!(
+                "Received invalid EndReason while handling report_game_ends, reason: {}",
+                reason
+            );
+            Status::invalid_argument("Invalid EndReason")
+        })?;
+        info!("Game#{game_id} Ends ({reason:?}), winners: {winners:?}");
+        self.end_game(&game_id, winners, reason.into()).await?;
+        Ok(Response::new(()))
+    }
+
+    async fn prepare_player_change(
+        &self,
+        request: Request<PreparePlayerChangeMessage>,
+    ) -> Result<Response<()>, Status> {
+        let req = request.into_inner();
+        let game_id = parse_game_id(&req.game_id, "prepare_player_change")?;
+        let mut guard = self.games.lock().await;
+        let game = guard
+            .get_mut(&game_id)
+            .ok_or_else(|| unknown_game(&game_id, "prepare_player_change"))?;
+        game.heartbeat();
+        let next_player = usize::try_from(req.next_player)
+            .ok()
+            .filter(|player| *player < game.n_players())
+            .ok_or_else(|| {
+                error!(
+                    "Received invalid next player while handling prepare_player_change, next player: {}",
+                    req.next_player
+                );
+                Status::invalid_argument("Invalid next player")
+            })?;
+        let instance = game
+            .instance()
+            .ok_or_else(|| Status::failed_precondition("Game already ended"))?;
+        drop(guard);
+        info!(
+            "Prepare player change for game#{game_id}, next player: {}",
+            req.next_player
+        );
+        let started_at = Instant::now();
+        instance
+            .prepare_player_change(next_player)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to prepare player change for game ID: {}, cause: {e}",
+                    req.game_id
+                );
+                Status::internal(format!("Failed to prepare player change: {}", e))
+            })?;
+        let overhead = started_at.elapsed();
+        debug!("Game#{game_id} player change took {overhead:?}");
+        // the runtime call can take a while, the other games are not blocked meanwhile
+        if let Some(game) = self
+            .games
+            .lock()
+            .await
+            .get(&game_id)
+            .filter(|game| !game.is_ending())
+        {
+            game.set_current_player(next_player, overhead);
+        }
+        Ok(Response::new(()))
+    }
+
+    async fn report_events(
+        &self,
+        request: Request<Streaming<GameEvent>>,
+    ) -> Result<Response<()>, Status> {
+        let mut events = request.into_inner();
+        while let Some(GameEvent { event }) = events.message().await? {
+            match event {
+                Some(Event::GameStarts(event)) => {
+                    let game_id = parse_game_id(&event.game_id, "report_events")?;
+                    info!("Game#{game_id} starts");
+                    self.games
+                        .lock()
+                        .await
+                        .get_mut(&game_id)
+                        .map(|game| {
+                            game.heartbeat();
+                            game.set_started();
+                        })
+                        .ok_or_else(|| unknown_game(&game_id, "report_events"))?;
+                }
+                Some(Event::TurnEnds(event)) => {
+                    let game_id = parse_game_id(&event.game_id, "report_events")?;
+                    debug!(
+                        "Game#{game_id} player#{} ended turn in {}ms, points: {:?}",
+                        event.player, event.elapsed_ms, event.points
+                    );
+                    let timeout = self
+                        .games
+                        .lock()
+                        .await
+                        .get_mut(&game_id)
+                        .map(|game| {
+                            game.heartbeat();
+                            game.record_turn(event)
+                        })
+                        .ok_or_else(|| unknown_game(&game_id, "report_events"))?;
+                    if timeout {
+                        warn!("Game#{game_id} exceeded its time limit");
+                        self.end_game(&game_id, vec![], game_result::EndReason::Timeout)
+                            .await?;
+                        break;
+                    }
+                }
+                None => {
+                    error!("Received empty event while handling report_events");
+                    return Err(Status::invalid_argument("Empty event"));
+                }
+            }
+        }
+        Ok(Response::new(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::Scheduler;
+    use crate::testing::{
+        controller, create_game, game_request, random_game, stub, wait_for, waiting_player,
+    };
+    use splendor_proto::controller::{
+        controller_server::Controller, game_result, CancelGameRequest, CreateGameRequest,
+        GameStatus, GetGameRequest,
+    };
+    use splendor_proto::supervisor::{supervisor_server::Supervisor, PreparePlayerChangeMessage};
+    use tonic::{Code, Request};
+
+    #[tokio::test]
+    async fn test_prepare_player_change() {
+        let controller = controller();
+        tokio::spawn(Scheduler::new(controller.clone()).run());
+        let game_id = create_game(&controller, game_request(2)).await;
+        wait_for(&controller, &game_id, GameStatus::Running).await;
+        let change = |next_player: i32| {
+            controller.prepare_player_change(Request::new(PreparePlayerChangeMessage {
+                game_id: game_id.clone(),
+                next_player,
+            }))
+        };
+        for next_player in [-1, 2, i32::MAX] {
+            let error = change(next_player).await.unwrap_err();
+            assert_eq!(error.code(), Code::InvalidArgument, "player {next_player}");
+        }
+        change(1).await.unwrap();
+        let info = controller
+            .get_game(Request::new(GetGameRequest {
+                game_id: game_id.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(info.current_player, Some(1));
+        assert_eq!(info.turns, 1);
+
+        controller
+            .cancel_game(Request::new(CancelGameRequest { game_id }))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_two_players() {
+        let controller = controller();
+        controller.serve_supervisor();
+        tokio::spawn(Scheduler::new(controller.clone()).run());
+        let request = CreateGameRequest {
+            server_image: stub("game", &[]),
+            player_images: vec![waiting_player("never"); 2],
+            ..game_request(2)
+        };
+        let game_id = create_game(&controller, request).await;
+        let info = wait_for(&controller, &game_id, GameStatus::Ended).await;
+
+        // the stub played the same game
+        let mut game = random_game(42, 2);
+        let mut turns = 1;
+        let winners = loop {
+            if let Some(winners) = game.step().await.unwrap() {
+                break winners;
+            }
+            turns += 1;
+        };
+        let result = info.result.unwrap();
+        assert_eq!(result.reason(), game_result::EndReason::Normal);
+        assert_eq!(
+            result.winners,
+            winners.iter().map(|idx| *idx as i32).collect::<Vec<_>>()
+        );
+        assert_eq!(result.containers.len(), 3);
+        assert_eq!(info.turns, turns);
+        assert_eq!(info.round, game.current_round() as u64);
+        assert_eq!(
+            info.points,
+            game.points().iter().map(|p| *p as u32).collect::<Vec<_>>()
+        );
+    }
+}
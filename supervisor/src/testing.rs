@@ -0,0 +1,307 @@
+//! Helpers of the supervisor tests.
+//!
+//! The games of the tests run on the [`ProcessRuntime`], with the test binary itself as the
+//! game server and players: when spawned with [`STUB`] set, it runs the stub instead of the
+//! tests.
+
+use crate::runtime::ProcessRuntime;
+use crate::GameController;
+use futures_util::StreamExt;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use splendor_config::ServerConfig;
+use splendor_core::naive_actors::RandomActor;
+use splendor_engine::GameContext;
+use splendor_proto::controller::{
+    controller_server::Controller, CreateGameRequest, GameInfo, GameStatus, WatchGameRequest,
+};
+use splendor_proto::supervisor::{
+    game_ends_message::EndReason, game_event::Event, supervisor_client::SupervisorClient,
+    supervisor_server::SupervisorServer, GameEndsMessage, GameEvent, GameStartsMessage,
+    PreparePlayerChangeMessage, TurnEndsMessage,
+};
+use std::net::TcpListener;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, UnixListenerStream};
+use tonic::transport::{Endpoint, Server, Uri};
+use tonic::Request;
+use tower::service_fn;
+use uuid::Uuid;
+
+/// Selects the stub run by the test binary, `server`, `game` or `player`.
+const STUB: &str = "SUPERVISOR_STUB";
+/// The exit code of the stub server after the readiness probe, it serves until killed if unset.
+const STUB_EXIT: &str = "SUPERVISOR_STUB_EXIT";
+/// The stubs wait for this file in the game directory if set, the server before listening and
+/// the players before exiting.
+const STUB_WAIT: &str = "SUPERVISOR_STUB_WAIT";
+
+#[ctor::ctor]
+fn run_stub() {
+    let Ok(stub) = std::env::var(STUB) else {
+        return;
+    };
+    let code = match stub.as_str() {
+        "server" => stub_server(),
+        "game" => stub_game(),
+        "player" => stub_player(),
+        _ => panic!("unknown stub {stub}"),
+    };
+    std::process::exit(code);
+}
+
+/// Writes the secrets and listens like the game server, but never plays.
+fn stub_server() -> i32 {
+    let config = ServerConfig::from_env().unwrap();
+    write_secrets(&config);
+    println!("seed={}", config.seed.unwrap_or_default());
+    wait_file();
+    let listener = TcpListener::bind(config.server_addr).unwrap();
+    listener.accept().unwrap();
+    match std::env::var(STUB_EXIT) {
+        Ok(code) => code.parse().unwrap(),
+        Err(_) => loop {
+            std::thread::park();
+        },
+    }
+}
+
+/// Plays a seeded game of random actors and reports it to the supervisor like the game server.
+fn stub_game() -> i32 {
+    let config = ServerConfig::from_env().unwrap();
+    write_secrets(&config);
+    let listener = TcpListener::bind(config.server_addr).unwrap();
+    listener.accept().unwrap();
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(report_game(config));
+    0
+}
+
+async fn report_game(config: ServerConfig) {
+    let socket = config.supervisor_socket.unwrap();
+    let channel = Endpoint::try_from("http://[::]:50051")
+        .unwrap()
+        .connect_with_connector(service_fn(move |_: Uri| {
+            UnixStream::connect(socket.clone())
+        }))
+        .await
+        .unwrap();
+    let mut client = SupervisorClient::new(channel);
+    let game_id = config.game_id.to_string();
+    let (sender, receiver) = mpsc::channel(16);
+    let events = tokio::spawn({
+        let mut client = client.clone();
+        async move { client.report_events(ReceiverStream::new(receiver)).await }
+    });
+    let send = |event| sender.send(GameEvent { event: Some(event) });
+    send(Event::GameStarts(GameStartsMessage {
+        game_id: game_id.clone(),
+    }))
+    .await
+    .unwrap();
+
+    let mut game = random_game(config.seed.unwrap(), config.n_players);
+    let mut reported_records = 0;
+    let winners = loop {
+        let player = game.current_player();
+        let round = game.current_round();
+        client
+            .prepare_player_change(PreparePlayerChangeMessage {
+                game_id: game_id.clone(),
+                next_player: player as i32,
+            })
+            .await
+            .unwrap();
+        let winners = game.step().await.unwrap();
+        let records = game.records()[reported_records..]
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap())
+            .collect();
+        reported_records = game.records().len();
+        send(Event::TurnEnds(TurnEndsMessage {
+            game_id: game_id.clone(),
+            player: player as i32,
+            round: round as u64,
+            records,
+            points: game.points().iter().map(|p| *p as u32).collect(),
+            elapsed_ms: 0,
+        }))
+        .await
+        .unwrap();
+        if let Some(winners) = winners {
+            break winners;
+        }
+    };
+    drop(sender);
+    events.await.unwrap().unwrap();
+    // the supervisor kills the server once the game has ended
+    let _ = client
+        .report_game_ends(GameEndsMessage {
+            game_id,
+            winners: winners.iter().map(|idx| *idx as i32).collect(),
+            reason: EndReason::Normal as i32,
+        })
+        .await;
+}
+
+fn write_secrets(config: &ServerConfig) {
+    for idx in 0..config.n_players {
+        let dir = config.secrets_path.join(format!("player{idx}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("secret"), "secret\n").unwrap();
+    }
+}
+
+/// Prints its secret and exits.
+fn stub_player() -> i32 {
+    print!(
+        "{}",
+        std::fs::read_to_string(std::env::var("CLIENT_SECRET").unwrap()).unwrap()
+    );
+    eprintln!("oops");
+    wait_file();
+    0
+}
+
+fn wait_file() {
+    if let Ok(file) = std::env::var(STUB_WAIT) {
+        while !Path::new(&file).exists() {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+}
+
+/// The command running a stub, with extra environment variables.
+pub fn stub(role: &str, env: &[(&str, &str)]) -> String {
+    let exe = std::env::current_exe().unwrap();
+    let mut command = format!("{STUB}={role}");
+    for (name, value) in env {
+        command.push_str(&format!(" {name}='{value}'"));
+    }
+    format!("{command} exec '{}'", exe.display())
+}
+
+/// The command of a stub server exiting with `code` after the readiness probe.
+pub fn crashing_server(code: i32) -> String {
+    stub("server", &[(STUB_EXIT, &code.to_string())])
+}
+
+/// The command of a stub server waiting for `file` in the game directory before listening.
+pub fn waiting_server(file: &str) -> String {
+    stub("server", &[(STUB_WAIT, file)])
+}
+
+/// The command of a stub player waiting for `file` in the game directory before exiting.
+pub fn waiting_player(file: &str) -> String {
+    stub("player", &[(STUB_WAIT, file)])
+}
+
+/// The game played by the `game` stub, each random actor seeded with `seed` plus its index.
+pub fn random_game(seed: u64, n_players: usize) -> GameContext {
+    let actors = (0..n_players)
+        .map(|idx| Box::new(RandomActor::new(SmallRng::seed_from_u64(seed + idx as u64))) as _)
+        .collect();
+    GameContext::with_rng(&mut SmallRng::seed_from_u64(seed), actors)
+}
+
+/// A controller running games as local processes, its directory is removed on drop.
+pub(crate) struct TestController {
+    controller: GameController,
+    work_dir: PathBuf,
+}
+
+impl Deref for TestController {
+    type Target = GameController;
+
+    fn deref(&self) -> &GameController {
+        &self.controller
+    }
+}
+
+impl DerefMut for TestController {
+    fn deref_mut(&mut self) -> &mut GameController {
+        &mut self.controller
+    }
+}
+
+impl TestController {
+    /// The directory of a game, where the stubs run.
+    pub fn game_dir(&self, game_id: &str) -> PathBuf {
+        self.work_dir.join(game_id)
+    }
+
+    /// Serve the supervisor service to the game servers, like the supervisor binary.
+    pub fn serve_supervisor(&self) {
+        std::fs::create_dir_all(&self.work_dir).unwrap();
+        let uds = UnixListener::bind(self.work_dir.join("supervisor.sock")).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(SupervisorServer::new(self.controller.clone()))
+                .serve_with_incoming(UnixListenerStream::new(uds)),
+        );
+    }
+}
+
+impl Drop for TestController {
+    fn drop(&mut self) {
+        // the games ended by the watchdog may still be removing their directories
+        while self.work_dir.exists() {
+            let _ = std::fs::remove_dir_all(&self.work_dir);
+        }
+    }
+}
+
+/// A controller running games as local processes in a fresh directory.
+pub fn controller() -> TestController {
+    let work_dir = std::env::temp_dir().join(format!("splendor-test-{}", Uuid::new_v4()));
+    let socket = work_dir.join("supervisor.sock");
+    TestController {
+        controller: GameController::new(Arc::new(ProcessRuntime::new(work_dir.clone(), socket))),
+        work_dir,
+    }
+}
+
+/// The request of a game of `n_players` stub players on a stub server.
+pub fn game_request(n_players: usize) -> CreateGameRequest {
+    CreateGameRequest {
+        server_image: stub("server", &[]),
+        player_images: vec![stub("player", &[]); n_players],
+        seed: Some(42),
+        ..Default::default()
+    }
+}
+
+/// Create a game, returns its id.
+pub async fn create_game(controller: &GameController, request: CreateGameRequest) -> String {
+    controller
+        .create_game(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+        .game_id
+}
+
+/// Watch a game until it reaches the status.
+pub async fn wait_for(controller: &GameController, game_id: &str, status: GameStatus) -> GameInfo {
+    let mut stream = controller
+        .watch_game(Request::new(WatchGameRequest {
+            game_id: game_id.to_string(),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    while let Some(info) = stream.next().await {
+        let info = info.unwrap();
+        if info.status() == status {
+            return info;
+        }
+    }
+    panic!("game#{game_id} ended before reaching {status:?}");
+}
@@ -0,0 +1,181 @@
+use crate::runtime::ExitEvent;
+use crate::GameController;
+use futures_util::StreamExt;
+use splendor_proto::controller::game_result::EndReason;
+use std::time::Duration;
+
+/// Ends games that the game server has given up on.
+///
+/// A running game is ended when:
+/// - it exceeds its game timeout (`TIMEOUT`).
+/// - the game server stops sending heartbeats (`ABANDONED`).
+/// - the game server exits before reporting the game ends (`CRASHED`).
+pub struct Watchdog {
+    controller: GameController,
+    interval: Duration,
+}
+
+impl Watchdog {
+    pub fn new(controller: GameController, interval: Duration) -> Self {
+        Self {
+            controller,
+            interval,
+        }
+    }
+
+    pub async fn run(self) {
+        tokio::join!(self.check_deadlines(), self.watch_containers());
+    }
+
+    async fn check_deadlines(&self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            let expired = self
+                .controller
+                .games
+                .lock()
+                .await
+                .iter()
+                .filter_map(|(game_id, game)| game.expired().map(|reason| (*game_id, reason)))
+                .collect::<Vec<_>>();
+            for (game_id, reason) in expired {
+                warn!("Game#{game_id} expired ({reason:?})");
+                if let Err(e) = self.controller.end_game(&game_id, vec![], reason).await {
+                    error!("Failed to end expired game#{game_id}: {}", e);
+                }
+            }
+        }
+    }
+
+    async fn watch_containers(&self) {
+        loop {
+            let mut exits = self.controller.runtime.exits();
+            while let Some(exit) = exits.next().await {
+                match exit {
+                    Ok(exit) => self.on_exit(exit).await,
+                    Err(e) => {
+                        error!("Failed to receive exit events: {}", e);
+                        break;
+                    }
+                }
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+
+    async fn on_exit(&self, exit: ExitEvent) {
+        let ExitEvent {
+            game_id,
+            role,
+            exit_code,
+        } = exit;
+        debug!("Game#{game_id} {role} exited with code {exit_code:?}");
+        if role != "server" {
+            return;
+        }
+        let running = self
+            .controller
+            .games
+            .lock()
+            .await
+            .get(&game_id)
+            .is_some_and(|game| game.instance().is_some());
+        if running {
+            warn!("Game#{game_id} server exited with code {exit_code:?} before the game ends");
+            if let Err(e) = self
+                .controller
+                .end_game(&game_id, vec![], EndReason::Crashed)
+                .await
+            {
+                error!("Failed to end crashed game#{game_id}: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::Scheduler;
+    use crate::testing::{controller, crashing_server, create_game, game_request, wait_for};
+    use splendor_proto::controller::{CreateGameRequest, GameResult, GameStatus};
+    use uuid::Uuid;
+
+    /// Run the scheduler and the watchdog of the controller.
+    async fn supervise(controller: &GameController) {
+        tokio::spawn(Scheduler::new(controller.clone()).run());
+        tokio::spawn(Watchdog::new(controller.clone(), Duration::from_millis(10)).run());
+        // let the watchdog subscribe to the exits before any game starts
+        tokio::task::yield_now().await;
+    }
+
+    async fn end(controller: &GameController, request: CreateGameRequest) -> GameResult {
+        let game_id = create_game(controller, request).await;
+        let info = wait_for(controller, &game_id, GameStatus::Ended).await;
+        info.result.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_crashed() {
+        let controller = controller();
+        supervise(&controller).await;
+        let request = CreateGameRequest {
+            server_image: crashing_server(3),
+            ..game_request(2)
+        };
+        let result = end(&controller, request).await;
+        assert_eq!(result.reason(), EndReason::Crashed);
+        // the watchers receive the reports with the result
+        assert_eq!(result.server_exit_code, Some(3));
+        let roles = result
+            .containers
+            .iter()
+            .map(|status| status.role.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(roles, ["server", "player0", "player1"]);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned() {
+        let mut controller = controller();
+        controller.heartbeat_grace = Duration::ZERO;
+        supervise(&controller).await;
+        // the stub server never sends a heartbeat
+        let request = CreateGameRequest {
+            step_timeout: Some(1),
+            ..game_request(2)
+        };
+        let result = end(&controller, request).await;
+        assert_eq!(result.reason(), EndReason::Abandoned);
+        // the server was still running
+        assert_eq!(result.server_exit_code, None);
+        assert_eq!(result.containers.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let controller = controller();
+        supervise(&controller).await;
+        let game_id = create_game(
+            &controller,
+            CreateGameRequest {
+                game_timeout: Some(0),
+                ..game_request(2)
+            },
+        )
+        .await;
+        wait_for(&controller, &game_id, GameStatus::Running).await;
+        // the game timeout counts from the start of the game play
+        let id = game_id.parse::<Uuid>().unwrap();
+        controller
+            .games
+            .lock()
+            .await
+            .get_mut(&id)
+            .unwrap()
+            .set_started();
+        let info = wait_for(&controller, &game_id, GameStatus::Ended).await;
+        assert_eq!(info.result.unwrap().reason(), EndReason::Timeout);
+    }
+}
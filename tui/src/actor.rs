@@ -0,0 +1,117 @@
+use crate::input::{Answer, Outcome, Prompt, Request};
+use crate::ui;
+use crossterm::event::{Event, EventStream, KeyEventKind};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::ExecutableCommand;
+use futures_util::StreamExt;
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+use splendor_core::{
+    ActorError, DropTokensAction, GameSnapshot, PlayerAction, PlayerActor, SelectNoblesAction,
+};
+use std::fmt;
+use std::io::{self, Stdout};
+
+/// The player quits the game.
+#[derive(Debug, thiserror::Error)]
+#[error("the player quit the game")]
+pub struct QuitError;
+
+/// A player actor asking a human in the terminal.
+///
+/// The terminal is switched to the alternate screen until the actor is dropped.
+pub struct TuiActor {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TuiActor {
+    /// Take over the terminal.
+    pub fn new() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        io::stdout().execute(EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        terminal.clear()?;
+        Ok(TuiActor { terminal })
+    }
+
+    /// Show the board without asking anything.
+    pub fn show(&mut self, snapshot: &GameSnapshot) -> io::Result<()> {
+        self.terminal
+            .draw(|frame| ui::draw(frame, snapshot, None))?;
+        Ok(())
+    }
+
+    async fn ask(
+        &mut self,
+        request: Request,
+        snapshot: &GameSnapshot,
+    ) -> Result<Answer, ActorError> {
+        let mut prompt = Prompt::new(request, snapshot);
+        let mut events = EventStream::new();
+        loop {
+            self.terminal
+                .draw(|frame| ui::draw(frame, snapshot, Some(&prompt)))?;
+            let Some(event) = events.next().await else {
+                return Err(QuitError.into());
+            };
+            let Event::Key(key) = event? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match prompt.handle(key, snapshot) {
+                Outcome::Continue => {}
+                Outcome::Submit(answer) => {
+                    self.show(snapshot)?;
+                    return Ok(answer);
+                }
+                Outcome::Quit => return Err(QuitError.into()),
+            }
+        }
+    }
+}
+
+impl Drop for TuiActor {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = io::stdout().execute(LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+impl fmt::Debug for TuiActor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TuiActor").finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl PlayerActor for TuiActor {
+    async fn get_action(&mut self, snapshot: GameSnapshot) -> Result<PlayerAction, ActorError> {
+        match self.ask(Request::Action, &snapshot).await? {
+            Answer::Action(action) => Ok(action),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn drop_tokens(
+        &mut self,
+        snapshot: GameSnapshot,
+    ) -> Result<DropTokensAction, ActorError> {
+        match self.ask(Request::DropTokens, &snapshot).await? {
+            Answer::DropTokens(action) => Ok(action),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn select_noble(
+        &mut self,
+        snapshot: GameSnapshot,
+    ) -> Result<SelectNoblesAction, ActorError> {
+        match self.ask(Request::SelectNoble, &snapshot).await? {
+            Answer::SelectNoble(action) => Ok(action),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,389 @@
+//! Turning key presses into answers, checked with the rules of the engine.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use splendor_core::{
+    BuyCardAction, BuyCardSource, Color, ColorVec, DropTokensAction, GameSnapshot, PlayerAction,
+    ReserveCardAction, SelectNoblesAction, TakeTokenAction, Tier,
+};
+use splendor_engine::GameState;
+
+/// What the player is asked for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// The action of the turn.
+    Action,
+    /// The tokens to drop down to 10.
+    DropTokens,
+    /// The noble to visit.
+    SelectNoble,
+}
+
+/// The answer of a [`Request`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Answer {
+    /// The action of the turn.
+    Action(PlayerAction),
+    /// The tokens to drop.
+    DropTokens(DropTokensAction),
+    /// The noble to visit.
+    SelectNoble(SelectNoblesAction),
+}
+
+/// The card slot under the cursor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    /// A revealed card.
+    Revealed {
+        /// The tier of the card.
+        tier: Tier,
+        /// The index of the card.
+        idx: usize,
+    },
+    /// The pool of a tier.
+    Pool(Tier),
+    /// A reserved card of the player.
+    Reserved(usize),
+}
+
+/// The result of handling a key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Keep asking.
+    Continue,
+    /// The answer is valid, send it.
+    Submit(Answer),
+    /// The player quits the game.
+    Quit,
+}
+
+/// The input state of a [`Request`].
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    /// What the player is asked for.
+    pub request: Request,
+    /// The card slot under the cursor.
+    pub cursor: Cursor,
+    /// The tokens picked to take or drop.
+    pub tokens: ColorVec,
+    /// The index of the selected noble.
+    pub noble: usize,
+    /// The error of the last answer.
+    pub message: Option<String>,
+}
+
+impl Prompt {
+    /// Create a new prompt of a request.
+    pub fn new(request: Request, snapshot: &GameSnapshot) -> Self {
+        let noble = visitable_nobles(snapshot).first().copied().unwrap_or(0);
+        Prompt {
+            request,
+            cursor: Cursor::Revealed {
+                tier: Tier::I,
+                idx: 0,
+            },
+            tokens: ColorVec::empty(),
+            noble,
+            message: None,
+        }
+    }
+
+    /// The help line of the prompt.
+    pub fn help(&self) -> &'static str {
+        match self.request {
+            Request::Action => {
+                "←↑↓→ move · p buy · s reserve · k b g r w pick tokens · enter confirm · n pass · esc clear · q quit"
+            }
+            Request::DropTokens => "k b g r w y pick tokens to drop · enter confirm · esc clear · q quit",
+            Request::SelectNoble => "←→ select noble · enter confirm · q quit",
+        }
+    }
+
+    /// Handle a key press.
+    pub fn handle(&mut self, key: KeyEvent, snapshot: &GameSnapshot) -> Outcome {
+        if key.code == KeyCode::Char('q')
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+        {
+            return Outcome::Quit;
+        }
+        self.message = None;
+        let answer = match self.request {
+            Request::Action => self.handle_action(key, snapshot),
+            Request::DropTokens => self.handle_drop(key, snapshot),
+            Request::SelectNoble => self.handle_noble(key, snapshot),
+        };
+        match answer.map(|answer| validate(snapshot, answer).map(|_| answer)) {
+            Some(Ok(answer)) => Outcome::Submit(answer),
+            Some(Err(reason)) => {
+                self.message = Some(reason);
+                Outcome::Continue
+            }
+            None => Outcome::Continue,
+        }
+    }
+
+    fn handle_action(&mut self, key: KeyEvent, snapshot: &GameSnapshot) -> Option<Answer> {
+        let player = snapshot.current_player();
+        match key.code {
+            KeyCode::Up | KeyCode::Down => {
+                // rows from the top: tier III, tier II, tier I, reserved cards
+                let row = match self.cursor {
+                    Cursor::Revealed { tier, .. } | Cursor::Pool(tier) => 2 - tier as usize,
+                    Cursor::Reserved(_) => 3,
+                };
+                let row = if key.code == KeyCode::Up {
+                    row.saturating_sub(1)
+                } else {
+                    (row + 1).min(if player.reserved_cards.is_empty() {
+                        2
+                    } else {
+                        3
+                    })
+                };
+                self.cursor = match row {
+                    3 => Cursor::Reserved(0),
+                    row => Cursor::Revealed {
+                        tier: Tier::try_from(2 - row).unwrap(),
+                        idx: 0,
+                    },
+                };
+            }
+            KeyCode::Left | KeyCode::Right => {
+                let right = key.code == KeyCode::Right;
+                self.cursor = match self.cursor {
+                    Cursor::Revealed { tier, idx } => {
+                        let len = snapshot.card_pool.revealed[tier as usize].len();
+                        match (right, idx) {
+                            (true, idx) if idx + 1 >= len => Cursor::Pool(tier),
+                            (true, idx) => Cursor::Revealed { tier, idx: idx + 1 },
+                            (false, idx) => Cursor::Revealed {
+                                tier,
+                                idx: idx.saturating_sub(1),
+                            },
+                        }
+                    }
+                    Cursor::Pool(tier) => {
+                        let len = snapshot.card_pool.revealed[tier as usize].len();
+                        if right || len == 0 {
+                            Cursor::Pool(tier)
+                        } else {
+                            Cursor::Revealed { tier, idx: len - 1 }
+                        }
+                    }
+                    Cursor::Reserved(idx) => {
+                        let last = player.reserved_cards.len().saturating_sub(1);
+                        if right {
+                            Cursor::Reserved((idx + 1).min(last))
+                        } else {
+                            Cursor::Reserved(idx.saturating_sub(1))
+                        }
+                    }
+                };
+            }
+            KeyCode::Char(c) if color_of(c).is_some_and(|c| c != Color::Yellow) => {
+                let color = color_of(c).unwrap();
+                if self.tokens.get(color) < 2 {
+                    self.tokens.add(color, 1);
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace => self.tokens = ColorVec::empty(),
+            KeyCode::Char('n') => return Some(Answer::Action(PlayerAction::Nop)),
+            KeyCode::Char('s') => {
+                let action = match self.cursor {
+                    Cursor::Revealed { tier, idx } => ReserveCardAction {
+                        tier,
+                        idx: Some(idx),
+                    },
+                    Cursor::Pool(tier) => ReserveCardAction { tier, idx: None },
+                    Cursor::Reserved(_) => {
+                        self.message = Some("the card is already reserved".to_string());
+                        return None;
+                    }
+                };
+                return Some(Answer::Action(PlayerAction::ReserveCard(action)));
+            }
+            KeyCode::Enter if self.tokens.total() > 0 => {
+                let tokens = self.tokens;
+                let action = if tokens.iter().any(|n| n == 2) {
+                    TakeTokenAction::TwoSame(tokens)
+                } else {
+                    TakeTokenAction::ThreeDifferent(tokens)
+                };
+                return Some(Answer::Action(PlayerAction::TakeTokens(action)));
+            }
+            KeyCode::Char('p') | KeyCode::Enter => {
+                let target = match self.cursor {
+                    Cursor::Revealed { tier, idx } => snapshot.card_pool.revealed[tier as usize]
+                        .get(idx)
+                        .map(|card| (card, BuyCardSource::Revealed { tier, idx })),
+                    Cursor::Reserved(idx) => player
+                        .reserved_cards
+                        .get(idx)
+                        .map(|card| (card.unwrap(), BuyCardSource::Reserved(idx))),
+                    Cursor::Pool(_) => None,
+                };
+                let Some((card, source)) = target else {
+                    self.message = Some("no card to buy here".to_string());
+                    return None;
+                };
+                let Some(uses) = player.payment(card) else {
+                    self.message = Some("not enough tokens to buy the card".to_string());
+                    return None;
+                };
+                return Some(Answer::Action(PlayerAction::BuyCard(BuyCardAction {
+                    source,
+                    uses,
+                })));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_drop(&mut self, key: KeyEvent, snapshot: &GameSnapshot) -> Option<Answer> {
+        let player = snapshot.current_player();
+        match key.code {
+            KeyCode::Char(c) if color_of(c).is_some() => {
+                let color = color_of(c).unwrap();
+                if self.tokens.get(color) < player.tokens.get(color) {
+                    self.tokens.add(color, 1);
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace => self.tokens = ColorVec::empty(),
+            KeyCode::Enter => {
+                let excess = player.tokens.total() - 10;
+                if self.tokens.total() != excess {
+                    self.message = Some(format!("drop exactly {excess} tokens"));
+                    return None;
+                }
+                return Some(Answer::DropTokens(DropTokensAction(self.tokens)));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_noble(&mut self, key: KeyEvent, snapshot: &GameSnapshot) -> Option<Answer> {
+        let nobles = visitable_nobles(snapshot);
+        let pos = nobles.iter().position(|n| *n == self.noble).unwrap_or(0);
+        match key.code {
+            KeyCode::Left => self.noble = nobles[pos.saturating_sub(1)],
+            KeyCode::Right => self.noble = nobles[(pos + 1).min(nobles.len() - 1)],
+            KeyCode::Enter => return Some(Answer::SelectNoble(SelectNoblesAction(self.noble))),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Get the color of a key, `k` is black.
+pub fn color_of(key: char) -> Option<Color> {
+    match key {
+        'k' => Some(Color::Black),
+        'b' => Some(Color::Blue),
+        'g' => Some(Color::Green),
+        'r' => Some(Color::Red),
+        'w' => Some(Color::White),
+        'y' => Some(Color::Yellow),
+        _ => None,
+    }
+}
+
+/// Get the index of the nobles the current player can visit.
+pub fn visitable_nobles(snapshot: &GameSnapshot) -> Vec<usize> {
+    let bonus = &snapshot.current_player().development_cards.bonus;
+    snapshot
+        .nobles
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| n.requires.le(bonus))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Check an answer with the rules of the engine.
+pub fn validate(snapshot: &GameSnapshot, answer: Answer) -> Result<(), String> {
+    // the hidden cards do not matter to the rules of the current player
+    let mut state = GameState::determinize(snapshot, &mut SmallRng::seed_from_u64(0));
+    match answer {
+        Answer::Action(action) => state.apply_action(&action).map(|_| ()),
+        Answer::DropTokens(action) => state.drop_tokens(&action),
+        Answer::SelectNoble(action) => state.visit_noble(&action).map(|_| ()),
+    }
+    .map_err(|e| e.reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEvent;
+
+    fn snapshot() -> GameSnapshot {
+        GameState::with_rng(&mut SmallRng::seed_from_u64(42), 2).snapshot()
+    }
+
+    fn press(prompt: &mut Prompt, snapshot: &GameSnapshot, keys: &str) -> Outcome {
+        let mut outcome = Outcome::Continue;
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '>' => KeyCode::Right,
+                c => KeyCode::Char(c),
+            };
+            outcome = prompt.handle(KeyEvent::from(code), snapshot);
+        }
+        outcome
+    }
+
+    #[test]
+    fn test_take_tokens() {
+        let snapshot = snapshot();
+        let mut prompt = Prompt::new(Request::Action, &snapshot);
+        assert_eq!(
+            press(&mut prompt, &snapshot, "kbg\n"),
+            Outcome::Submit(Answer::Action(PlayerAction::TakeTokens(
+                TakeTokenAction::ThreeDifferent(ColorVec::new(1, 1, 1, 0, 0, 0))
+            )))
+        );
+
+        let mut prompt = Prompt::new(Request::Action, &snapshot);
+        assert_eq!(
+            press(&mut prompt, &snapshot, "rr\n"),
+            Outcome::Submit(Answer::Action(PlayerAction::TakeTokens(
+                TakeTokenAction::TwoSame(ColorVec::new(0, 0, 0, 2, 0, 0))
+            )))
+        );
+
+        // rejected by the rules, the prompt keeps asking
+        let mut prompt = Prompt::new(Request::Action, &snapshot);
+        assert_eq!(press(&mut prompt, &snapshot, "kkb\n"), Outcome::Continue);
+        assert!(prompt.message.is_some());
+    }
+
+    #[test]
+    fn test_cards() {
+        let snapshot = snapshot();
+        let mut prompt = Prompt::new(Request::Action, &snapshot);
+        assert_eq!(
+            press(&mut prompt, &snapshot, ">s"),
+            Outcome::Submit(Answer::Action(PlayerAction::ReserveCard(
+                ReserveCardAction {
+                    tier: Tier::I,
+                    idx: Some(1)
+                }
+            )))
+        );
+        assert_eq!(
+            press(&mut prompt, &snapshot, ">>>s"),
+            Outcome::Submit(Answer::Action(PlayerAction::ReserveCard(
+                ReserveCardAction {
+                    tier: Tier::I,
+                    idx: None
+                }
+            )))
+        );
+        // nothing can be bought without tokens
+        assert_eq!(press(&mut prompt, &snapshot, "p"), Outcome::Continue);
+        assert!(prompt.message.is_some());
+    }
+}
@@ -0,0 +1,9 @@
+//! A terminal client for humans to play against bots or on a game server.
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+
+mod actor;
+pub mod input;
+mod ui;
+
+pub use actor::{QuitError, TuiActor};
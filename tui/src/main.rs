@@ -0,0 +1,86 @@
+use rand::prelude::*;
+use smallvec::SmallVec;
+use splendor_bots::{MctsActor, MctsConfig};
+use splendor_core::naive_actors::{GreedyActor, RandomActor};
+use splendor_core::{PlayerActor, MAX_PLAYERS};
+use splendor_engine::GameContext;
+use splendor_sdk::WebSocketActorClient;
+use splendor_tui::TuiActor;
+use std::error::Error;
+
+const USAGE: &str = "\
+usage: splendor-tui local [--players N] [--bot random|greedy|mcts] [--seed SEED]
+       splendor-tui remote
+
+local   play as player#0 against bots in this process
+remote  play on a game server, connecting with RPC_URL and CLIENT_SECRET";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("local") => local(&args[1..]).await,
+        Some("remote") => {
+            let mut client = WebSocketActorClient::from_env(TuiActor::new()?).await?;
+            client.run().await?;
+            Ok(())
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
+
+async fn local(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut n_players = 2;
+    let mut bot = "greedy".to_string();
+    let mut seed = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value of {arg}\n{USAGE}"))?;
+        match arg.as_str() {
+            "--players" => n_players = value.parse()?,
+            "--bot" => bot = value.clone(),
+            "--seed" => seed = Some(value.parse::<u64>()?),
+            _ => return Err(format!("unknown argument {arg}\n{USAGE}").into()),
+        }
+    }
+    if !(2..=MAX_PLAYERS).contains(&n_players) {
+        return Err(format!("the number of players must be between 2 and {MAX_PLAYERS}").into());
+    }
+
+    let mut rng = match seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_entropy(),
+    };
+    let mut actors: SmallVec<Box<dyn PlayerActor>, MAX_PLAYERS> = SmallVec::new();
+    actors.push(Box::new(TuiActor::new()?));
+    for _ in 1..n_players {
+        let bot_rng = SmallRng::seed_from_u64(rng.next_u64());
+        actors.push(match bot.as_str() {
+            "random" => Box::new(RandomActor::new(bot_rng)),
+            "greedy" => Box::new(GreedyActor::new(bot_rng)),
+            "mcts" => Box::new(MctsActor::new(bot_rng, MctsConfig::default())),
+            _ => return Err(format!("unknown bot {bot}\n{USAGE}").into()),
+        });
+    }
+
+    let mut game = GameContext::with_rng(&mut rng, actors);
+    let winners = loop {
+        if let Some(winners) = game.step().await? {
+            break winners;
+        }
+    };
+    let points = game.points();
+    // restore the terminal before printing the result
+    drop(game);
+    for (idx, points) in points.iter().enumerate() {
+        let you = if idx == 0 { " (you)" } else { "" };
+        let won = if winners.contains(&idx) { ", won" } else { "" };
+        println!("Player#{idx}{you}: {points} points{won}");
+    }
+    Ok(())
+}
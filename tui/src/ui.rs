@@ -0,0 +1,282 @@
+use crate::input::{visitable_nobles, Cursor, Prompt, Request};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color as TermColor, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+use splendor_core::{Card, CardView, Color, ColorVec, GameSnapshot, Noble, Tier};
+
+/// Draw the board and the prompt.
+pub fn draw(frame: &mut Frame, snapshot: &GameSnapshot, prompt: Option<&Prompt>) {
+    let [header, board, players, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(12),
+        Constraint::Min(4),
+        Constraint::Length(3),
+    ])
+    .areas(frame.size());
+
+    let mut status = format!(
+        "Round {} · Player#{} to move",
+        snapshot.current_round + 1,
+        snapshot.current_player
+    );
+    if snapshot.last_round {
+        status.push_str(" · last round");
+    }
+    frame.render_widget(
+        Paragraph::new(status).style(Style::default().add_modifier(Modifier::BOLD)),
+        header,
+    );
+
+    frame.render_widget(
+        Paragraph::new(board_lines(snapshot, prompt))
+            .block(Block::default().borders(Borders::ALL).title(" Board ")),
+        board,
+    );
+    frame.render_widget(
+        Paragraph::new(player_lines(snapshot, prompt))
+            .block(Block::default().borders(Borders::ALL).title(" Players ")),
+        players,
+    );
+
+    let footer_lines = match prompt {
+        Some(prompt) => vec![
+            Line::from(selection(snapshot, prompt)),
+            match &prompt.message {
+                Some(message) => {
+                    Line::styled(message.as_str(), Style::default().fg(TermColor::LightRed))
+                }
+                None => Line::from(""),
+            },
+            Line::styled(prompt.help(), Style::default().fg(TermColor::DarkGray)),
+        ],
+        None => vec![Line::from("Waiting for the other players...")],
+    };
+    frame.render_widget(Paragraph::new(footer_lines), footer);
+}
+
+fn board_lines<'a>(snapshot: &'a GameSnapshot, prompt: Option<&Prompt>) -> Vec<Line<'a>> {
+    let player = snapshot.current_player();
+    let cursor = prompt
+        .filter(|p| p.request == Request::Action)
+        .map(|p| p.cursor);
+    let selected_noble = prompt
+        .filter(|p| p.request == Request::SelectNoble)
+        .map(|p| p.noble);
+    let visitable = visitable_nobles(snapshot);
+
+    let mut lines = vec![];
+    let mut nobles = vec![Span::raw("Nobles  ")];
+    for (idx, noble) in snapshot.nobles.iter().enumerate() {
+        let mut style = Style::default();
+        if visitable.contains(&idx) {
+            style = style.fg(TermColor::LightGreen);
+        }
+        if selected_noble == Some(idx) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        nobles.push(Span::styled(noble_text(noble), style));
+        nobles.push(Span::raw("  "));
+    }
+    lines.push(Line::from(nobles));
+    lines.push(Line::from(
+        [Span::raw("Bank    ")]
+            .into_iter()
+            .chain(token_spans(&snapshot.tokens))
+            .collect::<Vec<_>>(),
+    ));
+    lines.push(Line::from(""));
+
+    for tier in [Tier::III, Tier::II, Tier::I] {
+        let mut spans = vec![Span::raw(format!(
+            "{:<8}",
+            format!("Tier {}", tier as usize + 1)
+        ))];
+        for (idx, card) in snapshot.card_pool.revealed[tier as usize]
+            .iter()
+            .enumerate()
+        {
+            let selected = cursor == Some(Cursor::Revealed { tier, idx });
+            spans.push(card_span(card, player.payment(card).is_some(), selected));
+            spans.push(Span::raw(" "));
+        }
+        let mut style = Style::default().fg(TermColor::DarkGray);
+        if cursor == Some(Cursor::Pool(tier)) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        spans.push(Span::styled(
+            format!("[pool {:>2}]", snapshot.card_pool.remaining[tier as usize]),
+            style,
+        ));
+        lines.push(Line::from(spans));
+        lines.push(Line::from(""));
+    }
+    lines
+}
+
+fn player_lines<'a>(snapshot: &'a GameSnapshot, prompt: Option<&Prompt>) -> Vec<Line<'a>> {
+    let cursor = prompt
+        .filter(|p| p.request == Request::Action)
+        .map(|p| p.cursor);
+    let mut lines = vec![];
+    for player in snapshot.players.iter() {
+        let me = player.idx == snapshot.current_player;
+        let mut spans = vec![Span::styled(
+            format!(
+                "{}#{} {:>2} pts ",
+                if me { ">" } else { " " },
+                player.idx,
+                player.points
+            ),
+            if me {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            },
+        )];
+        spans.push(Span::raw(" tokens "));
+        spans.extend(token_spans(&player.tokens));
+        spans.push(Span::raw(" bonus "));
+        spans.extend(
+            token_spans(&player.development_cards.bonus)
+                .into_iter()
+                .take(5),
+        );
+        spans.push(Span::raw(format!(
+            " nobles {} reserved {}",
+            player.nobles.len(),
+            player.reserved_cards.len()
+        )));
+        lines.push(Line::from(spans));
+
+        if me && !player.reserved_cards.is_empty() {
+            let mut spans = vec![Span::raw("    reserved  ")];
+            for (idx, card) in player.reserved_cards.iter().enumerate() {
+                let selected = cursor == Some(Cursor::Reserved(idx));
+                spans.push(match card {
+                    CardView::Visible(card) => {
+                        card_span(card, player.payment(card).is_some(), selected)
+                    }
+                    CardView::Invisible(tier) => {
+                        Span::raw(format!("[tier {}]", *tier as usize + 1))
+                    }
+                });
+                spans.push(Span::raw(" "));
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+    lines
+}
+
+fn selection(snapshot: &GameSnapshot, prompt: &Prompt) -> Vec<Span<'static>> {
+    match prompt.request {
+        Request::Action => {
+            let mut spans = vec![Span::raw("Your turn, take ")];
+            spans.extend(token_spans(&prompt.tokens).into_iter().take(5));
+            spans
+        }
+        Request::DropTokens => {
+            let excess = snapshot.current_player().tokens.total().saturating_sub(10);
+            let mut spans = vec![Span::raw(format!("Drop {excess} tokens "))];
+            spans.extend(token_spans(&prompt.tokens));
+            spans
+        }
+        Request::SelectNoble => vec![Span::raw("Select the noble to visit")],
+    }
+}
+
+fn token_spans(tokens: &ColorVec) -> Vec<Span<'static>> {
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(i, n)| {
+            let color = Color::try_from(i).unwrap();
+            Span::styled(
+                format!("{}{} ", color.letter(), n),
+                Style::default().fg(term_color(color)),
+            )
+        })
+        .collect()
+}
+
+fn card_span(card: &Card, affordable: bool, selected: bool) -> Span<'static> {
+    let requires = card
+        .requires
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| *n > 0)
+        .map(|(i, n)| format!("{}{}", n, Color::try_from(i).unwrap().letter()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut style = Style::default().fg(term_color(card.bonus));
+    if affordable {
+        style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    }
+    if selected {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    Span::styled(
+        format!(
+            "[{}{} {:<11}]",
+            card.bonus.letter().to_ascii_uppercase(),
+            card.points,
+            requires
+        ),
+        style,
+    )
+}
+
+fn noble_text(noble: &Noble) -> String {
+    let requires = noble
+        .requires
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| *n > 0)
+        .map(|(i, n)| format!("{}{}", n, Color::try_from(i).unwrap().letter()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("<{requires}>")
+}
+
+fn term_color(color: Color) -> TermColor {
+    match color {
+        Color::Black => TermColor::DarkGray,
+        Color::Blue => TermColor::LightBlue,
+        Color::Green => TermColor::LightGreen,
+        Color::Red => TermColor::LightRed,
+        Color::White => TermColor::White,
+        Color::Yellow => TermColor::Yellow,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use splendor_engine::GameState;
+
+    #[test]
+    fn test_draw() {
+        let snapshot = GameState::with_rng(&mut SmallRng::seed_from_u64(42), 4).snapshot();
+        let prompt = Prompt::new(Request::Action, &snapshot);
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &snapshot, Some(&prompt)))
+            .unwrap();
+        let text = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        for expected in ["Round 1", "Tier 3", "Tier 1", "[pool 36]", "#3  0 pts"] {
+            assert!(text.contains(expected), "{expected:?} is not drawn");
+        }
+    }
+}